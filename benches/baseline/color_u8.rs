@@ -0,0 +1,91 @@
+// El Color de antes de pasar a f32: canales sRGB en u8 que, con la corrección gamma, se
+// decodifican a lineal, se operan y se vuelven a codificar en cada operación. Sólo quedan
+// las operaciones que usa benches/color.rs, copiadas tal cual.
+use std::ops::{Add, Mul};
+use std::sync::OnceLock;
+
+const ENCODE_LEVELS: usize = 4096;
+
+static DECODE_TABLE: OnceLock<[f32; 256]> = OnceLock::new();
+static ENCODE_TABLE: OnceLock<Vec<u8>> = OnceLock::new();
+
+fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 { value / 12.92 } else { ((value + 0.055) / 1.055).powf(2.4) }
+}
+
+fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.003_130_8 { value * 12.92 } else { 1.055 * value.powf(1.0 / 2.4) - 0.055 }
+}
+
+fn decode(channel: u8) -> f32 {
+    DECODE_TABLE.get_or_init(|| std::array::from_fn(|i| srgb_to_linear(i as f32 / 255.0)))[channel as usize]
+}
+
+fn encode(linear: f32) -> u8 {
+    let table = ENCODE_TABLE.get_or_init(|| {
+        (0..ENCODE_LEVELS)
+            .map(|i| (linear_to_srgb(i as f32 / (ENCODE_LEVELS - 1) as f32) * 255.0).round() as u8)
+            .collect()
+    });
+    table[(linear.clamp(0.0, 1.0) * (ENCODE_LEVELS - 1) as f32).round() as usize]
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Color {
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Color { r, g, b, a: 255 }
+    }
+
+    pub fn to_hex(self) -> u32 {
+        u32::from_be_bytes([0, self.r, self.g, self.b])
+    }
+
+    pub fn to_linear(self) -> [f32; 3] {
+        [decode(self.r), decode(self.g), decode(self.b)]
+    }
+
+    pub fn from_linear([r, g, b]: [f32; 3]) -> Self {
+        Color::new(encode(r), encode(g), encode(b))
+    }
+
+    pub fn lerp(&self, other: &Color, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        let a = (self.a as f32 + (other.a as f32 - self.a as f32) * t).round() as u8;
+        let ([r0, g0, b0], [r1, g1, b1]) = (self.to_linear(), other.to_linear());
+        Color { a, ..Color::from_linear([r0 + (r1 - r0) * t, g0 + (g1 - g0) * t, b0 + (b1 - b0) * t]) }
+    }
+}
+
+impl Add<Color> for Color {
+    type Output = Color;
+
+    fn add(self, other: Color) -> Color {
+        let ([r0, g0, b0], [r1, g1, b1]) = (self.to_linear(), other.to_linear());
+        Color { a: self.a, ..Color::from_linear([r0 + r1, g0 + g1, b0 + b1]) }
+    }
+}
+
+impl Mul<Color> for Color {
+    type Output = Color;
+
+    fn mul(self, other: Color) -> Color {
+        let ([r0, g0, b0], [r1, g1, b1]) = (self.to_linear(), other.to_linear());
+        Color { a: self.a, ..Color::from_linear([r0 * r1, g0 * g1, b0 * b1]) }
+    }
+}
+
+impl Mul<f32> for Color {
+    type Output = Color;
+
+    fn mul(self, scalar: f32) -> Color {
+        let [r, g, b] = self.to_linear();
+        Color { a: self.a, ..Color::from_linear([r * scalar, g * scalar, b * scalar]) }
+    }
+}
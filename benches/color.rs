@@ -0,0 +1,69 @@
+// Micro-benchmark de la aritmética de color: la misma cuenta de un fragmento iluminado
+// (mezcla de albedo, luz, ambiente y especular, y el pixel final) con el Color actual en f32
+// lineal y con el de antes en u8 sRGB (baseline/color_u8.rs). Se corre con
+// `cargo bench --bench color`.
+#![allow(dead_code)]
+
+// cargo compila los benchmarks con cfg(test) pero sin las funciones #[test], así los imports
+// que sólo usan ésas quedan sin usar en el módulo de prueba de color.rs
+#[allow(unused_imports)]
+#[path = "../src/color.rs"]
+mod color;
+// En un subdirectorio para que cargo no lo tome como otro benchmark
+#[path = "baseline/color_u8.rs"]
+mod color_u8;
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const FRAGMENTS: usize = 800 * 600;
+const RUNS: usize = 40;
+
+// Escribe la cuenta una vez para cada tipo: sólo cambia de qué módulo sale Color
+macro_rules! shade {
+    ($name:ident, $color:ty) => {
+        fn $name(out: &mut [u32]) {
+            let albedo = <$color>::new(200, 120, 60);
+            let other = <$color>::new(40, 90, 180);
+            let light = <$color>::new(255, 244, 234);
+            let ambient = <$color>::new(12, 12, 20);
+            for (i, pixel) in out.iter_mut().enumerate() {
+                let t = (i % 97) as f32 / 96.0;
+                let diffuse = (i % 89) as f32 / 88.0;
+                let specular = ((i % 13) as f32 / 12.0).powi(8);
+                let base = albedo.lerp(&other, t);
+                *pixel = (base * light * diffuse + ambient + light * specular).to_hex();
+            }
+        }
+    };
+}
+
+shade!(shade_f32, color::Color);
+shade!(shade_u8, color_u8::Color);
+
+// Mediana de `RUNS` pasadas de cada versión, alternadas para repartir el ruido de la máquina
+fn measure(out: &mut [u32], shades: [fn(&mut [u32]); 2]) -> [Duration; 2] {
+    let mut times = [Vec::with_capacity(RUNS), Vec::with_capacity(RUNS)];
+    for _ in 0..RUNS {
+        for (shade, times) in shades.iter().zip(times.iter_mut()) {
+            let start = Instant::now();
+            shade(black_box(&mut *out));
+            times.push(start.elapsed());
+        }
+    }
+    times.map(|mut times| {
+        times.sort();
+        times[RUNS / 2]
+    })
+}
+
+fn main() {
+    let mut out = vec![0u32; FRAGMENTS];
+    // Calentamiento (también llena las tablas perezosas de la versión u8)
+    measure(&mut out, [shade_f32, shade_u8]);
+
+    let [new, old] = measure(&mut out, [shade_f32, shade_u8]);
+    let per_fragment = |time: Duration| time.as_nanos() as f64 / FRAGMENTS as f64;
+    println!("f32 lineal: {:>8.3} ms ({:.2} ns/fragmento)", new.as_secs_f64() * 1e3, per_fragment(new));
+    println!("u8 sRGB:    {:>8.3} ms ({:.2} ns/fragmento)", old.as_secs_f64() * 1e3, per_fragment(old));
+}
@@ -0,0 +1,87 @@
+// Micro-benchmark de la escritura de fragmentos: llena todo un framebuffer de 800x600 con el
+// par set_current_color + point (el camino viejo) y con set_pixel. Se corre con
+// `cargo bench --bench framebuffer`.
+#![allow(dead_code)]
+
+// cargo compila los benchmarks con cfg(test) pero sin las funciones #[test], así los imports
+// que sólo usan ésas quedan sin usar en los módulos de prueba de color.rs y triangle.rs
+#[allow(unused_imports)]
+#[path = "../src/color.rs"]
+mod color;
+#[path = "../src/framebuffer.rs"]
+mod framebuffer;
+#[path = "../src/text.rs"]
+mod text;
+// Sólo los usan las pruebas de framebuffer.rs, que cargo bench también compila
+#[cfg(test)]
+#[path = "../src/fragments.rs"]
+mod fragments;
+#[cfg(test)]
+#[allow(unused_imports)]
+#[path = "../src/triangle.rs"]
+mod triangle;
+#[cfg(test)]
+#[path = "../src/vertex.rs"]
+mod vertex;
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+use framebuffer::Framebuffer;
+
+const WIDTH: usize = 800;
+const HEIGHT: usize = 600;
+const RUNS: usize = 60;
+
+// Mediana de `RUNS` llenados con cada función; se alternan para que el ruido de la máquina
+// afecte a las dos por igual, y cada llenado empieza con el framebuffer limpio, fuera de la
+// medición
+fn measure(framebuffer: &mut Framebuffer, fills: [fn(&mut Framebuffer); 2]) -> [Duration; 2] {
+    let mut times = [Vec::with_capacity(RUNS), Vec::with_capacity(RUNS)];
+    for _ in 0..RUNS {
+        for (fill, times) in fills.iter().zip(times.iter_mut()) {
+            framebuffer.clear();
+            let start = Instant::now();
+            fill(black_box(&mut *framebuffer));
+            times.push(start.elapsed());
+        }
+    }
+    times.map(|mut times| {
+        times.sort();
+        times[RUNS / 2]
+    })
+}
+
+// El color y la profundidad cambian por pixel, como en el loop de fragmentos
+fn fragment(x: usize, y: usize) -> (f32, u32) {
+    (((x ^ y) & 0xFF) as f32 / 256.0, (x as u32) << 8 | y as u32)
+}
+
+fn with_current_color(framebuffer: &mut Framebuffer) {
+    for y in 0..HEIGHT {
+        for x in 0..WIDTH {
+            let (depth, color) = fragment(x, y);
+            framebuffer.set_current_color(color);
+            framebuffer.point(x, y, depth);
+        }
+    }
+}
+
+fn with_set_pixel(framebuffer: &mut Framebuffer) {
+    for y in 0..HEIGHT {
+        for x in 0..WIDTH {
+            let (depth, color) = fragment(x, y);
+            framebuffer.set_pixel(x, y, depth, color);
+        }
+    }
+}
+
+fn main() {
+    let mut framebuffer = Framebuffer::new(WIDTH, HEIGHT);
+    // Calentamiento
+    measure(&mut framebuffer, [with_current_color, with_set_pixel]);
+
+    let [old, new] = measure(&mut framebuffer, [with_current_color, with_set_pixel]);
+    let per_pixel = |time: Duration| time.as_nanos() as f64 / (WIDTH * HEIGHT) as f64;
+    println!("set_current_color + point: {:>8.3} ms ({:.2} ns/pixel)", old.as_secs_f64() * 1e3, per_pixel(old));
+    println!("set_pixel:                 {:>8.3} ms ({:.2} ns/pixel)", new.as_secs_f64() * 1e3, per_pixel(new));
+}
@@ -0,0 +1,143 @@
+use std::f32::consts::TAU;
+use nalgebra_glm::{Vec2, Vec3};
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use crate::scene::BeltConfig;
+
+// Asteroides más cercanos a la cámara que se dibujan con malla; el resto son puntos
+pub const NEAR_ASTEROIDS: usize = 50;
+// Puestos de más que un asteroide con malla puede alejarse antes de volver a ser un punto.
+// Evita que uno que está en el límite cambie de forma en cada frame.
+const NEAR_HYSTERESIS: usize = 10;
+// Giro máximo de cada roca sobre los ejes X e Y, en radianes por paso
+const MAX_SPIN: f32 = 0.05;
+
+// Roca en una órbita circular alrededor del origen
+struct Asteroid {
+    orbit_radius: f32,
+    speed: f32,
+    phase: f32,
+    inclination: f32,
+    // Dirección (ángulo en la eclíptica) de la línea donde la órbita cruza la eclíptica
+    node: f32,
+    radius: f32,
+    spin_phase: Vec2,
+    spin_speed: Vec2,
+}
+
+impl Asteroid {
+    fn position_at(&self, time: f32) -> Vec3 {
+        let angle = self.phase + time * self.speed;
+        let (x, z) = (self.orbit_radius * angle.cos(), self.orbit_radius * angle.sin());
+        // Se inclina la órbita alrededor del eje X y luego se gira la línea de nodos
+        let (sin_i, cos_i) = self.inclination.sin_cos();
+        let (y, z) = (-z * sin_i, z * cos_i);
+        let (sin_n, cos_n) = self.node.sin_cos();
+        Vec3::new(x * cos_n + z * sin_n, y, -x * sin_n + z * cos_n)
+    }
+}
+
+/// Cinturón de asteroides generado a partir de una semilla. Cada frame `update` los mueve y
+/// elige los `NEAR_ASTEROIDS` más cercanos a la cámara, que se dibujan como malla; los demás
+/// se dibujan como puntos del tamaño que tendrían en pantalla.
+pub struct AsteroidBelt {
+    asteroids: Vec<Asteroid>,
+    positions: Vec<Vec3>,
+    near: Vec<usize>,
+    is_near: Vec<bool>,
+}
+
+impl AsteroidBelt {
+    pub fn new(config: &BeltConfig) -> Self {
+        let mut rng = StdRng::seed_from_u64(config.seed);
+        let (inner, outer) = (config.inner_radius, config.outer_radius);
+        let max_inclination = config.max_inclination.to_radians();
+        let asteroids: Vec<Asteroid> = (0..config.count)
+            .map(|_| {
+                // Uniforme en área: hay más rocas en la parte exterior de la banda
+                let orbit_radius = (inner * inner + (outer * outer - inner * inner) * rng.gen_range(0.0..=1.0f32)).sqrt();
+                Asteroid {
+                    orbit_radius,
+                    // Tercera ley de Kepler: las de afuera van más lento
+                    speed: config.orbit_speed * (inner / orbit_radius).powf(1.5),
+                    phase: rng.gen_range(0.0..TAU),
+                    inclination: rng.gen_range(-max_inclination..=max_inclination),
+                    node: rng.gen_range(0.0..TAU),
+                    // Hay muchas más rocas chicas que grandes
+                    radius: config.min_size + (config.max_size - config.min_size) * rng.gen_range(0.0..=1.0f32).powi(3),
+                    spin_phase: Vec2::new(rng.gen_range(0.0..TAU), rng.gen_range(0.0..TAU)),
+                    spin_speed: Vec2::new(rng.gen_range(-MAX_SPIN..=MAX_SPIN), rng.gen_range(-MAX_SPIN..=MAX_SPIN)),
+                }
+            })
+            .collect();
+        let count = asteroids.len();
+        AsteroidBelt { asteroids, positions: vec![Vec3::zeros(); count], near: Vec::new(), is_near: vec![false; count] }
+    }
+
+    /// Mueve los asteroides al frame `time` y vuelve a elegir los cercanos a la cámara en `eye`
+    /// que mira hacia `forward`; los que quedan detrás no cuentan, no se verían
+    pub fn update(&mut self, time: f32, eye: Vec3, forward: Vec3) {
+        for (position, asteroid) in self.positions.iter_mut().zip(&self.asteroids) {
+            *position = asteroid.position_at(time);
+        }
+
+        // Candidatos: los más cercanos, con el margen de la histéresis
+        let distance = |index: &usize| (self.positions[*index] - eye).magnitude_squared();
+        let mut order: Vec<usize> = (0..self.asteroids.len())
+            .filter(|&index| (self.positions[index] - eye).dot(&forward) > -self.asteroids[index].radius)
+            .collect();
+        let candidates = (NEAR_ASTEROIDS + NEAR_HYSTERESIS).min(order.len());
+        if candidates < order.len() {
+            order.select_nth_unstable_by(candidates, |a, b| distance(a).total_cmp(&distance(b)));
+        }
+        let candidates = &mut order[..candidates];
+        candidates.sort_by(|a, b| distance(a).total_cmp(&distance(b)));
+
+        // Siguen con malla los que ya la tenían y no se alejaron del margen; el resto de los
+        // puestos se llena por cercanía
+        let kept: Vec<usize> = self.near.iter().copied().filter(|index| candidates.contains(index)).collect();
+        let mut near = kept.clone();
+        for &index in candidates.iter() {
+            if near.len() >= NEAR_ASTEROIDS {
+                break;
+            }
+            if !kept.contains(&index) {
+                near.push(index);
+            }
+        }
+
+        for &index in &self.near {
+            self.is_near[index] = false;
+        }
+        for &index in &near {
+            self.is_near[index] = true;
+        }
+        self.near = near;
+    }
+
+    /// Los que se dibujan con malla en este frame
+    pub fn near(&self) -> &[usize] {
+        &self.near
+    }
+
+    /// Los que se dibujan como puntos en este frame
+    pub fn far(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.asteroids.len()).filter(|&index| !self.is_near[index])
+    }
+
+    pub fn position(&self, index: usize) -> Vec3 {
+        self.positions[index]
+    }
+
+    /// Radio de la roca en el mundo
+    pub fn radius(&self, index: usize) -> f32 {
+        self.asteroids[index].radius
+    }
+
+    /// Rotación (ángulos en X e Y, como la de create_model_matrix) con la que gira la roca
+    pub fn rotation(&self, index: usize, time: f32) -> Vec3 {
+        let asteroid = &self.asteroids[index];
+        let angles = asteroid.spin_phase + asteroid.spin_speed * time;
+        Vec3::new(angles.x.rem_euclid(TAU), angles.y.rem_euclid(TAU), 0.0)
+    }
+}
@@ -1,64 +1,598 @@
-use nalgebra_glm::{Vec3, rotate_vec3};
+use std::fs;
+use std::io;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use nalgebra_glm::{
+  Vec3, Mat3, Quat, rotate_vec3, lerp, quat_angle_axis, quat_rotate_vec3, quat_normalize, mat3_to_quat,
+};
 
+// Distancia mínima entre eye y center al hacer zoom, para no atravesar el foco
+const MIN_ZOOM_DISTANCE: f32 = 1.0;
+// Fracción de la distancia recorrida por cada paso de la rueda
+const ZOOM_RATE: f32 = 0.1;
+
+// Inclinación máxima respecto al horizonte, para no voltear la cámara sobre el vector up
+const MAX_PITCH: f32 = 89.0 * std::f32::consts::PI / 180.0;
+
+// Límites del multiplicador de sensibilidad de rotación
+const MIN_SENSITIVITY: f32 = 0.1;
+const MAX_SENSITIVITY: f32 = 5.0;
+
+// Distancia mínima al objetivo en modo órbita
+const MIN_ORBIT_RADIUS: f32 = 1.0;
+// Rapidez (por segundo) con la que la cámara de seguimiento se acerca a la pose deseada: en
+// cada frame recorre 1 - e^(-FOLLOW_RATE * delta) del camino, lo mismo a cualquier FPS (a 60
+// FPS es un 8% por frame)
+const FOLLOW_RATE: f32 = 5.0;
+
+// Vuelo animado de la cámara hacia un planeta. El destino se recalcula cada frame con
+// la posición actual del planeta, así se llega a donde está y no a donde estaba.
+pub struct CameraAnimation {
+  start_eye: Vec3,
+  start_center: Vec3,
+  pub target_index: usize,
+  // Ojo relativo al planeta al llegar
+  end_offset: Vec3,
+  started: Instant,
+  duration: Duration,
+  easing: fn(f32) -> f32,
+}
+
+impl CameraAnimation {
+  // Avance de 0 a 1 según el tiempo real transcurrido
+  fn progress(&self) -> f32 {
+    (self.started.elapsed().as_secs_f32() / self.duration.as_secs_f32()).min(1.0)
+  }
+}
+
+pub fn ease_in_out_cubic(t: f32) -> f32 {
+  if t < 0.5 {
+    4.0 * t * t * t
+  } else {
+    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CameraMode {
+  Free,
+  // Gira alrededor del planeta `target_index` de la lista de planetas
+  Orbit { target_index: usize },
+  // Persigue al planeta `target_index` desde atrás de su dirección de movimiento
+  Follow { target_index: usize },
+}
+
+// La cámara guarda su posición y una orientación en cuaternión. Los ejes se derivan de ella:
+// en espacio local la cámara mira hacia -Z, con +Y arriba y +X a la derecha.
 pub struct Camera {
   pub eye: Vec3,
-  pub center: Vec3,
-  pub up: Vec3,
-  pub has_changed: bool
+  orientation: Quat,
+  // Distancia al punto de enfoque (center) sobre la dirección de vista
+  focus_distance: f32,
+  // En el modo nivelado la orientación se reconstruye desde estos ángulos, sin acumular error
+  yaw: f32,
+  pitch: f32,
+  roll: f32,
+  pub has_changed: bool,
+  pub mode: CameraMode,
+  // Vector objetivo -> ojo mientras se orbita (su largo es el radio de la órbita)
+  orbit_offset: Vec3,
+  // Pose de la cámara libre guardada al entrar en modo órbita o seguimiento
+  free_pose: Option<(Vec3, Vec3)>,
+  pub animation: Option<CameraAnimation>,
+  // Sin límite de pitch y con rotaciones sobre los ejes locales (vuelo espacial)
+  pub six_dof: bool,
+  // Multiplicador de la rotación por teclado y mouse
+  pub sensitivity: f32,
+  // Se puede desactivar para explorar libremente (atravesando planetas)
+  pub collisions: bool,
 }
 
 impl Camera {
   pub fn new(eye: Vec3, center: Vec3, up: Vec3) -> Self {
-    Camera {
+    let mut camera = Camera {
       eye,
-      center,
-      up,
+      orientation: Quat::identity(),
+      focus_distance: (center - eye).magnitude(),
+      yaw: 0.0,
+      pitch: 0.0,
+      roll: 0.0,
       has_changed: true,
+      mode: CameraMode::Free,
+      orbit_offset: eye - center,
+      free_pose: None,
+      animation: None,
+      six_dof: false,
+      sensitivity: 1.0,
+      collisions: true,
+    };
+    camera.orientation = basis_to_quat(&(center - eye), &up);
+    camera.look_at(center);
+    camera
+  }
+
+  // Multiplica la sensibilidad por `factor`, dentro de los límites
+  pub fn adjust_sensitivity(&mut self, factor: f32) {
+    self.sensitivity = (self.sensitivity * factor).clamp(MIN_SENSITIVITY, MAX_SENSITIVITY);
+  }
+
+  pub fn orientation(&self) -> Quat {
+    self.orientation
+  }
+
+  pub fn forward(&self) -> Vec3 {
+    quat_rotate_vec3(&self.orientation, &Vec3::new(0.0, 0.0, -1.0))
+  }
+
+  pub fn right(&self) -> Vec3 {
+    quat_rotate_vec3(&self.orientation, &Vec3::new(1.0, 0.0, 0.0))
+  }
+
+  pub fn up(&self) -> Vec3 {
+    quat_rotate_vec3(&self.orientation, &Vec3::new(0.0, 1.0, 0.0))
+  }
+
+  // Punto de enfoque: sobre la dirección de vista, a `focus_distance` del ojo
+  pub fn center(&self) -> Vec3 {
+    self.eye + self.forward() * self.focus_distance
+  }
+
+  // Giro horizontal: alrededor de +Y del mundo, o del eje up local en modo 6DOF
+  pub fn yaw(&mut self, angle: f32) {
+    if self.six_dof {
+      self.rotate_local(angle, &Vec3::new(0.0, 1.0, 0.0));
+    } else {
+      self.yaw += angle;
+      self.rebuild_orientation();
     }
   }
 
-  pub fn move_center(&mut self, direction: Vec3) {
-    let radius_vector = self.center - self.eye;
-    let radius = radius_vector.magnitude();
+  // Inclinación alrededor del eje derecho; limitada a ±MAX_PITCH salvo en modo 6DOF
+  pub fn pitch(&mut self, angle: f32) {
+    if self.six_dof {
+      self.rotate_local(angle, &Vec3::new(1.0, 0.0, 0.0));
+    } else {
+      self.pitch = (self.pitch + angle).clamp(-MAX_PITCH, MAX_PITCH);
+      self.rebuild_orientation();
+    }
+  }
 
-    let angle_x = direction.x * 0.05; // Adjust this factor to control rotation speed
-    let angle_y = direction.y * 0.05;
+  // Alabeo alrededor de la dirección de vista
+  pub fn roll(&mut self, angle: f32) {
+    if self.six_dof {
+      self.rotate_local(angle, &Vec3::new(0.0, 0.0, -1.0));
+    } else {
+      self.roll += angle;
+      self.rebuild_orientation();
+    }
+  }
 
-    let rotated = rotate_vec3(&radius_vector, angle_x, &Vec3::new(0.0, 1.0, 0.0));
+  // Traslada el ojo en ejes de la cámara (x: derecha, y: arriba, z: adelante)
+  pub fn translate_local(&mut self, offset: Vec3) {
+    self.eye += self.right() * offset.x + self.up() * offset.y + self.forward() * offset.z;
+    self.has_changed = true;
+  }
 
-    let right = rotated.cross(&self.up).normalize();
-    let final_rotated = rotate_vec3(&rotated, angle_y, &right);
+  fn rotate_local(&mut self, angle: f32, axis: &Vec3) {
+    self.orientation = quat_normalize(&(self.orientation * quat_angle_axis(angle, axis)));
+    self.has_changed = true;
+  }
 
-    self.center = self.eye + final_rotated.normalize() * radius;
+  fn rebuild_orientation(&mut self) {
+    self.orientation = quat_angle_axis(self.yaw, &Vec3::new(0.0, 1.0, 0.0))
+      * quat_angle_axis(self.pitch, &Vec3::new(1.0, 0.0, 0.0))
+      * quat_angle_axis(self.roll, &Vec3::new(0.0, 0.0, -1.0));
     self.has_changed = true;
   }
 
-  pub fn move_ship(&mut self, direction: Vec3) {
-    let forward = (self.center - self.eye).normalize(); // Dirección en la que la cámara está mirando
-    let right = forward.cross(&self.up).normalize(); // Dirección lateral (perpendicular a 'forward' y 'up')
-    let up = right.cross(&forward).normalize(); // Dirección hacia arriba
+  // Coloca la cámara en una pose completa; en el modo nivelado el alabeo se deduce de `up`
+  pub fn set_pose(&mut self, eye: Vec3, center: Vec3, up: Vec3) {
+    self.eye = eye;
+    let Some(forward) = (center - eye).try_normalize(f32::EPSILON) else { return };
+    if self.six_dof {
+      self.orientation = basis_to_quat(&forward, &up);
+      self.focus_distance = (center - eye).magnitude();
+      self.has_changed = true;
+    } else {
+      let level_up = quat_rotate_vec3(&basis_to_quat(&forward, &world_up()), &Vec3::new(0.0, 1.0, 0.0));
+      self.roll = forward.dot(&level_up.cross(&up)).atan2(level_up.dot(&up));
+      self.look_at(center);
+    }
+  }
+
+  // Orienta la cámara hacia `target` sin mover el ojo (el alabeo se conserva)
+  pub fn look_at(&mut self, target: Vec3) {
+    let direction = target - self.eye;
+    let Some(forward) = direction.try_normalize(f32::EPSILON) else { return };
+    self.focus_distance = direction.magnitude();
+
+    if self.six_dof {
+      self.orientation = basis_to_quat(&forward, &self.up());
+      self.has_changed = true;
+    } else {
+      self.yaw = (-forward.x).atan2(-forward.z);
+      self.pitch = forward.y.clamp(-1.0, 1.0).asin().clamp(-MAX_PITCH, MAX_PITCH);
+      self.rebuild_orientation();
+    }
+  }
+
+  // Al salir del modo 6DOF la cámara vuelve a nivelarse con el horizonte
+  pub fn toggle_six_dof(&mut self) {
+    let center = self.center();
+    self.six_dof = !self.six_dof;
+    self.roll = 0.0;
+    self.look_at(center);
+  }
+
+  // Vuela hacia un planeta durante `duration`; al llegar queda orbitándolo a `radius`
+  pub fn warp_to(&mut self, target_index: usize, target: Vec3, radius: f32, duration: Duration) {
+    self.save_free_pose();
+    self.mode = CameraMode::Free;
+
+    let direction = (self.eye - target).try_normalize(f32::EPSILON).unwrap_or(Vec3::new(0.0, 0.0, 1.0));
+    self.animation = Some(CameraAnimation {
+      start_eye: self.eye,
+      start_center: self.center(),
+      target_index,
+      end_offset: direction * radius.max(MIN_ORBIT_RADIUS),
+      started: Instant::now(),
+      duration,
+      easing: ease_in_out_cubic,
+    });
+  }
+
+  // Avanza la animación activa hacia la posición actual del objetivo
+  pub fn update_animation(&mut self, target: Vec3) {
+    let Some(animation) = &self.animation else { return };
+    let progress = animation.progress();
+    let t = (animation.easing)(progress);
+
+    let eye = lerp(&animation.start_eye, &(target + animation.end_offset), t);
+    let center = lerp(&animation.start_center, &target, t);
+
+    if progress >= 1.0 {
+      self.mode = CameraMode::Orbit { target_index: animation.target_index };
+      self.orbit_offset = animation.end_offset;
+      self.animation = None;
+    }
+    self.eye = eye;
+    self.look_at(center);
+  }
+
+  // Cualquier entrada manual cancela el vuelo y deja la cámara libre donde esté
+  pub fn cancel_animation(&mut self) {
+    if self.animation.take().is_some() {
+      self.mode = CameraMode::Free;
+    }
+  }
+
+  // Empieza a orbitar un planeta a la distancia dada, manteniendo el lado desde el que se mira
+  pub fn orbit(&mut self, target_index: usize, target: Vec3, radius: f32) {
+    self.animation = None;
+    self.save_free_pose();
+    self.mode = CameraMode::Orbit { target_index };
 
-    // Mover la cámara en función de la entrada
-    let movement = right * direction.x + up * direction.y + forward * direction.z;
-    self.eye += movement;
-    self.center += movement;
+    let direction = (self.eye - target).try_normalize(f32::EPSILON).unwrap_or(Vec3::new(0.0, 0.0, 1.0));
+    self.orbit_offset = direction * radius.max(MIN_ORBIT_RADIUS);
+    self.anchor_orbit(target);
+  }
+
+  // Empieza a perseguir un planeta; la pose se ajusta cada frame con `follow`
+  pub fn chase(&mut self, target_index: usize) {
+    self.animation = None;
+    self.save_free_pose();
+    self.mode = CameraMode::Follow { target_index };
+  }
+
+  fn save_free_pose(&mut self) {
+    if self.mode == CameraMode::Free {
+      self.free_pose = Some((self.eye, self.center()));
+    }
+  }
+
+  // Acerca suavemente eye y center a la pose detrás del objetivo (suavizado exponencial por
+  // `delta` segundos), así que cambiar de objetivo en pleno vuelo se desliza en lugar de
+  // teletransportar
+  pub fn follow(&mut self, target_pos: Vec3, velocity_dir: Vec3, distance: f32, height: f32, delta: f32) {
+    let desired_eye = target_pos - velocity_dir * distance + world_up() * height;
+    let center = self.center();
+    let blend = 1.0 - (-FOLLOW_RATE * delta).exp();
 
+    self.eye += (desired_eye - self.eye) * blend;
+    self.look_at(center + (target_pos - center) * blend);
+  }
+
+  // Vuelve a la cámara libre restaurando la pose que tenía antes de orbitar o seguir
+  pub fn free(&mut self) {
+    self.animation = None;
+    if let Some((eye, center)) = self.free_pose.take() {
+      self.eye = eye;
+      self.look_at(center);
+    }
+    self.mode = CameraMode::Free;
     self.has_changed = true;
   }
 
+  // Los planetas se mueven: cada frame el ojo se recoloca respecto a la posición actual del objetivo
+  pub fn anchor_orbit(&mut self, target: Vec3) {
+    self.eye = target + self.orbit_offset;
+    self.look_at(target);
+  }
+
+  // Gira el ojo alrededor del objetivo (x: alrededor de Y, y: inclinación)
+  pub fn orbit_rotate(&mut self, direction: Vec3) {
+    let center = self.center();
+    let rate = 0.05 * self.sensitivity;
+    let forward = -self.orbit_offset;
+    let yawed = rotate_vec3(&forward, direction.x * rate, &world_up());
+
+    // Mirando justo hacia arriba o abajo el producto cruz se anula; sirve el eje de la cámara
+    let right = yawed.cross(&world_up()).try_normalize(f32::EPSILON).unwrap_or_else(|| self.right());
+    let pitch = clamp_pitch(&yawed, &world_up(), direction.y * rate);
+    self.orbit_offset = -rotate_vec3(&yawed, pitch, &right);
+    self.anchor_orbit(center);
+  }
+
+  pub fn move_center(&mut self, direction: Vec3) {
+    let rate = 0.05 * self.sensitivity;
+    self.yaw(direction.x * rate);
+    self.pitch(direction.y * rate);
+  }
+
+  // Acerca (delta > 0) o aleja el ojo sobre la dirección eye -> center. El paso es
+  // proporcional a la distancia actual, así que se siente igual cerca de una luna
+  // que lejos en el sistema.
+  pub fn zoom(&mut self, delta: f32) {
+    if let CameraMode::Orbit { .. } = self.mode {
+      let center = self.center();
+      let radius = (self.orbit_offset.magnitude() * (-delta * ZOOM_RATE).exp()).max(MIN_ORBIT_RADIUS);
+      self.orbit_offset = self.orbit_offset.normalize() * radius;
+      self.anchor_orbit(center);
+      return;
+    }
+
+    if self.focus_distance == 0.0 {
+      return;
+    }
+
+    let center = self.center();
+    self.focus_distance = (self.focus_distance * (-delta * ZOOM_RATE).exp()).max(MIN_ZOOM_DISTANCE);
+    self.eye = center - self.forward() * self.focus_distance;
+    self.has_changed = true;
+  }
+
+  pub fn move_ship(&mut self, direction: Vec3) {
+    self.translate_local(direction);
+  }
+
+  // Saca el ojo de cualquier esfera en la que haya entrado, proyectándolo sobre su superficie.
+  // Como sólo se elimina la componente que entra en la esfera, el movimiento se desliza
+  // por la superficie en lugar de detenerse en seco.
+  pub fn resolve_collisions(&mut self, colliders: &[Collider]) {
+    if !self.collisions {
+      return;
+    }
+
+    for collider in colliders {
+      let offset = self.eye - collider.center;
+      let distance = offset.magnitude();
+      if distance < collider.radius {
+        let normal = offset.try_normalize(f32::EPSILON).unwrap_or(-self.forward());
+        self.eye = collider.center + normal * collider.radius;
+        self.has_changed = true;
+      }
+    }
+  }
+
   // Gira la nave para que siempre mire hacia atrás con respecto a la cámara
   pub fn rotate_ship(&mut self, direction: Vec3) {
-    let forward = (self.center - self.eye).normalize();
-    
-    // Rotación alrededor del eje Y (horizontal) para el movimiento lateral
-    let rotated_forward = rotate_vec3(&forward, direction.x * 0.05, &self.up);
-    
-    // Rotación alrededor del eje X (vertical) para el movimiento hacia arriba/abajo
-    let right_axis = rotated_forward.cross(&self.up).normalize();
-    let final_rotated = rotate_vec3(&rotated_forward, direction.y * 0.05, &right_axis);
-    
-    // Actualizamos el centro de la nave (solo la orientación de la nave, no la cámara)
-    self.center = self.eye + final_rotated * (self.center - self.eye).magnitude();
-    self.has_changed = true;
+    self.move_center(direction);
+  }
+}
+
+// Esfera que el ojo de la cámara no puede atravesar
+#[derive(Debug, Clone, Copy)]
+pub struct Collider {
+  pub center: Vec3,
+  pub radius: f32,
+}
+
+// Intervalo entre keyframes al grabar una ruta
+const KEYFRAME_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Keyframe {
+  // Segundos desde el inicio de la grabación
+  pub time: f32,
+  pub eye: Vec3,
+  pub center: Vec3,
+  pub up: Vec3,
+}
+
+// Ruta de cámara grabada para reproducirla en videos de demostración
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Path {
+  pub keyframes: Vec<Keyframe>,
+}
+
+impl Path {
+  pub fn duration(&self) -> f32 {
+    self.keyframes.last().map_or(0.0, |keyframe| keyframe.time)
+  }
+
+  // Pose (eye, center, up) en `time`, interpolada con Catmull-Rom entre keyframes
+  pub fn sample(&self, time: f32) -> Option<(Vec3, Vec3, Vec3)> {
+    let last = self.keyframes.len().checked_sub(1)?;
+    let segment = self.keyframes.iter().rposition(|keyframe| keyframe.time <= time).unwrap_or(0).min(last.saturating_sub(1));
+
+    let k1 = &self.keyframes[segment];
+    let k2 = &self.keyframes[(segment + 1).min(last)];
+    // En los extremos se usan puntos fantasma reflejados para que la curva no se frene ahí
+    let k0 = (segment > 0).then(|| &self.keyframes[segment - 1]);
+    let k3 = (segment + 2 <= last).then(|| &self.keyframes[segment + 2]);
+
+    let span = k2.time - k1.time;
+    let t = if span > 0.0 { ((time - k1.time) / span).clamp(0.0, 1.0) } else { 0.0 };
+
+    let spline = |value: fn(&Keyframe) -> Vec3| {
+      let (p1, p2) = (value(k1), value(k2));
+      let p0 = k0.map_or(p1 * 2.0 - p2, value);
+      let p3 = k3.map_or(p2 * 2.0 - p1, value);
+      catmull_rom(&p0, &p1, &p2, &p3, t)
+    };
+
+    let eye = spline(|keyframe| keyframe.eye);
+    let center = spline(|keyframe| keyframe.center);
+    let up = spline(|keyframe| keyframe.up).try_normalize(f32::EPSILON).unwrap_or(k1.up);
+    Some((eye, center, up))
+  }
+
+  pub fn save(&self, file: &str) -> io::Result<()> {
+    let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+    fs::write(file, json)
+  }
+
+  pub fn load(file: &str) -> io::Result<Path> {
+    let json = fs::read_to_string(file)?;
+    serde_json::from_str(&json).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+  }
+}
+
+// Graba un keyframe cada KEYFRAME_INTERVAL con la pose actual de la cámara
+pub struct PathRecorder {
+  path: Path,
+  started: Instant,
+  last_keyframe: Option<Instant>,
+}
+
+impl PathRecorder {
+  pub fn new() -> Self {
+    PathRecorder { path: Path::default(), started: Instant::now(), last_keyframe: None }
+  }
+
+  // Se llama después de procesar la entrada, así durante un vuelo se guarda la pose animada
+  pub fn update(&mut self, camera: &Camera) {
+    let now = Instant::now();
+    if self.last_keyframe.is_some_and(|last| now - last < KEYFRAME_INTERVAL) {
+      return;
+    }
+    self.last_keyframe = Some(now);
+    self.path.keyframes.push(Keyframe {
+      time: (now - self.started).as_secs_f32(),
+      eye: camera.eye,
+      center: camera.center(),
+      up: camera.up(),
+    });
+  }
+
+  pub fn finish(self) -> Path {
+    self.path
+  }
+}
+
+// Reproduce una ruta con el tiempo real transcurrido desde que empezó
+pub struct PathPlayback {
+  path: Path,
+  started: Instant,
+}
+
+impl PathPlayback {
+  pub fn new(path: Path) -> Self {
+    PathPlayback { path, started: Instant::now() }
+  }
+
+  // Aplica la pose actual a la cámara; devuelve false cuando la ruta terminó
+  pub fn update(&self, camera: &mut Camera) -> bool {
+    let time = self.started.elapsed().as_secs_f32();
+    if let Some((eye, center, up)) = self.path.sample(time.min(self.path.duration())) {
+      camera.set_pose(eye, center, up);
+    }
+    time < self.path.duration()
+  }
+
+  pub fn into_path(self) -> Path {
+    self.path
+  }
+}
+
+fn catmull_rom(p0: &Vec3, p1: &Vec3, p2: &Vec3, p3: &Vec3, t: f32) -> Vec3 {
+  let t2 = t * t;
+  let t3 = t2 * t;
+  (p1 * 2.0 + (p2 - p0) * t + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * t2 + (-p0 + p1 * 3.0 - p2 * 3.0 + p3) * t3) * 0.5
+}
+
+fn world_up() -> Vec3 {
+  Vec3::new(0.0, 1.0, 0.0)
+}
+
+// Cuaternión que lleva -Z local a `forward` y +Y local lo más cerca posible de `up`
+fn basis_to_quat(forward: &Vec3, up: &Vec3) -> Quat {
+  let forward = forward.normalize();
+  let right = forward.cross(up).try_normalize(f32::EPSILON).unwrap_or(Vec3::new(1.0, 0.0, 0.0));
+  let up = right.cross(&forward);
+  mat3_to_quat(&Mat3::from_columns(&[right, up, -forward]))
+}
+
+// Recorta el ángulo de pitch para que la dirección no pase de ±MAX_PITCH sobre el horizonte
+fn clamp_pitch(forward: &Vec3, up: &Vec3, angle: f32) -> f32 {
+  let current = forward.normalize().dot(&up.normalize()).clamp(-1.0, 1.0).asin();
+  (current + angle).clamp(-MAX_PITCH, MAX_PITCH) - current
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use rand::{Rng, SeedableRng};
+  use rand::rngs::StdRng;
+
+  const EPSILON: f32 = 1e-4;
+
+  // Los tres ejes deben seguir siendo unitarios y perpendiculares entre sí
+  fn assert_orthonormal(camera: &Camera) {
+    let (forward, right, up) = (camera.forward(), camera.right(), camera.up());
+    for axis in [forward, right, up] {
+      assert!((axis.magnitude() - 1.0).abs() < EPSILON, "eje no unitario: {axis:?}");
+    }
+    assert!(forward.dot(&right).abs() < EPSILON);
+    assert!(forward.dot(&up).abs() < EPSILON);
+    assert!(right.dot(&up).abs() < EPSILON);
+    // Base derecha: right x up = -forward
+    assert!((right.cross(&up) + forward).magnitude() < EPSILON);
+  }
+
+  #[test]
+  fn basis_stays_orthonormal_under_random_rotations() {
+    let mut rng = StdRng::seed_from_u64(22);
+    for six_dof in [false, true] {
+      let mut camera = Camera::new(Vec3::new(0.0, 0.0, 10.0), Vec3::zeros(), world_up());
+      camera.six_dof = six_dof;
+      for _ in 0..10_000 {
+        let angle = rng.gen_range(-0.2..0.2);
+        match rng.gen_range(0..3) {
+          0 => camera.yaw(angle),
+          1 => camera.pitch(angle),
+          _ => camera.roll(angle),
+        }
+        assert_orthonormal(&camera);
+      }
+    }
+  }
+
+  #[test]
+  fn full_turn_of_yaw_returns_to_start() {
+    for six_dof in [false, true] {
+      let mut camera = Camera::new(Vec3::new(3.0, 2.0, 10.0), Vec3::zeros(), world_up());
+      camera.six_dof = six_dof;
+      camera.pitch(0.3);
+      camera.roll(0.2);
+      let (forward, up) = (camera.forward(), camera.up());
+
+      // 360 pasos de 1° acumulan el mismo error de redondeo que el giro con el mouse
+      for _ in 0..360 {
+        camera.yaw(1f32.to_radians());
+      }
+      assert!((camera.forward() - forward).magnitude() < 1e-3, "6dof={six_dof}: {:?} != {forward:?}", camera.forward());
+      assert!((camera.up() - up).magnitude() < 1e-3, "6dof={six_dof}: {:?} != {up:?}", camera.up());
+    }
   }
 }
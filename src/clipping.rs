@@ -0,0 +1,99 @@
+use nalgebra_glm::{Mat4, Vec3, Vec4};
+use crate::vertex::Vertex;
+
+// Distancia con signo al plano cercano en clip space (z >= -w es visible)
+fn near_distance(vertex: &Vertex) -> f32 {
+    vertex.clip_position.z + vertex.clip_position.w
+}
+
+/// Recorta un triángulo contra el plano cercano en clip space.
+/// Devuelve cero, uno o dos triángulos con los atributos interpolados.
+pub fn clip_near(tri: &[Vertex; 3]) -> Vec<[Vertex; 3]> {
+    let distances = [near_distance(&tri[0]), near_distance(&tri[1]), near_distance(&tri[2])];
+
+    if distances.iter().all(|&d| d >= 0.0) {
+        return vec![tri.clone()];
+    }
+    if distances.iter().all(|&d| d < 0.0) {
+        return Vec::new();
+    }
+
+    // Sutherland-Hodgman contra un solo plano: el polígono resultante tiene 3 o 4 vértices
+    let mut polygon: Vec<Vertex> = Vec::with_capacity(4);
+    for i in 0..3 {
+        let j = (i + 1) % 3;
+        let (current, next) = (&tri[i], &tri[j]);
+        let (d_current, d_next) = (distances[i], distances[j]);
+
+        if d_current >= 0.0 {
+            polygon.push(current.clone());
+        }
+        if (d_current >= 0.0) != (d_next >= 0.0) {
+            let t = d_current / (d_current - d_next);
+            polygon.push(current.lerp(next, t));
+        }
+    }
+
+    // Triangulación en abanico
+    (1..polygon.len() - 1)
+        .map(|i| [polygon[0].clone(), polygon[i].clone(), polygon[i + 1].clone()])
+        .collect()
+}
+
+/// Esfera envolvente de una malla en model space
+#[derive(Debug, Clone, Copy)]
+pub struct BoundingSphere {
+    pub center: Vec3,
+    pub radius: f32,
+}
+
+impl BoundingSphere {
+    /// Centro en el punto medio de la caja envolvente y radio hasta el punto más lejano
+    pub fn from_points(points: &[Vec3]) -> Self {
+        if points.is_empty() {
+            return BoundingSphere { center: Vec3::zeros(), radius: 0.0 };
+        }
+
+        let mut min = points[0];
+        let mut max = points[0];
+        for p in points {
+            min = min.inf(p);
+            max = max.sup(p);
+        }
+        let center = (min + max) * 0.5;
+        let radius = points.iter()
+            .map(|p| (p - center).magnitude())
+            .fold(0.0, f32::max);
+
+        BoundingSphere { center, radius }
+    }
+
+    /// Prueba la esfera contra los seis planos del frustum de la matriz MVP.
+    /// Los planos se extraen en model space (Gribb-Hartmann), así que el radio
+    /// se compara sin transformar; solo se descarta si está completamente fuera.
+    pub fn is_in_frustum(&self, mvp: &Mat4) -> bool {
+        let rows = [mvp.row(0).transpose(), mvp.row(1).transpose(), mvp.row(2).transpose(), mvp.row(3).transpose()];
+        let planes = [
+            rows[3] + rows[0], // izquierdo
+            rows[3] - rows[0], // derecho
+            rows[3] + rows[1], // inferior
+            rows[3] - rows[1], // superior
+            rows[3] + rows[2], // cercano
+            rows[3] - rows[2], // lejano
+        ];
+        let center = Vec4::new(self.center.x, self.center.y, self.center.z, 1.0);
+
+        planes.iter().all(|plane| {
+            let normal_length = Vec3::new(plane.x, plane.y, plane.z).magnitude();
+            normal_length == 0.0 || plane.dot(&center) / normal_length >= -self.radius
+        })
+    }
+}
+
+/// División perspectiva y transformación de viewport de un vértice ya recortado
+pub fn project_to_screen(vertex: &mut Vertex, viewport_matrix: &Mat4) {
+    let clip = vertex.clip_position;
+    let ndc = Vec4::new(clip.x / clip.w, clip.y / clip.w, clip.z / clip.w, 1.0);
+    let screen = viewport_matrix * ndc;
+    vertex.transformed_position = Vec3::new(screen.x, screen.y, screen.z);
+}
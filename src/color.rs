@@ -1,43 +1,356 @@
 use std::fmt;
-use std::ops::{Add, Mul};
+use std::ops::{Add, AddAssign, Mul, Sub};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use serde::{Deserialize, Deserializer};
+
+// Umbrales de la matriz de Bayer 4x4 (0..16) y amplitud del dithering en niveles de 8 bits
+const BAYER_4X4: [[u8; 4]; 4] = [
+    [0, 8, 2, 10],
+    [12, 4, 14, 6],
+    [3, 11, 1, 9],
+    [15, 7, 13, 5],
+];
+const DITHER_STRENGTH: f32 = 2.0;
+
+// Los canales se guardan en espacio lineal y en f32, sin recortar: un brillo fuerte puede
+// pasar de 1 y sólo se recorta al codificar el pixel en to_hex. Con la corrección gamma
+// activa las operaciones (suma, escala, mezcla) se hacen directamente sobre esos valores; sin
+// ella se pasan a sRGB, se opera y se vuelven a lineal (para comparar con la mezcla ingenua).
+static GAMMA_CORRECTION: AtomicBool = AtomicBool::new(true);
+
+/// Activa o desactiva la iluminación en espacio lineal (para comparar)
+pub fn set_gamma_correction(enabled: bool) {
+    GAMMA_CORRECTION.store(enabled, Ordering::Relaxed);
+}
+
+pub fn gamma_correction() -> bool {
+    GAMMA_CORRECTION.load(Ordering::Relaxed)
+}
+
+/// Factor de brillo pensado sobre valores sRGB (como los que había antes de la corrección)
+/// llevado al espacio donde se opera, así esas constantes no cambian de aspecto
+pub fn srgb_factor(factor: f32) -> f32 {
+    if gamma_correction() { srgb_to_linear(factor.clamp(0.0, 1.0)) } else { factor }
+}
+
+// Curva sRGB exacta (tramo lineal cerca del negro y potencia 2.4)
+fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 { value / 12.92 } else { ((value + 0.055) / 1.055).powf(2.4) }
+}
+
+fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.003_130_8 { value * 12.92 } else { 1.055 * value.powf(1.0 / 2.4) - 0.055 }
+}
+
+/// Luminancia relativa (pesos Rec. 709) de canales en espacio lineal
+pub fn luminance([r, g, b]: [f32; 3]) -> f32 {
+    0.2126 * r + 0.7152 * g + 0.0722 * b
+}
+
+// La misma curva en tiempo de compilación (powf no es const), para que Color::new siga
+// sirviendo en constantes: x^2.4 = x^2 * (x^2)^(1/5), con la raíz por Newton
+const fn srgb_to_linear_const(value: f64) -> f64 {
+    if value <= 0.04045 {
+        return value / 12.92;
+    }
+    let square = ((value + 0.055) / 1.055) * ((value + 0.055) / 1.055);
+    let mut root = 1.0;
+    let mut i = 0;
+    while i < 64 {
+        let fourth = root * root * root * root;
+        root -= (fourth * root - square) / (5.0 * fourth);
+        i += 1;
+    }
+    square * root
+}
+
+// Valor lineal de cada nivel sRGB de 8 bits
+const DECODE_TABLE: [f32; 256] = {
+    let mut table = [0.0; 256];
+    let mut i = 0;
+    while i < 256 {
+        table[i] = srgb_to_linear_const(i as f64 / 255.0) as f32;
+        i += 1;
+    }
+    table
+};
+
+// Valor lineal en el que se pasa de cada nivel sRGB al siguiente (a mitad de camino en sRGB)
+const ENCODE_THRESHOLDS: [f32; 255] = {
+    let mut table = [0.0; 255];
+    let mut i = 0;
+    while i < 255 {
+        table[i] = srgb_to_linear_const((i as f64 + 0.5) / 255.0) as f32;
+        i += 1;
+    }
+    table
+};
+
+// Nivel sRGB de 8 bits más cercano; lo que pase de 1 queda en 255
+fn encode(linear: f32) -> u8 {
+    ENCODE_THRESHOLDS.partition_point(|&threshold| threshold < linear) as u8
+}
+
+// Nombres que se aceptan al leer un color de texto (en minúsculas)
+const NAMED_COLORS: [(&str, Color); 14] = [
+    ("black", Color::new(0, 0, 0)),
+    ("white", Color::new(255, 255, 255)),
+    ("gray", Color::new(128, 128, 128)),
+    ("grey", Color::new(128, 128, 128)),
+    ("red", Color::new(255, 0, 0)),
+    ("green", Color::new(0, 255, 0)),
+    ("blue", Color::new(0, 0, 255)),
+    ("yellow", Color::new(255, 255, 0)),
+    ("orange", Color::new(255, 165, 0)),
+    ("cyan", Color::new(0, 255, 255)),
+    ("magenta", Color::new(255, 0, 255)),
+    ("purple", Color::new(128, 0, 128)),
+    ("pink", Color::new(255, 192, 203)),
+    ("brown", Color::new(139, 69, 19)),
+];
+
+/// Por qué no se pudo leer un color de texto
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColorParseError {
+    Empty,
+    /// Cantidad de dígitos hexadecimales (sólo se aceptan 3, 6 u 8)
+    Length(usize),
+    InvalidDigit(char),
+    /// No es hexadecimal ni uno de los nombres conocidos
+    UnknownName(String),
+}
+
+impl fmt::Display for ColorParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ColorParseError::Empty => write!(f, "el color está vacío"),
+            ColorParseError::Length(digits) => write!(f, "se esperaban 3, 6 u 8 dígitos hexadecimales y hay {}", digits),
+            ColorParseError::InvalidDigit(c) => write!(f, "'{}' no es un dígito hexadecimal", c),
+            ColorParseError::UnknownName(name) => write!(f, "color desconocido \"{}\"", name),
+        }
+    }
+}
+
+impl std::error::Error for ColorParseError {}
 
 #[derive(Debug, Clone, Copy)]
 pub struct Color {
-    pub r: u8,
-    pub g: u8,
-    pub b: u8,
+    // Canales en espacio lineal: 1 es el blanco de la pantalla, pero un color puede pasarse
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    // Opacidad en [0, 1] (1 = opaco); el framebuffer no la guarda, sólo decide cómo se mezcla
+    pub a: f32,
 }
 
 impl Color {
-    pub const BLACK: Color = Color { r: 0, g: 0, b: 0 };
+    pub const BLACK: Color = Color { r: 0.0, g: 0.0, b: 0.0, a: 1.0 };
+
+    /// Color opaco a partir de sus niveles sRGB de 8 bits (los de un selector de color)
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Color { r: DECODE_TABLE[r as usize], g: DECODE_TABLE[g as usize], b: DECODE_TABLE[b as usize], a: 1.0 }
+    }
+
+    pub const fn rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Color { a: a as f32 / 255.0, ..Color::new(r, g, b) }
+    }
+
+    /// El mismo color con opacidad `alpha` en [0, 1]
+    pub fn with_alpha(self, alpha: f32) -> Self {
+        Color { a: alpha.clamp(0.0, 1.0), ..self }
+    }
 
-    pub fn new(r: u8, g: u8, b: u8) -> Self {
-        Color { r, g, b }
+    /// Opacidad en [0, 1]
+    pub fn alpha(&self) -> f32 {
+        self.a
     }
 
+    pub fn is_opaque(&self) -> bool {
+        self.a >= 1.0
+    }
+
+    /// Pixel para el framebuffer: cada canal se recorta a [0, 1] y se codifica en sRGB
     pub fn to_hex(self) -> u32 {
-        u32::from_be_bytes([0, self.r, self.g, self.b])
+        u32::from_be_bytes([0, encode(self.r), encode(self.g), encode(self.b)])
+    }
+
+    /// Inversa de to_hex (opaco)
+    pub fn from_hex(hex: u32) -> Self {
+        let [_, r, g, b] = hex.to_be_bytes();
+        Color::new(r, g, b)
+    }
+
+    /// Color escrito como `#RGB`, `#RRGGBB` o `#RRGGBBAA` (con o sin `#`, en mayúsculas o
+    /// minúsculas). `#RGB` repite cada dígito: `#f80` es `#ff8800`.
+    pub fn from_hex_str(text: &str) -> Result<Self, ColorParseError> {
+        let digits = text.trim();
+        let digits = digits.strip_prefix('#').unwrap_or(digits);
+        if digits.is_empty() {
+            return Err(ColorParseError::Empty);
+        }
+        let values = digits
+            .chars()
+            .map(|c| c.to_digit(16).map(|value| value as u8).ok_or(ColorParseError::InvalidDigit(c)))
+            .collect::<Result<Vec<u8>, _>>()?;
+        let pair = |i: usize| values[i] << 4 | values[i + 1];
+        match values.len() {
+            3 => Ok(Color::new(values[0] * 17, values[1] * 17, values[2] * 17)),
+            6 => Ok(Color::new(pair(0), pair(2), pair(4))),
+            8 => Ok(Color::rgba(pair(0), pair(2), pair(4), pair(6))),
+            len => Err(ColorParseError::Length(len)),
+        }
+    }
+
+    /// Dithering ordenado (Bayer 4x4) según la posición del pixel: desplaza cada canal hasta
+    /// un nivel sRGB arriba o abajo para que los escalones de 8 bits se mezclen en vez de
+    /// verse como bandas. Los canales que quedan en negro o en blanco puros no se tocan.
+    pub fn dithered(self, x: usize, y: usize) -> Self {
+        let threshold = (BAYER_4X4[y % 4][x % 4] as f32 + 0.5) / 16.0 - 0.5;
+        let offset = (threshold * DITHER_STRENGTH).round() as i16;
+        let channel = |value: f32| match encode(value) {
+            0 | 255 => value,
+            level => DECODE_TABLE[(level as i16 + offset).clamp(0, 255) as usize],
+        };
+        Color { r: channel(self.r), g: channel(self.g), b: channel(self.b), a: self.a }
+    }
+
+    /// Canales en espacio lineal (pueden pasar de 1)
+    pub fn to_linear(self) -> [f32; 3] {
+        [self.r, self.g, self.b]
+    }
+
+    /// Color opaco a partir de canales lineales; no se recortan hasta to_hex
+    pub fn from_linear([r, g, b]: [f32; 3]) -> Self {
+        Color { r, g, b, a: 1.0 }
     }
 
+    // Canales en sRGB, donde se opera sin la corrección gamma
+    fn to_srgb(self) -> [f32; 3] {
+        [self.r, self.g, self.b].map(|c| linear_to_srgb(c.max(0.0)))
+    }
+
+    // Aplica `op` canal a canal en el espacio donde se opera (ver GAMMA_CORRECTION); conserva
+    // la opacidad de este color
+    fn combine(self, other: Color, op: impl Fn(f32, f32) -> f32) -> Color {
+        if gamma_correction() {
+            return Color { r: op(self.r, other.r), g: op(self.g, other.g), b: op(self.b, other.b), a: self.a };
+        }
+        let ([r0, g0, b0], [r1, g1, b1]) = (self.to_srgb(), other.to_srgb());
+        let channel = |c0: f32, c1: f32| srgb_to_linear(op(c0, c1).max(0.0));
+        Color { r: channel(r0, r1), g: channel(g0, g1), b: channel(b0, b1), a: self.a }
+    }
+
+    /// Luminancia relativa en [0, 1], siempre medida en espacio lineal
+    pub fn luminance(self) -> f32 {
+        luminance(self.to_linear())
+    }
+
+    /// Mezcla de colores; la opacidad también se interpola
     pub fn lerp(&self, other: &Color, t: f32) -> Self {
         let t = t.clamp(0.0, 1.0);
-        Color {
-          r: (self.r as f32 + (other.r as f32 - self.r as f32) * t).round() as u8,
-          g: (self.g as f32 + (other.g as f32 - self.g as f32) * t).round() as u8,
-          b: (self.b as f32 + (other.b as f32 - self.b as f32) * t).round() as u8,
+        let a = self.a + (other.a - self.a) * t;
+        Color { a, ..self.combine(*other, |c0, c1| c0 + (c1 - c0) * t) }
+    }
+
+    /// Color opaco a partir de tono (grados, cualquier valor: se envuelve en [0, 360)),
+    /// saturación y valor en [0, 1]. Se opera sobre los canales en sRGB.
+    pub fn from_hsv(hue: f32, saturation: f32, value: f32) -> Self {
+        let (s, v) = (saturation.clamp(0.0, 1.0), value.clamp(0.0, 1.0));
+        let h = hue.rem_euclid(360.0) / 60.0;
+        let chroma = v * s;
+        let x = chroma * (1.0 - (h % 2.0 - 1.0).abs());
+        let (r, g, b) = match h as u32 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let m = v - chroma;
+        Color::from_linear([r, g, b].map(|c| srgb_to_linear(c + m)))
+    }
+
+    /// (tono en [0, 360), saturación, valor), con los canales recortados a [0, 1]. Los grises
+    /// no tienen tono: se devuelve 0.
+    pub fn to_hsv(self) -> (f32, f32, f32) {
+        let [r, g, b] = self.to_srgb().map(|c| c.min(1.0));
+        let max = r.max(g).max(b);
+        let chroma = max - r.min(g).min(b);
+        if chroma <= 0.0 {
+            return (0.0, 0.0, max);
         }
+        let hue = if max == r {
+            ((g - b) / chroma).rem_euclid(6.0)
+        } else if max == g {
+            (b - r) / chroma + 2.0
+        } else {
+            (r - g) / chroma + 4.0
+        };
+        (hue * 60.0, chroma / max, max)
+    }
+
+    /// El mismo color con el tono girado `degrees` grados; conserva la opacidad
+    pub fn with_hue_shift(self, degrees: f32) -> Self {
+        let (h, s, v) = self.to_hsv();
+        Color { a: self.a, ..Color::from_hsv(h + degrees, s, v) }
+    }
+
+    /// Mezcla en HSV: el tono va por el lado corto del círculo, así rojo -> amarillo pasa por
+    /// naranja y no por el verde apagado de la mezcla RGB. Si un extremo es gris se usa el
+    /// tono del otro.
+    pub fn lerp_hsv(&self, other: &Color, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        let (h0, s0, v0) = self.to_hsv();
+        let (h1, s1, v1) = other.to_hsv();
+        let (h0, h1) = match (s0 > 0.0, s1 > 0.0) {
+            (false, true) => (h1, h1),
+            (true, false) => (h0, h0),
+            _ => (h0, h1),
+        };
+        let delta = (h1 - h0 + 180.0).rem_euclid(360.0) - 180.0;
+        let a = self.a + (other.a - self.a) * t;
+        Color { a, ..Color::from_hsv(h0 + delta * t, s0 + (s1 - s0) * t, v0 + (v1 - v0) * t) }
+    }
+
+    /// Composición "source-over" de este color (con su opacidad) sobre `dst`, que es opaco
+    pub fn over(&self, dst: &Color) -> Self {
+        Color { a: 1.0, ..dst.lerp(&Color { a: 1.0, ..*self }, self.alpha()) }
     }
 }
 
+// Las operaciones conservan la opacidad del primer color. No se recorta nada: un brillo
+// sumado de más sigue siendo más brillante que el blanco hasta que se codifica el pixel
 impl Add<Color> for Color {
     type Output = Color;
 
     fn add(self, other: Color) -> Color {
-        Color {
-            r: self.r.saturating_add(other.r),
-            g: self.g.saturating_add(other.g),
-            b: self.b.saturating_add(other.b),
-        }
+        self.combine(other, |c0, c1| c0 + c1)
+    }
+}
+
+impl AddAssign<Color> for Color {
+    fn add_assign(&mut self, other: Color) {
+        *self = *self + other;
+    }
+}
+
+// Ningún canal baja de 0
+impl Sub<Color> for Color {
+    type Output = Color;
+
+    fn sub(self, other: Color) -> Color {
+        self.combine(other, |c0, c1| (c0 - c1).max(0.0))
+    }
+}
+
+// Producto canal a canal, para teñir un color con el de una luz
+impl Mul<Color> for Color {
+    type Output = Color;
+
+    fn mul(self, other: Color) -> Color {
+        self.combine(other, |c0, c1| c0 * c1)
     }
 }
 
@@ -45,16 +358,160 @@ impl Mul<f32> for Color {
     type Output = Color;
 
     fn mul(self, scalar: f32) -> Color {
-        Color {
-            r: (self.r as f32 * scalar).clamp(0.0, 255.0) as u8,
-            g: (self.g as f32 * scalar).clamp(0.0, 255.0) as u8,
-            b: (self.b as f32 * scalar).clamp(0.0, 255.0) as u8,
+        let scalar = scalar.max(0.0);
+        self.combine(self, |c, _| c * scalar)
+    }
+}
+
+// Un nombre de NAMED_COLORS o un color hexadecimal (ver from_hex_str)
+impl FromStr for Color {
+    type Err = ColorParseError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let name = text.trim().to_ascii_lowercase();
+        if let Some((_, color)) = NAMED_COLORS.iter().find(|(known, _)| *known == name) {
+            return Ok(*color);
+        }
+        match Color::from_hex_str(&name) {
+            // Sin `#` y con letras que no son hexadecimales es más probable un nombre mal escrito
+            Err(ColorParseError::InvalidDigit(_)) if !name.starts_with('#') => Err(ColorParseError::UnknownName(text.trim().to_string())),
+            result => result,
         }
     }
 }
 
+// En los archivos de configuración los colores se escriben como texto ("#ff8800", "orange")
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 impl fmt::Display for Color {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Color(r: {}, g: {}, b: {})", self.r, self.g, self.b)
+        write!(f, "Color(r: {:.3}, g: {:.3}, b: {:.3}, a: {:.3})", self.r, self.g, self.b, self.a)
+    }
+}
+
+// Ninguna prueba cambia GAMMA_CORRECTION: es global y las pruebas corren en paralelo
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_srgb_level_round_trips() {
+        for level in 0..=255u8 {
+            let hex = u32::from_be_bytes([0, level, level / 2, 255 - level]);
+            assert_eq!(Color::from_hex(hex).to_hex(), hex, "nivel {level}");
+            assert_eq!(encode(DECODE_TABLE[level as usize]), level);
+        }
+    }
+
+    #[test]
+    fn srgb_curve_round_trips() {
+        for i in 0..=1000 {
+            let value = i as f32 / 1000.0;
+            assert!((linear_to_srgb(srgb_to_linear(value)) - value).abs() < 1e-5, "sRGB {value}");
+            assert!((srgb_to_linear(linear_to_srgb(value)) - value).abs() < 1e-5, "lineal {value}");
+            // La tabla en tiempo de compilación usa la misma curva
+            assert!((srgb_to_linear_const(value as f64) as f32 - srgb_to_linear(value)).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn hsv_round_trips_over_a_grid() {
+        for hue in (0..360).step_by(15) {
+            for saturation in [0.1, 0.25, 0.5, 0.75, 1.0] {
+                for value in [0.1, 0.25, 0.5, 0.75, 1.0] {
+                    let (h, s, v) = Color::from_hsv(hue as f32, saturation, value).to_hsv();
+                    let hue_error = (h - hue as f32 + 180.0).rem_euclid(360.0) - 180.0;
+                    assert!(hue_error.abs() < 0.05, "tono {hue} -> {h}");
+                    assert!((s - saturation).abs() < 1e-4 && (v - value).abs() < 1e-4, "({hue}, {saturation}, {value}) -> ({h}, {s}, {v})");
+                }
+            }
+        }
+
+        // Y al revés, desde pixeles: cada nivel vuelve al mismo byte
+        for r in (0..=255u32).step_by(17) {
+            for g in (0..=255u32).step_by(17) {
+                for b in (0..=255u32).step_by(17) {
+                    let hex = r << 16 | g << 8 | b;
+                    let (h, s, v) = Color::from_hex(hex).to_hsv();
+                    assert_eq!(Color::from_hsv(h, s, v).to_hex(), hex, "{hex:06x}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn grays_have_no_hue() {
+        for level in [0, 1, 128, 255] {
+            let (h, s, _) = Color::new(level, level, level).to_hsv();
+            assert_eq!((h, s), (0.0, 0.0));
+        }
+        // El tono se envuelve y la saturación se recorta
+        assert_eq!(Color::from_hsv(-120.0, 2.0, 1.0).to_hex(), Color::from_hsv(240.0, 1.0, 1.0).to_hex());
+        assert_eq!(Color::from_hsv(720.0, 1.0, 1.0).to_hex(), 0xFF0000);
+    }
+
+    #[test]
+    fn channels_only_clamp_when_encoded() {
+        let white = Color::new(255, 255, 255);
+        // Blanco * 2 + blanco pasa de 1 y sigue siendo más brillante hasta el pixel
+        let bright = white * 2.0 + white;
+        assert_eq!(bright.to_linear(), [3.0, 3.0, 3.0]);
+        assert!(bright.luminance() > 1.0);
+        assert_eq!(bright.to_hex(), 0xFFFFFF);
+        assert_eq!((bright * 0.25).to_hex(), Color::from_linear([0.75; 3]).to_hex());
+
+        // Hacia abajo nada queda negativo
+        assert_eq!((Color::BLACK - white).to_linear(), [0.0; 3]);
+        assert_eq!((white * -1.0).to_linear(), [0.0; 3]);
+        assert_eq!(Color::from_linear([-1.0, 0.5, 2.0]).to_hex(), Color::from_linear([0.0, 0.5, 1.0]).to_hex());
+
+        // La opacidad sí se recorta siempre a [0, 1]
+        assert_eq!(white.with_alpha(3.0).alpha(), 1.0);
+        assert_eq!(white.with_alpha(-1.0).alpha(), 0.0);
+    }
+
+    #[test]
+    fn parses_every_accepted_form() {
+        let parse = |text: &str| text.parse::<Color>().map(|color| (color.to_hex(), color.alpha()));
+        for text in ["#f80", "f80", "#F80", "#ff8800", "FF8800", "  #Ff8800 ", "#ff8800ff"] {
+            assert_eq!(parse(text), Ok((0xFF8800, 1.0)), "{text:?}");
+        }
+        assert_eq!(parse("#ff880080"), Ok((0xFF8800, 128.0 / 255.0)));
+        for text in ["orange", "ORANGE", " Orange "] {
+            assert_eq!(parse(text), Ok((0xFFA500, 1.0)), "{text:?}");
+        }
+        assert_eq!(parse("gray"), parse("grey"));
+        for (name, color) in NAMED_COLORS {
+            assert_eq!(parse(name), Ok((color.to_hex(), 1.0)));
+        }
+
+        // En la configuración se leen con serde
+        let color: Color = serde_json::from_str("\"#123456\"").unwrap();
+        assert_eq!(color.to_hex(), 0x123456);
+    }
+
+    #[test]
+    fn rejects_malformed_colors() {
+        let error = |text: &str| text.parse::<Color>().err();
+        assert_eq!(error(""), Some(ColorParseError::Empty));
+        assert_eq!(error("  "), Some(ColorParseError::Empty));
+        assert_eq!(error("#"), Some(ColorParseError::Empty));
+        assert_eq!(error("#ff88"), Some(ColorParseError::Length(4)));
+        assert_eq!(error("12345"), Some(ColorParseError::Length(5)));
+        assert_eq!(error("#ff8800f"), Some(ColorParseError::Length(7)));
+        assert_eq!(error("#ff8800ff00"), Some(ColorParseError::Length(10)));
+        assert_eq!(error("#zz8800"), Some(ColorParseError::InvalidDigit('z')));
+        assert_eq!(error("#ff 880"), Some(ColorParseError::InvalidDigit(' ')));
+        // Sin `#` lo que no es hexadecimal se toma como un nombre mal escrito
+        assert_eq!(error("Oragne"), Some(ColorParseError::UnknownName("Oragne".to_string())));
+        assert_eq!(Color::from_hex_str("orange").err(), Some(ColorParseError::InvalidDigit('o')));
+
+        assert!(serde_json::from_str::<Color>("\"nope\"").is_err());
+        assert!(serde_json::from_str::<Color>("16777215").is_err());
     }
 }
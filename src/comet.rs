@@ -0,0 +1,143 @@
+use nalgebra_glm::{Vec3, Vec4};
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use crate::color::Color;
+use crate::framebuffer::Framebuffer;
+use crate::particles::{Particle, Particles};
+use crate::scene::TailConfig;
+use crate::Uniforms;
+
+const TAIL_COLOR: Color = Color::new(190, 215, 255);
+// Luz que suma cada partícula recién salida en el perihelio; las que se superponen cerca del
+// núcleo llegan a blanco
+const TAIL_BRIGHTNESS: f32 = 1.2;
+// Peso de los cuatro vecinos del pixel central de cada partícula, que la suaviza
+const NEIGHBOR_WEIGHT: f32 = 0.35;
+// Radio en el mundo de cada partícula; de lejos queda en un pixel
+const PARTICLE_RADIUS: f32 = 0.015;
+// Actividad por debajo de la cual el cometa deja de soltar partículas
+const MIN_ACTIVITY: f32 = 0.15;
+// Velocidad inicial de las partículas (fracción de TailConfig::speed): una parte ya sale
+// hacia afuera del sol y otra al azar, que abre la cola
+const INITIAL_PUSH: f32 = 0.3;
+const SCATTER: f32 = 0.08;
+
+/// Cola de un cometa: partículas que salen del núcleo, el viento del sol empuja hacia afuera
+/// de él y se apagan a lo largo de su vida. Cerca del perihelio el cometa está más activo:
+/// suelta más partículas, más brillantes y empujadas con más fuerza, así la cola se alarga.
+pub struct CometTail {
+    particles: Particles,
+    config: TailConfig,
+    perihelion: f32,
+    rng: StdRng,
+    // Fracción de partícula pendiente de lanzar de un frame al siguiente
+    pending: f32,
+    // Posición del núcleo en el update anterior, para saber su velocidad
+    last_nucleus: Option<Vec3>,
+}
+
+impl CometTail {
+    pub fn new(config: &TailConfig, perihelion: f32, seed: u64) -> Self {
+        CometTail {
+            particles: Particles::new(config.particles),
+            config: config.clone(),
+            perihelion,
+            rng: StdRng::seed_from_u64(seed),
+            pending: 0.0,
+            last_nucleus: None,
+        }
+    }
+
+    /// Actividad en [0, 1]: 1 en el perihelio y baja con la distancia al sol
+    fn activity(&self, sun_distance: f32) -> f32 {
+        (self.perihelion / sun_distance.max(f32::EPSILON)).min(1.0)
+    }
+
+    /// Avanza `delta` segundos con el núcleo (de radio `radius`) en `nucleus` y el sol en `sun`
+    pub fn update(&mut self, delta: f32, nucleus: Vec3, radius: f32, sun: Vec3) {
+        let speed = self.config.speed;
+        // El empuje va con la actividad con la que salió la partícula, que también da su brillo
+        self.particles.update(delta, |particle| {
+            let away = (particle.position - sun).try_normalize(f32::EPSILON).unwrap_or(Vec3::zeros());
+            away * speed * particle.brightness
+        });
+
+        // Las partículas salen con la velocidad del núcleo, así la cola lo acompaña y lo que
+        // la separa de él es sólo el empuje del sol
+        let nucleus_velocity = match self.last_nucleus.replace(nucleus) {
+            Some(last) if delta > 0.0 => (nucleus - last) / delta,
+            _ => Vec3::zeros(),
+        };
+        let away = (nucleus - sun).try_normalize(f32::EPSILON).unwrap_or(Vec3::y());
+        let activity = self.activity((nucleus - sun).magnitude());
+        if activity < MIN_ACTIVITY {
+            self.pending = 0.0;
+            return;
+        }
+        // A pleno, el conjunto se llena justo a lo largo de una vida
+        self.pending += self.config.particles as f32 / self.config.lifetime * activity * delta;
+        while self.pending >= 1.0 {
+            self.pending -= 1.0;
+            let offset = random_unit(&mut self.rng) * radius;
+            let scatter = random_unit(&mut self.rng) * (SCATTER * speed);
+            let particle = Particle {
+                position: nucleus + offset,
+                velocity: nucleus_velocity + away * (INITIAL_PUSH * speed * activity) + scatter,
+                age: 0.0,
+                lifetime: self.config.lifetime * self.rng.gen_range(0.6..1.0),
+                brightness: activity * self.rng.gen_range(0.6..1.0),
+            };
+            if !self.particles.spawn(particle) {
+                self.pending = 0.0;
+                break;
+            }
+        }
+    }
+
+    /// Suma cada partícula a la imagen con prueba de profundidad y sin escribirla: lo que
+    /// esté delante la tapa y las que se superponen se acumulan
+    pub fn render(&self, framebuffer: &mut Framebuffer, uniforms: &Uniforms) {
+        let view_projection = uniforms.projection_matrix * uniforms.view_matrix;
+        let pixels_per_unit = (uniforms.projection_matrix[(1, 1)] * uniforms.viewport_matrix[(1, 1)]).abs();
+        for particle in self.particles.iter() {
+            let p = particle.position;
+            let clip = view_projection * Vec4::new(p.x, p.y, p.z, 1.0);
+            if clip.w <= 0.0 || clip.z < -clip.w {
+                continue;
+            }
+            let screen = uniforms.viewport_matrix * Vec4::new(clip.x / clip.w, clip.y / clip.w, clip.z / clip.w, 1.0);
+
+            // Aparece rápido y se apaga despacio
+            let life = particle.life();
+            let fade = (life * 10.0).min(1.0) * (1.0 - life).powf(1.5);
+            let color = TAIL_COLOR * (particle.brightness * fade * TAIL_BRIGHTNESS);
+
+            // Una cruz suave de lejos y un cuadrado del tamaño de la partícula de cerca
+            let reach = (PARTICLE_RADIUS * pixels_per_unit / clip.w).floor() as i64;
+            let (x, y) = (screen.x.floor() as i64, screen.y.floor() as i64);
+            for py in y - reach - 1..=y + reach + 1 {
+                for px in x - reach - 1..=x + reach + 1 {
+                    let (dx, dy) = ((px - x).abs() - reach, (py - y).abs() - reach);
+                    let weight = match (dx.max(0), dy.max(0)) {
+                        (0, 0) => 1.0,
+                        (0, 1) | (1, 0) => NEIGHBOR_WEIGHT,
+                        _ => continue,
+                    };
+                    if px >= 0 && py >= 0 && (px as usize) < framebuffer.width && (py as usize) < framebuffer.height {
+                        framebuffer.add_pixel(px as usize, py as usize, screen.z, color * weight);
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Punto al azar dentro de la esfera unitaria
+fn random_unit(rng: &mut StdRng) -> Vec3 {
+    loop {
+        let candidate = Vec3::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0));
+        if candidate.magnitude_squared() <= 1.0 {
+            return candidate;
+        }
+    }
+}
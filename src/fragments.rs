@@ -1,28 +1,21 @@
 use nalgebra_glm::{Vec3, Vec2};
+use crate::color::Color;
 
 #[derive(Debug, Clone, Copy)]
 pub struct Fragments {
     pub position: Vec2,
     pub depth: f32,
+    // Distancia a la cámara en espacio de vista (la w del clip), para la niebla
+    pub view_depth: f32,
     pub normal: Vec3,
     pub intensity: f32,
     pub vertex_pos: Vec3,
+    pub world_position: Vec3,
+    // Atributos interpolados con corrección de perspectiva
+    #[allow(dead_code)]
+    pub tex_coords: Vec2,
+    #[allow(dead_code)]
+    pub color: Color,
+    // Material de la cara (índice en Uniforms::materials)
+    pub material: Option<u32>,
 }
-
-impl Fragments {
-    pub fn new(
-        position: Vec2,
-        depth: f32,
-        normal: Vec3,
-        intensity: f32,
-        vertex_pos: Vec3,
-    ) -> Self {
-        Fragments {
-            position,
-            depth,
-            normal,
-            intensity,
-            vertex_pos,
-        }
-    }
-}
\ No newline at end of file
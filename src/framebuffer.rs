@@ -1,19 +1,177 @@
+use nalgebra_glm::Vec3;
+use crate::color::Color;
+use crate::text::{self, GLYPH_SIZE, LINE_HEIGHT};
+
+/// Lado en pixeles de cada tile para el rasterizado en paralelo
+pub const TILE_SIZE: usize = 64;
+
+/// Opacidad desde la que un fragmento translúcido también escribe su profundidad; los más
+/// transparentes dejan ver (y dejan dibujar) lo que está detrás
+pub const DEPTH_WRITE_ALPHA: f32 = 0.6;
+
+// Prueba de profundidad compartida por el framebuffer y los tiles. Las capas del fondo se
+// dibujan todas a MAX_DEPTH y se componen entre sí (el cielo y luego lo que se le suma),
+// así que a esa profundidad un pixel todavía libre también pasa.
+fn passes_depth(depth: f32, stored: f32) -> bool {
+    depth < stored || (depth == Framebuffer::MAX_DEPTH && stored == Framebuffer::MAX_DEPTH)
+}
+
+/// Rectángulo de pixeles [x0, x1) x [y0, y1)
+#[derive(Debug, Clone, Copy)]
+pub struct Rect {
+    pub x0: usize,
+    pub y0: usize,
+    pub x1: usize,
+    pub y1: usize,
+}
+
+impl Rect {
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        x >= self.x0 as f32 && x < self.x1 as f32 && y >= self.y0 as f32 && y < self.y1 as f32
+    }
+}
+
+/// Divide el framebuffer en tiles y agrupa los triángulos según los tiles que tocan
+pub struct TileGrid {
+    tiles_x: usize,
+    tiles_y: usize,
+    tile_size: usize,
+    width: usize,
+    height: usize,
+}
+
+impl TileGrid {
+    /// Tiles de `tile_size` pixeles de lado (TILE_SIZE en el pipeline); uno que cubra todo el
+    /// framebuffer equivale a no dividirlo
+    pub fn with_tile_size(width: usize, height: usize, tile_size: usize) -> Self {
+        let tile_size = tile_size.max(1);
+        TileGrid {
+            tiles_x: width.div_ceil(tile_size),
+            tiles_y: height.div_ceil(tile_size),
+            tile_size,
+            width,
+            height,
+        }
+    }
+
+    pub fn tile_count(&self) -> usize {
+        self.tiles_x * self.tiles_y
+    }
+
+    pub fn tile_rect(&self, tile: usize) -> Rect {
+        let (tx, ty) = (tile % self.tiles_x, tile / self.tiles_x);
+        Rect {
+            x0: tx * self.tile_size,
+            y0: ty * self.tile_size,
+            x1: ((tx + 1) * self.tile_size).min(self.width),
+            y1: ((ty + 1) * self.tile_size).min(self.height),
+        }
+    }
+
+    /// Tile que contiene el pixel (x, y), o None si está fuera del framebuffer
+    pub fn tile_at(&self, x: f32, y: f32) -> Option<usize> {
+        if x < 0.0 || y < 0.0 || x >= self.width as f32 || y >= self.height as f32 {
+            return None;
+        }
+        Some((y as usize / self.tile_size) * self.tiles_x + x as usize / self.tile_size)
+    }
+
+    /// Para cada tile, los índices (en orden de envío) de los triángulos cuyo
+    /// bounding box en pantalla lo toca
+    pub fn bin(&self, bounding_boxes: &[(f32, f32, f32, f32)]) -> Vec<Vec<usize>> {
+        let mut bins = vec![Vec::new(); self.tile_count()];
+        let max_x = self.width as f32 - 1.0;
+        let max_y = self.height as f32 - 1.0;
+
+        for (index, &(min_x, min_y, bb_max_x, bb_max_y)) in bounding_boxes.iter().enumerate() {
+            if bb_max_x < 0.0 || bb_max_y < 0.0 || min_x > max_x || min_y > max_y {
+                continue;
+            }
+            let tx0 = min_x.clamp(0.0, max_x) as usize / self.tile_size;
+            let ty0 = min_y.clamp(0.0, max_y) as usize / self.tile_size;
+            let tx1 = bb_max_x.clamp(0.0, max_x) as usize / self.tile_size;
+            let ty1 = bb_max_y.clamp(0.0, max_y) as usize / self.tile_size;
+
+            for ty in ty0..=ty1 {
+                for tx in tx0..=tx1 {
+                    bins[ty * self.tiles_x + tx].push(index);
+                }
+            }
+        }
+
+        bins
+    }
+}
+
+/// Copia local de un tile del framebuffer; cada hilo escribe solo en la suya
+pub struct TileBuffer {
+    pub rect: Rect,
+    buffer: Vec<u32>,
+    zbuffer: Vec<f32>,
+    overdraw: Option<Vec<u32>>,
+}
+
+impl TileBuffer {
+    fn index(&self, x: usize, y: usize) -> usize {
+        (y - self.rect.y0) * (self.rect.x1 - self.rect.x0) + (x - self.rect.x0)
+    }
+
+    /// Early-z: true si un fragmento a esta profundidad quedaría visible
+    pub fn depth_test(&self, x: usize, y: usize, depth: f32) -> bool {
+        passes_depth(depth, self.zbuffer[self.index(x, y)])
+    }
+
+    /// Escribe un fragmento que ya pasó depth_test
+    pub fn write(&mut self, x: usize, y: usize, depth: f32, color: u32) {
+        let index = self.index(x, y);
+        self.buffer[index] = color;
+        self.zbuffer[index] = depth;
+        if let Some(overdraw) = self.overdraw.as_mut() {
+            overdraw[index] += 1;
+        }
+    }
+
+    /// Mezcla un fragmento translúcido que ya pasó depth_test sobre lo que hay en el pixel
+    pub fn blend(&mut self, x: usize, y: usize, depth: f32, color: Color) {
+        let index = self.index(x, y);
+        self.buffer[index] = color.over(&Color::from_hex(self.buffer[index])).to_hex();
+        if color.alpha() >= DEPTH_WRITE_ALPHA {
+            self.zbuffer[index] = depth;
+        }
+        if let Some(overdraw) = self.overdraw.as_mut() {
+            overdraw[index] += 1;
+        }
+    }
+}
+
 pub struct Framebuffer {
     pub width: usize,
     pub height: usize,
     pub buffer: Vec<u32>,
     pub zbuffer: Vec<f32>,
+    // Escrituras por pixel en el frame; solo existe con la vista de overdraw activa
+    overdraw: Option<Vec<u32>>,
+    // Frame anterior ya mezclado para el desenfoque de movimiento; vacío hasta el primer frame.
+    // Se guarda en espacio lineal y en f32, así la estela se apaga del todo en vez de quedar
+    // trabada en el nivel sRGB más bajo
+    accumulation: Option<Vec<[f32; 3]>>,
     background_color: u32,
     current_color: u32,
 }
 
 impl Framebuffer {
+    /// Profundidad de un pixel sin geometría: el zbuffer se limpia a este valor y el fondo
+    /// se dibuja a esta profundidad
+    pub const MAX_DEPTH: f32 = f32::MAX;
+
     pub fn new(width: usize, height: usize) -> Self {
         Framebuffer {
             width,
             height,
             buffer: vec![0; width * height],
-            zbuffer: vec![f32::INFINITY; width * height],
+            zbuffer: vec![Self::MAX_DEPTH; width * height],
+            overdraw: None,
+            accumulation: None,
             background_color: 0x000000,
             current_color: 0xFFFFFF,
         }
@@ -24,17 +182,327 @@ impl Framebuffer {
             *pixel = self.background_color;
         }
         for depth in self.zbuffer.iter_mut() {
-            *depth = f32::INFINITY;
+            *depth = Self::MAX_DEPTH;
+        }
+        if let Some(overdraw) = self.overdraw.as_mut() {
+            overdraw.fill(0);
         }
     }
 
-    pub fn point(&mut self, x: usize, y: usize, depth: f32) {
+    /// Reserva o libera el contador de escrituras por pixel
+    pub fn set_overdraw_tracking(&mut self, enabled: bool) {
+        match (enabled, self.overdraw.is_some()) {
+            (true, false) => self.overdraw = Some(vec![0; self.width * self.height]),
+            (false, true) => self.overdraw = None,
+            _ => {}
+        }
+    }
+
+    /// Reserva o libera el buffer de acumulación. Al activarlo empieza vacío, así no reaparece
+    /// un frame viejo de la última vez que estuvo activo.
+    pub fn set_accumulation(&mut self, enabled: bool) {
+        match (enabled, self.accumulation.is_some()) {
+            (true, false) => self.accumulation = Some(Vec::new()),
+            (false, true) => self.accumulation = None,
+            _ => {}
+        }
+    }
+
+    /// Mezcla la imagen terminada con el frame anterior (`persistence` es el peso de éste)
+    /// y la guarda para el siguiente. No hace nada si la acumulación está desactivada.
+    pub fn accumulate(&mut self, persistence: f32) {
+        let Some(previous) = self.accumulation.as_mut() else { return };
+        if previous.len() != self.buffer.len() {
+            *previous = self.buffer.iter().map(|&pixel| Color::from_hex(pixel).to_linear()).collect();
+            return;
+        }
+
+        // Mezcla de la luz en espacio lineal, como el resto de la composición
+        let weight = persistence.clamp(0.0, 1.0);
+        for (pixel, old) in self.buffer.iter_mut().zip(previous.iter_mut()) {
+            let current = Color::from_hex(*pixel).to_linear();
+            *old = [0, 1, 2].map(|i| current[i] * (1.0 - weight) + old[i] * weight);
+            *pixel = Color::from_linear(*old).to_hex();
+        }
+    }
+
+    /// Reemplaza el color de cada pixel por una rampa según cuántas veces se
+    /// escribió: azul = 1 escritura, rojo = 8 o más. Los pixeles sin escrituras quedan negros.
+    pub fn apply_overdraw_heatmap(&mut self) {
+        let Some(overdraw) = self.overdraw.as_ref() else { return };
+        let ramp = [
+            Color::new(0, 0, 255),
+            Color::new(0, 255, 255),
+            Color::new(0, 255, 0),
+            Color::new(255, 255, 0),
+            Color::new(255, 0, 0),
+        ];
+
+        for (pixel, &count) in self.buffer.iter_mut().zip(overdraw) {
+            *pixel = if count == 0 {
+                0x000000
+            } else {
+                let t = ((count - 1) as f32 / 7.0).min(1.0) * (ramp.len() - 1) as f32;
+                let stop = (t as usize).min(ramp.len() - 2);
+                ramp[stop].lerp(&ramp[stop + 1], t - stop as f32).to_hex()
+            };
+        }
+    }
+
+    /// Profundidad guardada en un pixel, o None si está fuera del framebuffer
+    pub fn depth_at(&self, x: usize, y: usize) -> Option<f32> {
         if x < self.width && y < self.height {
+            Some(self.zbuffer[y * self.width + x])
+        } else {
+            None
+        }
+    }
+
+    /// True si el pixel existe y un fragmento a esta profundidad quedaría visible
+    pub fn depth_test(&self, x: usize, y: usize, depth: f32) -> bool {
+        self.depth_at(x, y).is_some_and(|stored| passes_depth(depth, stored))
+    }
+
+    /// Límites, prueba de profundidad y escritura en una sola llamada, sin estado de color
+    pub fn set_pixel(&mut self, x: usize, y: usize, depth: f32, color: u32) {
+        if self.depth_test(x, y, depth) {
             let index = y * self.width + x;
-            if self.zbuffer[index] > depth {
-                self.buffer[index] = self.current_color;
+            self.buffer[index] = color;
+            self.zbuffer[index] = depth;
+            if let Some(overdraw) = self.overdraw.as_mut() {
+                overdraw[index] += 1;
+            }
+        }
+    }
+
+    /// Composición "source-over" con la opacidad `alpha`, con prueba de profundidad; sólo
+    /// escribe la profundidad si el fragmento es bastante opaco (DEPTH_WRITE_ALPHA)
+    pub fn blend_pixel(&mut self, x: usize, y: usize, depth: f32, color: Color, alpha: f32) {
+        if self.depth_test(x, y, depth) {
+            let index = y * self.width + x;
+            self.buffer[index] = color.with_alpha(alpha).over(&Color::from_hex(self.buffer[index])).to_hex();
+            if alpha >= DEPTH_WRITE_ALPHA {
                 self.zbuffer[index] = depth;
             }
+            if let Some(overdraw) = self.overdraw.as_mut() {
+                overdraw[index] += 1;
+            }
+        }
+    }
+
+    /// Suma un color al pixel (mezcla aditiva) si pasa la prueba de profundidad, sin
+    /// escribir profundidad: lo que se dibuje después por delante lo tapa
+    pub fn add_pixel(&mut self, x: usize, y: usize, depth: f32, color: Color) {
+        if self.depth_test(x, y, depth) {
+            let index = y * self.width + x;
+            self.buffer[index] = (Color::from_hex(self.buffer[index]) + color).to_hex();
+        }
+    }
+
+    /// Línea de un pixel de ancho entre dos puntos de pantalla (x, y y profundidad), con
+    /// Bresenham sobre los pixeles que realmente cubre y la profundidad interpolada en cada
+    /// uno. Respeta el zbuffer, se mezcla con la opacidad de `color` y recorta los extremos a
+    /// la pantalla antes de rasterizar. Sin `include_end` no pinta el último pixel, así en una
+    /// polilínea el vértice compartido no se mezcla dos veces.
+    pub fn line(&mut self, p0: Vec3, p1: Vec3, color: Color, include_end: bool) {
+        let Some((p0, p1)) = self.clip_line(p0, p1) else { return };
+        let (x0, y0) = (p0.x.floor() as i64, p0.y.floor() as i64);
+        let (x1, y1) = (p1.x.floor() as i64, p1.y.floor() as i64);
+        let (dx, dy) = ((x1 - x0).abs(), -(y1 - y0).abs());
+        let (step_x, step_y) = ((x1 - x0).signum(), (y1 - y0).signum());
+        let steps = dx.max(-dy).max(1) as f32;
+
+        let (mut x, mut y, mut error) = (x0, y0, dx + dy);
+        for step in 0.. {
+            let last = x == x1 && y == y1;
+            if last && !include_end && step > 0 {
+                break;
+            }
+            let depth = p0.z + (p1.z - p0.z) * (step as f32 / steps);
+            self.blend_pixel(x as usize, y as usize, depth, color, color.alpha());
+            if last {
+                break;
+            }
+            let doubled = 2 * error;
+            if doubled >= dy {
+                error += dy;
+                x += step_x;
+            }
+            if doubled <= dx {
+                error += dx;
+                y += step_y;
+            }
+        }
+    }
+
+    // Cohen-Sutherland contra el rectángulo [0, ancho) x [0, alto); la profundidad se
+    // interpola junto con la posición
+    fn clip_line(&self, mut p0: Vec3, mut p1: Vec3) -> Option<(Vec3, Vec3)> {
+        const LEFT: u8 = 1;
+        const RIGHT: u8 = 2;
+        const TOP: u8 = 4;
+        const BOTTOM: u8 = 8;
+        // Justo antes del borde para que floor() no salga del último pixel
+        let (max_x, max_y) = (self.width as f32 - 1e-3, self.height as f32 - 1e-3);
+        let outcode = |p: &Vec3| {
+            let mut code = 0;
+            if p.x < 0.0 { code |= LEFT } else if p.x > max_x { code |= RIGHT }
+            if p.y < 0.0 { code |= TOP } else if p.y > max_y { code |= BOTTOM }
+            code
+        };
+
+        let (mut code0, mut code1) = (outcode(&p0), outcode(&p1));
+        loop {
+            if code0 | code1 == 0 {
+                return Some((p0, p1));
+            }
+            if code0 & code1 != 0 {
+                return None;
+            }
+            // Se mueve el extremo que está afuera hasta el borde que cruza
+            let outside = if code0 != 0 { code0 } else { code1 };
+            let delta = p1 - p0;
+            let t = if outside & LEFT != 0 {
+                -p0.x / delta.x
+            } else if outside & RIGHT != 0 {
+                (max_x - p0.x) / delta.x
+            } else if outside & TOP != 0 {
+                -p0.y / delta.y
+            } else {
+                (max_y - p0.y) / delta.y
+            };
+            let mut point = p0 + delta * t;
+            // Se fija la coordenada del borde para no quedar afuera por redondeo
+            if outside & LEFT != 0 { point.x = 0.0 } else if outside & RIGHT != 0 { point.x = max_x }
+            else if outside & TOP != 0 { point.y = 0.0 } else { point.y = max_y }
+
+            if outside == code0 {
+                p0 = point;
+                code0 = outcode(&p0);
+            } else {
+                p1 = point;
+                code1 = outcode(&p1);
+            }
+        }
+    }
+
+    /// Escribe `text` con la esquina superior izquierda en (x, y), ampliando cada pixel de
+    /// la fuente a `scale` x `scale`. El fondo queda transparente, no toca la profundidad y
+    /// lo que cae fuera del framebuffer se recorta; `\n` empieza una línea nueva.
+    pub fn draw_text(&mut self, x: i32, y: i32, text: &str, color: Color, scale: usize) {
+        let scale = scale.max(1);
+        for (line_index, line) in text.lines().enumerate() {
+            let top = y as i64 + (line_index * LINE_HEIGHT * scale) as i64;
+            for (column, c) in line.chars().enumerate() {
+                let left = x as i64 + (column * GLYPH_SIZE * scale) as i64;
+                for (row, bits) in text::glyph(c).iter().enumerate() {
+                    for bit in 0..GLYPH_SIZE {
+                        if bits & (0x80 >> bit) != 0 {
+                            self.fill_rect(left + (bit * scale) as i64, top + (row * scale) as i64, scale, scale, color);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Rectángulo de 2D sobre la imagen (sin profundidad), recortado a los bordes y mezclado
+    /// según la opacidad del color
+    pub fn fill_rect(&mut self, x: i64, y: i64, width: usize, height: usize, color: Color) {
+        let (x0, y0) = (x.clamp(0, self.width as i64) as usize, y.clamp(0, self.height as i64) as usize);
+        let (x1, y1) = ((x + width as i64).clamp(0, self.width as i64) as usize, (y + height as i64).clamp(0, self.height as i64) as usize);
+        for py in y0..y1 {
+            for px in x0..x1 {
+                let index = py * self.width + px;
+                self.buffer[index] = if color.is_opaque() { color.to_hex() } else { color.over(&Color::from_hex(self.buffer[index])).to_hex() };
+            }
+        }
+    }
+
+    /// Escribe el color actual solo si el fragmento está más cerca que lo ya dibujado.
+    /// Se conserva por compatibilidad; el pipeline usa set_pixel.
+    #[allow(dead_code)]
+    pub fn point(&mut self, x: usize, y: usize, depth: f32) {
+        self.set_pixel(x, y, depth, self.current_color);
+    }
+
+    pub fn read_tile(&self, rect: Rect) -> TileBuffer {
+        let tile_width = rect.x1 - rect.x0;
+        let mut buffer = Vec::with_capacity(tile_width * (rect.y1 - rect.y0));
+        let mut zbuffer = Vec::with_capacity(buffer.capacity());
+        let mut overdraw = self.overdraw.as_ref().map(|_| Vec::with_capacity(buffer.capacity()));
+        for y in rect.y0..rect.y1 {
+            let row = y * self.width;
+            buffer.extend_from_slice(&self.buffer[row + rect.x0..row + rect.x1]);
+            zbuffer.extend_from_slice(&self.zbuffer[row + rect.x0..row + rect.x1]);
+            if let (Some(tile), Some(source)) = (overdraw.as_mut(), self.overdraw.as_ref()) {
+                tile.extend_from_slice(&source[row + rect.x0..row + rect.x1]);
+            }
+        }
+        TileBuffer { rect, buffer, zbuffer, overdraw }
+    }
+
+    pub fn write_tile(&mut self, tile: &TileBuffer) {
+        let rect = tile.rect;
+        let tile_width = rect.x1 - rect.x0;
+        for (i, y) in (rect.y0..rect.y1).enumerate() {
+            let row = y * self.width;
+            self.buffer[row + rect.x0..row + rect.x1].copy_from_slice(&tile.buffer[i * tile_width..(i + 1) * tile_width]);
+            self.zbuffer[row + rect.x0..row + rect.x1].copy_from_slice(&tile.zbuffer[i * tile_width..(i + 1) * tile_width]);
+            if let (Some(target), Some(source)) = (self.overdraw.as_mut(), tile.overdraw.as_ref()) {
+                target[row + rect.x0..row + rect.x1].copy_from_slice(&source[i * tile_width..(i + 1) * tile_width]);
+            }
+        }
+    }
+
+    /// Reduce el framebuffer por un factor entero promediando cada bloque de
+    /// factor x factor pixeles (filtro caja) hacia `out`. El promedio se hace en espacio
+    /// lineal: promediar los bytes sRGB oscurece los bordes entre zonas claras y oscuras.
+    pub fn downsample(&self, factor: usize, out: &mut Vec<u32>) {
+        let (out_width, out_height) = (self.width / factor, self.height / factor);
+        let samples = (factor * factor) as f32;
+        out.resize(out_width * out_height, 0);
+
+        for y in 0..out_height {
+            for x in 0..out_width {
+                let mut sum = [0.0f32; 3];
+                for sy in 0..factor {
+                    let row = (y * factor + sy) * self.width + x * factor;
+                    for &pixel in &self.buffer[row..row + factor] {
+                        let linear = Color::from_hex(pixel).to_linear();
+                        sum = [sum[0] + linear[0], sum[1] + linear[1], sum[2] + linear[2]];
+                    }
+                }
+                out[y * out_width + x] = Color::from_linear(sum.map(|c| c / samples)).to_hex();
+            }
+        }
+    }
+
+    /// Amplía el framebuffer a `out_width` x `out_height` repitiendo el pixel más cercano
+    /// hacia `out`
+    pub fn upsample(&self, out_width: usize, out_height: usize, out: &mut Vec<u32>) {
+        out.resize(out_width * out_height, 0);
+        let columns: Vec<usize> = (0..out_width).map(|x| (x * self.width / out_width).min(self.width - 1)).collect();
+
+        for y in 0..out_height {
+            let row = (y * self.height / out_height).min(self.height - 1) * self.width;
+            for (x, &column) in columns.iter().enumerate() {
+                out[y * out_width + x] = self.buffer[row + column];
+            }
+        }
+    }
+
+    /// Copia la profundidad a `out`, de otro tamaño: cada pixel toma la del pixel propio que
+    /// contiene su centro. Sirve para probar el HUD contra la escena a la escala de la ventana.
+    pub fn resample_depth(&self, out: &mut Framebuffer) {
+        let source = |i: usize, size: usize, source_size: usize| ((2 * i + 1) * source_size / (2 * size)).min(source_size - 1);
+        let columns: Vec<usize> = (0..out.width).map(|x| source(x, out.width, self.width)).collect();
+
+        for y in 0..out.height {
+            let row = source(y, out.height, self.height) * self.width;
+            for (x, &column) in columns.iter().enumerate() {
+                out.zbuffer[y * out.width + x] = self.zbuffer[row + column];
+            }
         }
     }
 
@@ -42,7 +510,144 @@ impl Framebuffer {
         self.background_color = color;
     }
 
+    #[allow(dead_code)]
     pub fn set_current_color(&mut self, color: u32) {
         self.current_color = color;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra_glm::{Vec2, Vec3};
+    use crate::triangle::triangle;
+    use crate::vertex::Vertex;
+
+    const NEAR: u32 = 0xFF0000;
+    const FAR: u32 = 0x0000FF;
+
+    // Vértice ya en pantalla: Vertex::new deja la posición transformada igual a la original
+    fn vertex(x: f32, y: f32, depth: f32) -> Vertex {
+        Vertex::new(Vec3::new(x, y, depth), Vec3::z(), Vec2::zeros())
+    }
+
+    fn covered(framebuffer: &Framebuffer, tri: &[Vertex; 3]) -> Vec<(usize, usize)> {
+        let rect = Rect { x0: 0, y0: 0, x1: framebuffer.width, y1: framebuffer.height };
+        triangle(&tri[0], &tri[1], &tri[2], &rect)
+            .iter()
+            .map(|fragment| (fragment.position.x as usize, fragment.position.y as usize))
+            .collect()
+    }
+
+    fn draw(framebuffer: &mut Framebuffer, tri: &[Vertex; 3], color: u32) {
+        let rect = Rect { x0: 0, y0: 0, x1: framebuffer.width, y1: framebuffer.height };
+        for fragment in triangle(&tri[0], &tri[1], &tri[2], &rect) {
+            framebuffer.set_pixel(fragment.position.x as usize, fragment.position.y as usize, fragment.depth, color);
+        }
+    }
+
+    #[test]
+    fn nearer_triangle_wins_in_either_order() {
+        let near = [vertex(4.0, 4.0, 0.2), vertex(40.0, 8.0, 0.3), vertex(12.0, 40.0, 0.2)];
+        let far = [vertex(8.0, 2.0, 0.8), vertex(46.0, 30.0, 0.7), vertex(2.0, 30.0, 0.9)];
+
+        for near_first in [true, false] {
+            let mut framebuffer = Framebuffer::new(48, 48);
+            framebuffer.clear();
+            let order = if near_first { [(&near, NEAR), (&far, FAR)] } else { [(&far, FAR), (&near, NEAR)] };
+            for (tri, color) in order {
+                draw(&mut framebuffer, tri, color);
+            }
+
+            let near_pixels = covered(&framebuffer, &near);
+            let far_pixels = covered(&framebuffer, &far);
+            assert!(near_pixels.iter().any(|pixel| far_pixels.contains(pixel)), "los triángulos deben superponerse");
+            for &(x, y) in &near_pixels {
+                assert_eq!(framebuffer.buffer[y * framebuffer.width + x], NEAR, "pixel ({}, {}), cercano primero: {}", x, y, near_first);
+            }
+            for &(x, y) in far_pixels.iter().filter(|pixel| !near_pixels.contains(pixel)) {
+                assert_eq!(framebuffer.buffer[y * framebuffer.width + x], FAR);
+            }
+        }
+    }
+
+    #[test]
+    fn clear_resets_depth() {
+        let mut framebuffer = Framebuffer::new(4, 4);
+        framebuffer.point(1, 1, 0.5);
+        assert_eq!(framebuffer.depth_at(1, 1), Some(0.5));
+        framebuffer.clear();
+        framebuffer.point(1, 1, 0.9);
+        assert_eq!(framebuffer.depth_at(1, 1), Some(0.9));
+        assert_eq!(framebuffer.depth_at(4, 0), None);
+    }
+
+    // El cielo pinta a MAX_DEPTH y después se le suman estrellas a la misma profundidad
+    #[test]
+    fn background_layers_compose_at_max_depth() {
+        let mut framebuffer = Framebuffer::new(4, 4);
+        for _ in 0..2 {
+            framebuffer.set_pixel(1, 1, Framebuffer::MAX_DEPTH, 0x202020);
+            framebuffer.add_pixel(1, 1, Framebuffer::MAX_DEPTH, Color::new(0x20, 0, 0));
+            assert_ne!(framebuffer.buffer[5], 0x202020, "la suma a MAX_DEPTH no se aplicó");
+            framebuffer.clear();
+        }
+
+        // La geometría sí tapa al fondo
+        framebuffer.set_pixel(1, 1, 0.5, NEAR);
+        framebuffer.add_pixel(1, 1, Framebuffer::MAX_DEPTH, Color::new(0x20, 0x20, 0x20));
+        framebuffer.set_pixel(1, 1, Framebuffer::MAX_DEPTH, FAR);
+        assert_eq!(framebuffer.buffer[5], NEAR);
+    }
+
+    #[test]
+    fn downsample_averages_in_linear_space() {
+        let mut framebuffer = Framebuffer::new(2, 2);
+        framebuffer.buffer.copy_from_slice(&[0xFFFFFF, 0x000000, 0x000000, 0xFFFFFF]);
+        let mut out = Vec::new();
+        framebuffer.downsample(2, &mut out);
+        // La mitad de la luz del blanco es el nivel sRGB 188, no 127
+        assert_eq!(out, vec![0xBCBCBC]);
+
+        // Un bloque de un solo color no cambia
+        framebuffer.buffer.fill(0x3366CC);
+        framebuffer.downsample(2, &mut out);
+        assert_eq!(out, vec![0x3366CC]);
+    }
+
+    #[test]
+    fn resampled_depth_comes_from_the_pixel_under_each_center() {
+        let mut framebuffer = Framebuffer::new(4, 2);
+        framebuffer.zbuffer.copy_from_slice(&[0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8]);
+
+        // Mitad de tamaño: el centro de cada bloque de 2x2 cae en su pixel de abajo a la derecha
+        let mut half = Framebuffer::new(2, 1);
+        framebuffer.resample_depth(&mut half);
+        assert_eq!(half.zbuffer, vec![0.6, 0.8]);
+
+        // Doble de tamaño: cada pixel se repite
+        let mut double = Framebuffer::new(8, 4);
+        framebuffer.resample_depth(&mut double);
+        assert_eq!(&double.zbuffer[..8], &[0.1, 0.1, 0.2, 0.2, 0.3, 0.3, 0.4, 0.4]);
+        assert_eq!(&double.zbuffer[24..], &[0.5, 0.5, 0.6, 0.6, 0.7, 0.7, 0.8, 0.8]);
+    }
+
+    #[test]
+    fn accumulation_blends_in_linear_space_and_fades_out() {
+        let mut framebuffer = Framebuffer::new(1, 1);
+        framebuffer.set_accumulation(true);
+        framebuffer.buffer[0] = 0xFFFFFF;
+        framebuffer.accumulate(0.5);
+
+        // Mitad del blanco anterior sobre un frame negro: nivel 188, no 127
+        framebuffer.buffer[0] = 0x000000;
+        framebuffer.accumulate(0.5);
+        assert_eq!(framebuffer.buffer[0], 0xBCBCBC);
+
+        for _ in 0..100 {
+            framebuffer.buffer[0] = 0x000000;
+            framebuffer.accumulate(0.9);
+        }
+        assert_eq!(framebuffer.buffer[0], 0x000000, "la estela no se apagó");
+    }
+}
@@ -0,0 +1,360 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use nalgebra_glm::{Vec2, Vec3};
+use serde::Deserialize;
+use crate::obj_loader::{Obj, ObjOptions};
+
+// Lector de glTF binario (.glb) para geometría estática: la primera primitiva de la primera
+// malla, con posiciones, normales y uv. Sin skinning, animaciones ni extensiones.
+
+const GLB_MAGIC: u32 = 0x4654_6C67; // "glTF"
+const CHUNK_JSON: u32 = 0x4E4F_534A;
+const CHUNK_BIN: u32 = 0x004E_4942;
+// Tipos de componente de los accessors
+const COMPONENT_I8: u32 = 5120;
+const COMPONENT_U8: u32 = 5121;
+const COMPONENT_I16: u32 = 5122;
+const COMPONENT_U16: u32 = 5123;
+const COMPONENT_U32: u32 = 5125;
+const COMPONENT_F32: u32 = 5126;
+// Modo de dibujo de la primitiva (el único que se admite)
+const MODE_TRIANGLES: u32 = 4;
+
+#[derive(Debug)]
+pub enum GltfError {
+    Io(io::Error),
+    /// El archivo no es un .glb válido (cabecera, JSON o rangos de los buffers)
+    Invalid(String),
+    /// Es válido pero usa algo que este lector no soporta
+    Unsupported(String),
+}
+
+impl fmt::Display for GltfError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GltfError::Io(err) => write!(f, "no se pudo leer el archivo: {}", err),
+            GltfError::Invalid(reason) => write!(f, "glTF inválido: {}", reason),
+            GltfError::Unsupported(feature) => write!(f, "glTF no soportado: {}", feature),
+        }
+    }
+}
+
+impl std::error::Error for GltfError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GltfError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+// Sólo las partes del JSON que se usan
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Document {
+    #[serde(default)]
+    meshes: Vec<MeshJson>,
+    #[serde(default)]
+    accessors: Vec<Accessor>,
+    #[serde(default)]
+    buffer_views: Vec<BufferView>,
+    #[serde(default)]
+    buffers: Vec<Buffer>,
+    #[serde(default)]
+    extensions_required: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct MeshJson {
+    primitives: Vec<Primitive>,
+}
+
+#[derive(Deserialize)]
+struct Primitive {
+    attributes: HashMap<String, usize>,
+    indices: Option<usize>,
+    #[serde(default = "triangles")]
+    mode: u32,
+}
+
+fn triangles() -> u32 {
+    MODE_TRIANGLES
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Accessor {
+    buffer_view: Option<usize>,
+    #[serde(default)]
+    byte_offset: usize,
+    component_type: u32,
+    #[serde(default)]
+    normalized: bool,
+    count: usize,
+    #[serde(rename = "type")]
+    kind: String,
+    sparse: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BufferView {
+    buffer: usize,
+    #[serde(default)]
+    byte_offset: usize,
+    byte_length: usize,
+    byte_stride: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct Buffer {
+    uri: Option<String>,
+}
+
+/// Carga la primera primitiva de un .glb. Las uv de glTF ya tienen la fila 0 arriba, como
+/// las imágenes, así que no se invierten como las del .obj.
+pub fn load(path: &str, options: ObjOptions) -> Result<Obj, GltfError> {
+    let bytes = std::fs::read(path).map_err(GltfError::Io)?;
+    parse(&bytes, options)
+}
+
+fn parse(bytes: &[u8], options: ObjOptions) -> Result<Obj, GltfError> {
+    let (document, binary) = split_glb(bytes)?;
+
+    if let Some(extension) = document.extensions_required.first() {
+        return Err(GltfError::Unsupported(format!("extensión requerida {}", extension)));
+    }
+    if document.buffers.iter().any(|buffer| buffer.uri.is_some()) {
+        return Err(GltfError::Unsupported("buffers externos al .glb".to_string()));
+    }
+    let primitive = document.meshes.first()
+        .and_then(|mesh| mesh.primitives.first())
+        .ok_or_else(|| GltfError::Invalid("no tiene mallas".to_string()))?;
+    if primitive.mode != MODE_TRIANGLES {
+        return Err(GltfError::Unsupported(format!("primitiva en modo {} (sólo triángulos)", primitive.mode)));
+    }
+
+    let reader = Reader { document: &document, binary };
+    let attribute = |name: &str| primitive.attributes.get(name).copied();
+    let position_index = attribute("POSITION").ok_or_else(|| GltfError::Invalid("la primitiva no tiene POSITION".to_string()))?;
+    let positions: Vec<Vec3> = reader.floats(position_index, 3)?.chunks_exact(3).map(|p| Vec3::new(p[0], p[1], p[2])).collect();
+
+    let normals = attribute("NORMAL")
+        .map(|index| reader.floats(index, 3))
+        .transpose()?
+        .map(|values| values.chunks_exact(3).map(|n| Vec3::new(n[0], n[1], n[2])).collect::<Vec<_>>());
+    let texcoords = attribute("TEXCOORD_0")
+        .map(|index| reader.floats(index, 2))
+        .transpose()?
+        .map(|values| values.chunks_exact(2).map(|t| Vec2::new(t[0], t[1])).collect::<Vec<_>>());
+    if [normals.as_ref().map(Vec::len), texcoords.as_ref().map(Vec::len)].into_iter().flatten().any(|len| len != positions.len()) {
+        return Err(GltfError::Invalid("los atributos no tienen la misma cantidad de vértices".to_string()));
+    }
+
+    // Sin índices la primitiva es una lista de triángulos sobre los vértices en orden
+    let indices = match primitive.indices {
+        Some(index) => reader.indices(index)?,
+        None => (0..positions.len() as u32).collect(),
+    };
+    if let Some(&index) = indices.iter().find(|&&index| index as usize >= positions.len()) {
+        return Err(GltfError::Invalid(format!("índice {} fuera de rango (hay {} vértices)", index, positions.len())));
+    }
+    let triangles = indices.len() / 3 * 3;
+
+    Ok(Obj::from_triangles(positions, normals, texcoords, indices[..triangles].to_vec(), options))
+}
+
+// Separa el JSON y el bloque binario del contenedor .glb
+fn split_glb(bytes: &[u8]) -> Result<(Document, &[u8]), GltfError> {
+    let word = |offset: usize| {
+        bytes.get(offset..offset + 4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .ok_or_else(|| GltfError::Invalid("archivo truncado".to_string()))
+    };
+    if word(0)? != GLB_MAGIC {
+        return Err(GltfError::Unsupported("sólo se admite glTF binario (.glb)".to_string()));
+    }
+    if word(4)? != 2 {
+        return Err(GltfError::Unsupported(format!("versión {} de glTF", word(4)?)));
+    }
+
+    let (mut json, mut binary) = (None, &[][..]);
+    let mut offset = 12;
+    while offset + 8 <= bytes.len() {
+        let (length, kind) = (word(offset)? as usize, word(offset + 4)?);
+        // El largo viene del archivo: se suma con checked_add para que uno enorme no desborde
+        let end = (offset + 8).checked_add(length);
+        let data = end.and_then(|end| bytes.get(offset + 8..end))
+            .ok_or_else(|| GltfError::Invalid("bloque truncado".to_string()))?;
+        match kind {
+            CHUNK_JSON => json = Some(data),
+            CHUNK_BIN => binary = data,
+            _ => {}
+        }
+        offset += 8 + length;
+    }
+    let json = json.ok_or_else(|| GltfError::Invalid("no tiene bloque JSON".to_string()))?;
+    let document = serde_json::from_slice(json).map_err(|err| GltfError::Invalid(err.to_string()))?;
+    Ok((document, binary))
+}
+
+struct Reader<'a> {
+    document: &'a Document,
+    binary: &'a [u8],
+}
+
+impl Reader<'_> {
+    // Bytes de cada elemento del accessor, respetando el stride si la vista está intercalada
+    fn elements(&self, index: usize, components: usize) -> Result<(&Accessor, Vec<&[u8]>), GltfError> {
+        let accessor = self.document.accessors.get(index)
+            .ok_or_else(|| GltfError::Invalid(format!("accessor {} inexistente", index)))?;
+        if accessor.sparse.is_some() {
+            return Err(GltfError::Unsupported("accessors sparse".to_string()));
+        }
+        let expected = match components {
+            1 => "SCALAR",
+            2 => "VEC2",
+            _ => "VEC3",
+        };
+        if accessor.kind != expected {
+            return Err(GltfError::Invalid(format!("accessor {} es {} y se esperaba {}", index, accessor.kind, expected)));
+        }
+        let view = accessor.buffer_view
+            .and_then(|view| self.document.buffer_views.get(view))
+            .ok_or_else(|| GltfError::Unsupported(format!("accessor {} sin buffer view", index)))?;
+        if view.buffer != 0 {
+            return Err(GltfError::Unsupported("más de un buffer".to_string()));
+        }
+
+        // Los desplazamientos y largos vienen del JSON y pueden ser cualquier número: las
+        // cuentas se hacen con checked_*, así un archivo roto da un error y no un desborde
+        let size = components * component_size(accessor.component_type)?;
+        let stride = view.byte_stride.unwrap_or(size);
+        let view_bytes = view.byte_offset.checked_add(view.byte_length)
+            .and_then(|end| self.binary.get(view.byte_offset..end))
+            .ok_or_else(|| GltfError::Invalid("buffer view fuera del bloque binario".to_string()))?;
+        let elements = (0..accessor.count)
+            .map(|i| {
+                let start = i.checked_mul(stride).and_then(|offset| offset.checked_add(accessor.byte_offset));
+                start
+                    .and_then(|start| view_bytes.get(start..start.checked_add(size)?))
+                    .ok_or_else(|| GltfError::Invalid(format!("accessor {} fuera de su buffer view", index)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok((accessor, elements))
+    }
+
+    // Atributo de `components` flotantes por vértice; también acepta enteros normalizados
+    fn floats(&self, index: usize, components: usize) -> Result<Vec<f32>, GltfError> {
+        let (accessor, elements) = self.elements(index, components)?;
+        let value = match (accessor.component_type, accessor.normalized) {
+            (COMPONENT_F32, _) => |b: &[u8]| f32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+            (COMPONENT_U8, true) => |b: &[u8]| b[0] as f32 / 255.0,
+            (COMPONENT_U16, true) => |b: &[u8]| u16::from_le_bytes([b[0], b[1]]) as f32 / 65535.0,
+            (component, _) => return Err(GltfError::Unsupported(format!("atributo con componentes de tipo {}", component))),
+        };
+        let size = component_size(accessor.component_type)?;
+        Ok(elements.iter().flat_map(|element| element.chunks_exact(size).map(value)).collect())
+    }
+
+    fn indices(&self, index: usize) -> Result<Vec<u32>, GltfError> {
+        let (accessor, elements) = self.elements(index, 1)?;
+        let value = match accessor.component_type {
+            COMPONENT_U8 => |b: &[u8]| b[0] as u32,
+            COMPONENT_U16 => |b: &[u8]| u16::from_le_bytes([b[0], b[1]]) as u32,
+            COMPONENT_U32 => |b: &[u8]| u32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+            component => return Err(GltfError::Invalid(format!("índices de tipo {}", component))),
+        };
+        Ok(elements.into_iter().map(value).collect())
+    }
+}
+
+fn component_size(component_type: u32) -> Result<usize, GltfError> {
+    match component_type {
+        COMPONENT_U8 => Ok(1),
+        COMPONENT_U16 => Ok(2),
+        COMPONENT_U32 | COMPONENT_F32 => Ok(4),
+        COMPONENT_I8 | COMPONENT_I16 => Err(GltfError::Unsupported("componentes con signo".to_string())),
+        other => Err(GltfError::Invalid(format!("tipo de componente {}", other))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Arma un .glb con su cabecera y los dos bloques, rellenados a múltiplos de 4
+    fn glb(json: &str, binary: &[u8]) -> Vec<u8> {
+        let padded = |data: &[u8], fill: u8| {
+            let mut data = data.to_vec();
+            data.resize(data.len().div_ceil(4) * 4, fill);
+            data
+        };
+        let (json, binary) = (padded(json.as_bytes(), b' '), padded(binary, 0));
+        let mut bytes = Vec::new();
+        bytes.extend(GLB_MAGIC.to_le_bytes());
+        bytes.extend(2u32.to_le_bytes());
+        bytes.extend(((12 + 8 + json.len() + 8 + binary.len()) as u32).to_le_bytes());
+        for (kind, data) in [(CHUNK_JSON, &json), (CHUNK_BIN, &binary)] {
+            bytes.extend((data.len() as u32).to_le_bytes());
+            bytes.extend(kind.to_le_bytes());
+            bytes.extend(data);
+        }
+        bytes
+    }
+
+    // Un triángulo: tres posiciones (36 bytes) y tres índices u16 (6 bytes)
+    fn triangle(view_offset: &str, accessor_offset: &str) -> Vec<u8> {
+        let mut binary = Vec::new();
+        for value in [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0] {
+            binary.extend(value.to_le_bytes());
+        }
+        for index in [0u16, 1, 2] {
+            binary.extend(index.to_le_bytes());
+        }
+        let json = format!(
+            r#"{{
+                "meshes": [{{ "primitives": [{{ "attributes": {{ "POSITION": 0 }}, "indices": 1 }}] }}],
+                "accessors": [
+                    {{ "bufferView": 0, "byteOffset": {accessor_offset}, "componentType": 5126, "count": 3, "type": "VEC3" }},
+                    {{ "bufferView": 1, "componentType": 5123, "count": 3, "type": "SCALAR" }}
+                ],
+                "bufferViews": [
+                    {{ "buffer": 0, "byteOffset": {view_offset}, "byteLength": 36 }},
+                    {{ "buffer": 0, "byteOffset": 36, "byteLength": 6 }}
+                ],
+                "buffers": [{{ "byteLength": 44 }}]
+            }}"#
+        );
+        glb(&json, &binary)
+    }
+
+    fn invalid(bytes: &[u8]) -> bool {
+        matches!(parse(bytes, ObjOptions::default()), Err(GltfError::Invalid(_)))
+    }
+
+    #[test]
+    fn loads_a_small_glb() {
+        let obj = parse(&triangle("0", "0"), ObjOptions::default()).unwrap();
+        let vertices = obj.get_vertex_array();
+        let positions: Vec<Vec3> = vertices.iter().map(|vertex| vertex.position).collect();
+        assert_eq!(positions, vec![Vec3::zeros(), Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0)]);
+        // Sin NORMAL se calculan: el triángulo es antihorario visto desde +Z
+        assert!(vertices.iter().all(|vertex| (vertex.normal - Vec3::new(0.0, 0.0, 1.0)).magnitude() < 1e-5));
+    }
+
+    #[test]
+    fn out_of_range_offsets_are_errors_not_overflows() {
+        let huge = usize::MAX.to_string();
+        assert!(invalid(&triangle(&huge, "0")));
+        assert!(invalid(&triangle("0", &huge)));
+        assert!(invalid(&triangle("16", "0")));
+
+        // Bloque que dice medir más que el archivo
+        let mut truncated = triangle("0", "0");
+        truncated[12..16].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(invalid(&truncated));
+        assert!(invalid(&triangle("0", "0")[..40]));
+    }
+}
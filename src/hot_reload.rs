@@ -0,0 +1,47 @@
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime};
+
+// Cada cuánto se revisan las fechas de modificación
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Vigila un conjunto de archivos revisando su fecha de modificación como mucho una vez por
+/// segundo. No hay hilos ni notificaciones del sistema: se llama a `poll` en cada frame.
+pub struct FileWatcher {
+    // Ruta y última fecha vista (None si el archivo no existía)
+    files: Vec<(String, Option<SystemTime>)>,
+    last_poll: Instant,
+}
+
+impl FileWatcher {
+    pub fn new(paths: &[&str]) -> Self {
+        FileWatcher {
+            files: paths.iter().map(|path| (path.to_string(), modified(path))).collect(),
+            last_poll: Instant::now(),
+        }
+    }
+
+    /// Archivos que aparecieron o cambiaron desde la última revisión. Si se borran no se
+    /// avisa: quien los cargó se queda con lo que tenía.
+    pub fn poll(&mut self) -> Vec<String> {
+        if self.last_poll.elapsed() < POLL_INTERVAL {
+            return Vec::new();
+        }
+        self.last_poll = Instant::now();
+
+        let mut changed = Vec::new();
+        for (path, last) in &mut self.files {
+            let current = modified(path);
+            if current != *last {
+                *last = current;
+                if current.is_some() {
+                    changed.push(path.clone());
+                }
+            }
+        }
+        changed
+    }
+}
+
+fn modified(path: &str) -> Option<SystemTime> {
+    Path::new(path).metadata().and_then(|metadata| metadata.modified()).ok()
+}
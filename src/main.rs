@@ -1,7 +1,9 @@
-use nalgebra_glm::{Vec3, Mat4, look_at, perspective};
-use minifb::{Key, Window, WindowOptions};
-use std::f32::consts::PI;
+use nalgebra_glm::{Vec2, Vec3, Vec4, Mat4, perspective, translation, quat_to_mat4, quat_conjugate};
+use minifb::{Key, KeyRepeat, MouseButton, MouseMode, Window, WindowOptions};
+use std::f32::consts::{PI, TAU};
 use std::time::{Duration, Instant};
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
 
 mod triangle;
 mod obj_loader;
@@ -12,22 +14,325 @@ mod vertex;
 mod fragments;
 mod camera;
 mod skybox;
+mod clipping;
+mod model;
+mod stats;
+mod ship;
+mod noise;
+mod texture;
+mod screenshot;
+mod post;
+mod orbit;
+mod text;
+mod picking;
+mod material;
+mod mesh;
+mod gltf_loader;
+mod hot_reload;
+mod toml_reader;
+mod scene;
+mod asteroids;
+mod particles;
+mod comet;
+mod sim;
 
 use vertex::Vertex;
-use camera::Camera;
-use obj_loader::Obj;
-use framebuffer::Framebuffer;
-use skybox::Skybox;
-use shaders::{fragment_shader, moon_position, vertex_shader, planet_orbit, ShaderType};
-use triangle::triangle;
-
-pub struct Uniforms {
+use fragments::Fragments;
+use model::Model;
+use stats::RenderStats;
+use camera::{Camera, CameraMode, Collider, Path, PathRecorder, PathPlayback};
+use ship::Ship;
+use color::Color;
+use noise::perlin;
+use texture::Texture;
+use obj_loader::{Obj, ObjOptions, Pivot};
+use hot_reload::FileWatcher;
+use scene::{BodyConfig, MoonConfig, SceneConfig};
+use asteroids::AsteroidBelt;
+use comet::CometTail;
+use sim::{SimClock, TICKS_PER_SECOND};
+use framebuffer::{Framebuffer, TileGrid, TileBuffer, TILE_SIZE};
+use skybox::{Skybox, SkyboxConfig};
+use post::{Bloom, ColorGrade};
+use orbit::{Orbit, OrbitStyle, OrbitTrail};
+use picking::{Ray, ScreenRect};
+use material::Material;
+use shaders::{cycle_angle, fragment_shader, moon_orbit_angle, moon_position, smoothstep, vertex_shader, Fog, ParamField, ShaderParams, ShaderType, DEBUG_MODE_COUNT, DEBUG_OVERDRAW, RING_INNER_RADIUS, RING_OUTER_RADIUS};
+use triangle::{triangle, line, point, is_finite, is_front_facing, screen_bounds};
+use clipping::{clip_near, project_to_screen};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+// Recorre un slice en paralelo con rayon, o secuencialmente sin la feature `parallel`
+macro_rules! maybe_par_iter {
+    ($slice:expr) => {{
+        #[cfg(feature = "parallel")]
+        let iter = $slice.par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let iter = $slice.iter();
+        iter
+    }};
+}
+
+// Color sin iluminar de las aristas y los puntos, que no se pierde en las caras a oscuras
+const EDGE_COLOR: Color = Color::new(200, 200, 200);
+
+// Cómo se rasterizan los triángulos ensamblados
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RenderMode {
+    Filled,
+    Wireframe,
+    Points,
+}
+
+impl RenderMode {
+    fn next(self) -> Self {
+        match self {
+            RenderMode::Filled => RenderMode::Wireframe,
+            RenderMode::Wireframe => RenderMode::Points,
+            RenderMode::Points => RenderMode::Filled,
+        }
+    }
+}
+
+// Estilo de la iluminación: degradado realista o por bandas con contorno (toon)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShadingStyle {
+    Smooth,
+    Toon,
+}
+
+impl ShadingStyle {
+    fn toggle(self) -> Self {
+        match self {
+            ShadingStyle::Smooth => ShadingStyle::Toon,
+            ShadingStyle::Toon => ShadingStyle::Smooth,
+        }
+    }
+}
+
+// Qué se dibuja de las órbitas: nada, la estela de lo que recorrió cada cuerpo o la elipse
+// completa
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum OrbitDisplay {
+    Hidden,
+    Trails,
+    Paths,
+}
+
+impl OrbitDisplay {
+    fn next(self) -> Self {
+        match self {
+            OrbitDisplay::Hidden => OrbitDisplay::Trails,
+            OrbitDisplay::Trails => OrbitDisplay::Paths,
+            OrbitDisplay::Paths => OrbitDisplay::Hidden,
+        }
+    }
+}
+
+// Lo que se eligió con un clic: un cuerpo de la escena o una de sus lunas (índices en
+// BodyConfig::moons)
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Selection {
+    Body(usize),
+    Moon { body: usize, moon: usize },
+}
+
+impl Selection {
+    // Cuerpo elegido o el que orbita la luna elegida
+    fn body(self) -> usize {
+        match self {
+            Selection::Body(body) | Selection::Moon { body, .. } => body,
+        }
+    }
+
+    // Las lunas no tienen nombre en la escena: se nombran por su número y su planeta
+    fn name(self, bodies: &[BodyConfig]) -> String {
+        match self {
+            Selection::Body(body) => bodies[body].name.clone(),
+            Selection::Moon { body, moon } => format!("Luna {} de {}", moon + 1, bodies[body].name),
+        }
+    }
+}
+
+// Desenfoque de movimiento: peso inicial del frame anterior, paso de , / . y máximo (con 1
+// la imagen se congelaría)
+const DEFAULT_PERSISTENCE: f32 = 0.25;
+const PERSISTENCE_STEP: f32 = 0.05;
+const MAX_PERSISTENCE: f32 = 0.9;
+
+// Franjas y gajos de la esfera generada; son los de models/sphere.obj (960 triángulos)
+const SPHERE_STACKS: usize = 16;
+const SPHERE_SLICES: usize = 32;
+// Distancia a la que se fusionan los vértices de sphere.obj (su costura los repite)
+const SPHERE_WELD: Option<f32> = Some(1e-4);
+// Modelos que se leen al inicio y se recargan si cambian mientras corre el programa; la
+// nave se normaliza apoyada en el origen
+const SPHERE_MODEL: &str = "models/sphere.obj";
+const RING_MODEL: &str = "models/ring.obj";
+const SHIP_MODEL: &str = "models/ship.obj";
+const SHIP_PIVOT: Option<Pivot> = Some(Pivot::Bottom);
+// Divisiones del anillo; con menos el borde interior se ve poligonal de cerca
+const RING_SEGMENTS: usize = 256;
+// Icosfera de las rocas cercanas del cinturón (80 triángulos)
+const ASTEROID_SUBDIVISIONS: u32 = 1;
+
+// Resolución interna respecto a la ventana, en potencias de 2: -2 es 1/4, 0 la nativa y 2
+// el cuádruple. F3 / F4 la bajan y suben; F2 alterna entre la nativa y SSAA_LEVEL.
+const MIN_RENDER_SCALE: i32 = -2;
+const MAX_RENDER_SCALE: i32 = 2;
+const SSAA_LEVEL: i32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct RenderScale {
+    level: i32,
+}
+
+impl RenderScale {
+    const NATIVE: RenderScale = RenderScale { level: 0 };
+
+    fn factor(self) -> f32 {
+        2.0f32.powi(self.level)
+    }
+
+    fn with_level(level: i32) -> Self {
+        RenderScale { level: level.clamp(MIN_RENDER_SCALE, MAX_RENDER_SCALE) }
+    }
+
+    fn halved(self) -> Self {
+        RenderScale::with_level(self.level - 1)
+    }
+
+    fn doubled(self) -> Self {
+        RenderScale::with_level(self.level + 1)
+    }
+
+    /// Tamaño del framebuffer para una ventana de `width` x `height`
+    fn render_size(self, width: usize, height: usize) -> (usize, usize) {
+        if self.level >= 0 {
+            (width << self.level, height << self.level)
+        } else {
+            ((width >> -self.level).max(1), (height >> -self.level).max(1))
+        }
+    }
+}
+
+impl std::fmt::Display for RenderScale {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.level >= 0 { write!(f, "{}x", 1 << self.level) } else { write!(f, "1/{}x", 1 << -self.level) }
+    }
+}
+
+// Rango del FOV vertical ajustable con Ctrl + rueda (radianes)
+const MIN_FOV: f32 = 10.0 * PI / 180.0;
+const MAX_FOV: f32 = 100.0 * PI / 180.0;
+
+// Radio inicial de la órbita de cámara, en múltiplos de la escala del planeta
+const ORBIT_DISTANCE_FACTOR: f32 = 4.0;
+// Distancia detrás y altura de la cámara de seguimiento, en múltiplos de la escala del planeta
+const FOLLOW_DISTANCE_FACTOR: f32 = 6.0;
+const FOLLOW_HEIGHT_FACTOR: f32 = 2.0;
+// Radianes de alabeo por segundo con Z/X
+const ROLL_SPEED: f32 = 1.8;
+// Unidades de rotación por segundo con las flechas
+const ROTATION_SPEED: f32 = 60.0;
+// Pasos de zoom por segundo con W/S en modo órbita
+const ORBIT_ZOOM_SPEED: f32 = 12.0;
+// La velocidad de traslación (unidades/s) es proporcional a la distancia a la superficie
+// del planeta más cercano, dentro de estos límites
+const SPEED_PER_DISTANCE: f32 = 1.5;
+const MIN_SPEED: f32 = 1.0;
+const MAX_SPEED: f32 = 500.0;
+// Multiplicador con Shift (y divisor con Ctrl)
+const SPEED_BOOST: f32 = 10.0;
+// Factor aplicado a la sensibilidad por cada pulsación de Shift + +/-
+const SENSITIVITY_STEP: f32 = 1.25;
+// Tiempo que se muestra en el título un valor recién cambiado
+const NOTICE_DURATION: Duration = Duration::from_secs(2);
+// Distancia a la superficie del sol desde la que empieza el calor
+const HEAT_DISTANCE: f32 = 30.0;
+// Desplazamiento máximo de la vista al temblar, en unidades del mundo
+const MAX_SHAKE: f32 = 0.3;
+// Margen sobre el radio de cada cuerpo que la cámara no puede atravesar
+const COLLISION_MARGIN: f32 = 1.2;
+// Archivo donde se guarda la ruta de cámara grabada con R
+const CAMERA_PATH_FILE: &str = "camera_path.json";
+// Delta máximo por frame, para que una pausa larga no lance la cámara
+const MAX_FRAME_DELTA: f32 = 0.1;
+// Rapidez (por segundo) con la que el brillo del motor sigue al empuje
+const THRUST_RESPONSE: f32 = 6.0;
+// Radio exterior del halo del sol, en radios del disco; brillo en el borde del disco
+const CORONA_SCALE: f32 = 2.6;
+const CORONA_INTENSITY: f32 = 0.9;
+// Puntos de la elipse completa de cada órbita
+const PATH_SAMPLES: usize = 256;
+// Puntos máximos de la estela de cada órbita y distancia mínima entre puntos seguidos; en
+// la escena por defecto alcanzan para cerrar la órbita más grande (unas 440 unidades)
+const TRAIL_LENGTH: usize = 2000;
+const TRAIL_MIN_DISTANCE: f32 = 0.25;
+// Pasos del reloj por ciclo de los rayos del halo
+const CORONA_PERIOD: u32 = 4096;
+const CORONA_SEED: u32 = 71;
+// Carpeta de las texturas de los planetas
+const TEXTURE_DIR: &str = "textures";
+// Normal del plano de la Vía Láctea, inclinado respecto al plano de las órbitas
+const GALACTIC_PLANE: Vec3 = Vec3::new(0.35, 0.9, 0.25);
+// Semilla del cielo procedural: el mismo cielo en cada ejecución
+const SKY_SEED: u64 = 2024;
+// Caras del skybox opcional, en el orden de Skybox::from_cubemap
+const SKYBOX_FACES: [&str; 6] = [
+    "textures/skybox/right.png",
+    "textures/skybox/left.png",
+    "textures/skybox/top.png",
+    "textures/skybox/bottom.png",
+    "textures/skybox/front.png",
+    "textures/skybox/back.png",
+];
+// Duración del vuelo hacia un planeta con las teclas numéricas
+const WARP_DURATION: Duration = Duration::from_secs(2);
+
+// Unidades de rotación de cámara por pixel de movimiento del mouse
+const MOUSE_SENSITIVITY: f32 = 0.2;
+
+// Planos del frustum, compartidos con la vista de profundidad
+pub const NEAR_PLANE: f32 = 0.1;
+pub const FAR_PLANE: f32 = 1000.0;
+
+pub struct Uniforms<'a> {
     model_matrix: Mat4,
     view_matrix: Mat4,
     projection_matrix: Mat4,
     viewport_matrix: Mat4,
-    time: u32,
+    // Tiempo de la simulación en pasos de SimClock
+    time: f32,
     debug_mode: u32,
+    // Descarta caras traseras; se desactiva para mallas de doble cara como el anillo
+    cull_backfaces: bool,
+    render_mode: RenderMode,
+    style: ShadingStyle,
+    // Dithering ordenado al empaquetar el color (F10 lo desactiva para comparar)
+    dither: bool,
+    // Posición del sol (fuente de luz) y del ojo, en el mundo
+    light_position: Vec3,
+    camera_position: Vec3,
+    // Esferas (centro y radio en el mundo) que pueden hacer sombra sobre el objeto
+    shadow_spheres: &'a [(Vec3, f32)],
+    // Cercanía al sol (0 lejos, 1 pegado a la superficie) para el tinte de calor
+    heat: f32,
+    // Imagen que muestrea ShaderType::Textured
+    texture: Option<&'a Texture>,
+    // Materiales del modelo que se dibuja (los del .mtl), a los que apunta Fragments::material
+    materials: &'a [Material],
+    // Cielo que refleja la nave y empuje de sus motores (0 quieta, 1 acelerando)
+    skybox: Option<&'a Skybox>,
+    thrust: f32,
+    // Cuánto se inclina el polo norte del planeta hacia el sol: 1 en pleno verano del norte,
+    // -1 en su invierno y 0 en los equinoccios o sin inclinación axial. Marca la estación de
+    // los casquetes polares.
+    season: f32,
+    // Niebla por distancia; el cielo no la lleva porque está en el infinito
+    fog: Option<Fog>,
 }
 
 fn create_model_matrix(translation: Vec3, scale: f32, rotation: Vec3) -> Mat4 {
@@ -68,17 +373,15 @@ fn create_model_matrix(translation: Vec3, scale: f32, rotation: Vec3) -> Mat4 {
     transform_matrix * rotation_matrix
 }
 
-fn create_view_matrix(eye: Vec3, center: Vec3, up: Vec3) -> Mat4 {
-    look_at(&eye, &center, &up)
+// Vista = inversa de la pose de la cámara: deshace la traslación y luego la orientación
+fn create_view_matrix(camera: &Camera) -> Mat4 {
+    quat_to_mat4(&quat_conjugate(&camera.orientation())) * translation(&(-camera.eye))
 }
 
-fn create_perspective_matrix(window_width: f32, window_height: f32) -> Mat4 {
-    let fov = 45.0 * PI / 180.0;
+fn create_perspective_matrix(fov: f32, window_width: f32, window_height: f32) -> Mat4 {
     let aspect_ratio = window_width / window_height;
-    let near = 0.1;
-    let far = 1000.0;
-
-    perspective(fov, aspect_ratio, near, far)
+    // nalgebra_glm recibe primero el aspect ratio y luego el FOV vertical
+    perspective(aspect_ratio, fov, NEAR_PLANE, FAR_PLANE)
 }
 
 fn create_viewport_matrix(width: f32, height: f32) -> Mat4 {
@@ -90,114 +393,814 @@ fn create_viewport_matrix(width: f32, height: f32) -> Mat4 {
     )
 }
 
-fn render_rings(framebuffer: &mut Framebuffer, planet_position: Vec3, uniforms: &Uniforms, vertex_array: &[Vertex]) {
+// Objeto translúcido pendiente: se dibuja después de todos los opacos
+struct TransparentDraw<'a> {
+    uniforms: Uniforms<'a>,
+    model: &'a Model,
+    shader: ShaderType,
+    params: ShaderParams,
+}
+
+// Dibuja un objeto ya, o lo deja en `transparent` si su shader es translúcido
+fn draw<'a>(
+    framebuffer: &mut Framebuffer,
+    transparent: &mut Vec<TransparentDraw<'a>>,
+    uniforms: Uniforms<'a>,
+    model: &'a Model,
+    shader: ShaderType,
+    params: ShaderParams,
+    stats: &mut RenderStats,
+) {
+    if shader.is_transparent() {
+        transparent.push(TransparentDraw { uniforms, model, shader, params });
+    } else {
+        render(framebuffer, &uniforms, model, &shader, &params, stats);
+    }
+}
+
+// Los translúcidos se mezclan sobre lo ya dibujado, así que van de atrás hacia adelante
+// según la profundidad en la vista del centro de su esfera envolvente
+fn render_transparent(framebuffer: &mut Framebuffer, mut transparent: Vec<TransparentDraw>, stats: &mut RenderStats) {
+    let view_depth = |draw: &TransparentDraw| {
+        let center = draw.model.bounds.center;
+        (draw.uniforms.view_matrix * draw.uniforms.model_matrix * Vec4::new(center.x, center.y, center.z, 1.0)).z
+    };
+    // La cámara mira hacia -z: lo más lejano tiene la z más negativa
+    transparent.sort_by(|a, b| view_depth(a).total_cmp(&view_depth(b)));
+    for draw in &transparent {
+        render(framebuffer, &draw.uniforms, draw.model, &draw.shader, &draw.params, stats);
+    }
+}
+
+// `shadow_spheres` son el planeta y sus lunas, que proyectan su sombra sobre el anillo
+#[allow(clippy::too_many_arguments)]
+fn render_rings<'a>(
+    framebuffer: &mut Framebuffer,
+    transparent: &mut Vec<TransparentDraw<'a>>,
+    planet_position: Vec3,
+    shadow_spheres: &'a [(Vec3, f32)],
+    ring_scale: f32,
+    axial_tilt: f32,
+    uniforms: &Uniforms<'a>,
+    ring_model: &'a Model,
+    stats: &mut RenderStats,
+) {
     let ring_uniforms = Uniforms {
-        model_matrix: create_model_matrix(planet_position, 2.0, Vec3::new(0.0, 0.0, 0.0)),
-        view_matrix: uniforms.view_matrix,
-        projection_matrix: uniforms.projection_matrix,
-        viewport_matrix: uniforms.viewport_matrix,
-        time: uniforms.time,
-        debug_mode: uniforms.debug_mode,
+        // Con la misma inclinación que el planeta, el anillo queda en su ecuador
+        model_matrix: create_model_matrix(planet_position, ring_scale, Vec3::new(0.0, 0.0, axial_tilt)),
+        cull_backfaces: false,
+        shadow_spheres,
+        materials: &ring_model.materials,
+        ..*uniforms
     };
     let ring_shader = ShaderType::Ring;
-    render(framebuffer, &ring_uniforms, vertex_array, &ring_shader);
+    draw(framebuffer, transparent, ring_uniforms, ring_model, ring_shader, ShaderParams::for_type(&ring_shader), stats);
+}
+
+// Ver Uniforms::season
+fn season(body: &BodyConfig, position: Vec3, light_position: Vec3) -> f32 {
+    let max_lean = body.axial_tilt.to_radians().sin();
+    match (light_position - position).try_normalize(f32::EPSILON) {
+        Some(to_sun) if max_lean.abs() > f32::EPSILON => (body.pole().dot(&to_sun) / max_lean).clamp(-1.0, 1.0),
+        _ => 0.0,
+    }
 }
 
-fn moon_render(framebuffer: &mut Framebuffer, position: Vec3, time: u32, view_matrix: Mat4, projection_matrix: Mat4, viewport_matrix: Mat4, sphere_vertex_arrays: &[Vertex]) {
-    let moon_pos = moon_position(time as f32, 1.3);
+// La luna recibe la sombra de su planeta (`planet_shadow`: su centro y radio en el mundo)
+fn moon_render(framebuffer: &mut Framebuffer, position: Vec3, planet_shadow: &(Vec3, f32), moon: &MoonConfig, uniforms: &Uniforms, sphere_model: &Model, stats: &mut RenderStats) {
+    let orbit_angle = moon_orbit_angle(uniforms.time, moon.orbit_speed, moon.phase.to_radians());
+    let spin = moon.spin_angle(uniforms.time, orbit_angle);
     let moon_uniforms = Uniforms {
-        model_matrix: create_model_matrix(position + moon_pos, 0.5, Vec3::new(0.0, 0.0, 0.0)),
-        view_matrix,
-        projection_matrix,
-        viewport_matrix,
-        time,
-        debug_mode: 0,
+        model_matrix: create_model_matrix(moon_world_position(position, moon, uniforms.time), moon.scale(), Vec3::new(0.0, spin, 0.0)),
+        shadow_spheres: std::slice::from_ref(planet_shadow),
+        season: 0.0,
+        ..*uniforms
+    };
+    render(framebuffer, &moon_uniforms, sphere_model, &moon.shader, &ShaderParams::for_type(&moon.shader), stats);
+}
+
+// La posición de la luna es la de su planeta en este frame más el desplazamiento de su órbita
+fn moon_world_position(planet_position: Vec3, moon: &MoonConfig, time: f32) -> Vec3 {
+    planet_position + moon_position(time, moon.orbit_radius, moon.orbit_speed, moon.inclination.to_radians(), moon.phase.to_radians())
+}
+
+// Radio en pixeles desde el que una roca lejana se sombrea pixel a pixel
+const IMPOSTOR_MIN_RADIUS: f32 = 1.0;
+
+// Los asteroides cercanos van con malla por el camino normal. Los lejanos son un disco del
+// tamaño que tendría la roca en pantalla a la profundidad de su centro: si mide más de un
+// pixel cada uno lleva la normal de la esfera en ese punto y se sombrea como la malla; si
+// no, todo el disco toma el color con una normal entre la cámara y el sol, que se parece al
+// promedio de la cara que se ve. El color sale del punto del modelo, con la rotación de
+// la roca, que mira hacia esa normal; así, al pasar de punto a malla no cambian ni el
+// tamaño, ni el brillo, ni las manchas.
+fn render_belt(framebuffer: &mut Framebuffer, belt: &AsteroidBelt, uniforms: &Uniforms, asteroid_model: &Model, stats: &mut RenderStats) {
+    let shader = ShaderType::Asteroid;
+    let params = ShaderParams::for_type(&shader);
+    for &index in belt.near() {
+        let scale = belt.radius(index) / asteroid_model.bounds.radius;
+        let asteroid_uniforms = Uniforms {
+            model_matrix: create_model_matrix(belt.position(index), scale, belt.rotation(index, uniforms.time)),
+            ..*uniforms
+        };
+        render(framebuffer, &asteroid_uniforms, asteroid_model, &shader, &params, stats);
+    }
+
+    // Radio en pixeles de algo de radio 1 a una unidad de distancia de la cámara
+    let pixels_per_unit = (uniforms.projection_matrix[(1, 1)] * uniforms.viewport_matrix[(1, 1)]).abs();
+    // Derecha de la cámara en el mundo (primera fila de la vista)
+    let camera_right = Vec3::new(uniforms.view_matrix[(0, 0)], uniforms.view_matrix[(0, 1)], uniforms.view_matrix[(0, 2)]);
+    for index in belt.far() {
+        let position = belt.position(index);
+        let to_camera = (uniforms.camera_position - position).try_normalize(f32::EPSILON).unwrap_or(Vec3::y());
+        let to_sun = (uniforms.light_position - position).try_normalize(f32::EPSILON).unwrap_or(to_camera);
+        let normal = (to_camera + to_sun).try_normalize(f32::EPSILON).unwrap_or(to_camera);
+        // Ejes del disco: mira hacia el ojo, no sólo hacia atrás de la cámara, así las rocas en
+        // los bordes de la pantalla se iluminan igual que su malla
+        let right = (camera_right - to_camera * camera_right.dot(&to_camera)).try_normalize(f32::EPSILON).unwrap_or(camera_right);
+        let up = to_camera.cross(&right);
+        // Deshace la rotación de la roca para llevar una normal del mundo a su modelo
+        let rotation = create_model_matrix(Vec3::zeros(), 1.0, belt.rotation(index, uniforms.time)).transpose();
+        let model_point = |normal: Vec3| {
+            let local = (rotation * Vec4::new(normal.x, normal.y, normal.z, 0.0)).xyz();
+            asteroid_model.bounds.center + local * asteroid_model.bounds.radius
+        };
+        let point_uniforms = Uniforms {
+            model_matrix: create_model_matrix(position, 1.0, Vec3::new(0.0, 0.0, 0.0)),
+            ..*uniforms
+        };
+
+        let mut vertex = vertex_shader(&Vertex::new(Vec3::zeros(), normal, Vec2::zeros()), &point_uniforms);
+        let clip = vertex.clip_position;
+        if clip.w <= 0.0 || clip.z < -clip.w {
+            continue;
+        }
+        project_to_screen(&mut vertex, &uniforms.viewport_matrix);
+        let (cx, cy) = (vertex.transformed_position.x, vertex.transformed_position.y);
+        let radius = belt.radius(index) * pixels_per_unit / clip.w;
+        let reach = radius.ceil() as i64;
+        let margin = reach as f32 + 1.0;
+        if cx < -margin || cy < -margin || cx > framebuffer.width as f32 + margin || cy > framebuffer.height as f32 + margin {
+            continue;
+        }
+        let fragment = Fragments { vertex_pos: model_point(normal), ..point(&vertex) };
+        let Some(color) = fragment_shader(&fragment, &point_uniforms, &shader, &params) else { continue };
+
+        // Las rocas de menos de un pixel se mezclan según cuánto del pixel cubren, así no
+        // titilan al alejarse
+        let coverage = (PI * radius * radius).min(1.0);
+        let (x, y) = (cx.floor() as i64, cy.floor() as i64);
+        for dy in -reach..=reach {
+            for dx in -reach..=reach {
+                let (px, py) = (x + dx, y + dy);
+                // Desplazamiento del centro del pixel desde el centro del disco, en radios
+                let (ox, oy) = ((px as f32 + 0.5 - cx) / radius, (py as f32 + 0.5 - cy) / radius);
+                let inside = dx == 0 && dy == 0 || ox * ox + oy * oy <= 1.0;
+                if !inside || px < 0 || py < 0 {
+                    continue;
+                }
+                let color = if radius < IMPOSTOR_MIN_RADIUS {
+                    color
+                } else {
+                    let normal = (right * ox - up * oy + to_camera * (1.0 - ox * ox - oy * oy).max(0.0).sqrt()).normalize();
+                    let surface = Fragments { normal, world_position: position + normal * belt.radius(index), vertex_pos: model_point(normal), ..fragment };
+                    let Some(color) = fragment_shader(&surface, &point_uniforms, &shader, &params) else { continue };
+                    color
+                };
+                let (px, py) = (px as usize, py as usize);
+                let color = if uniforms.dither { color.dithered(px, py) } else { color };
+                if coverage >= 1.0 {
+                    framebuffer.set_pixel(px, py, fragment.depth, color.to_hex());
+                } else {
+                    framebuffer.blend_pixel(px, py, fragment.depth, color, coverage);
+                }
+            }
+        }
+    }
+}
+
+// Halo del sol en pantalla: brillo aditivo alrededor del disco proyectado, con rayos que
+// varían lentamente con el ángulo. Se prueba contra la profundidad del centro del sol, así
+// el disco y lo que esté delante (planetas, la nave) no quedan cubiertos.
+fn render_corona(framebuffer: &mut Framebuffer, sun_position: Vec3, sun_radius: f32, uniforms: &Uniforms) {
+    let clip = uniforms.projection_matrix * uniforms.view_matrix * Vec4::new(sun_position.x, sun_position.y, sun_position.z, 1.0);
+    if clip.w <= sun_radius {
+        return;
+    }
+    let ndc = clip / clip.w;
+    let center = uniforms.viewport_matrix * Vec4::new(ndc.x, ndc.y, ndc.z, 1.0);
+
+    // Radio del disco en pixeles con la escala vertical de la proyección
+    let disc_radius = sun_radius * uniforms.projection_matrix[(1, 1)] / clip.w * framebuffer.height as f32 / 2.0;
+    let outer_radius = disc_radius * CORONA_SCALE;
+    let x0 = (center.x - outer_radius).floor().max(0.0) as usize;
+    let y0 = (center.y - outer_radius).floor().max(0.0) as usize;
+    let x1 = ((center.x + outer_radius).ceil().max(0.0) as usize).min(framebuffer.width);
+    let y1 = ((center.y + outer_radius).ceil().max(0.0) as usize).min(framebuffer.height);
+
+    let phase = cycle_angle(uniforms.time, CORONA_PERIOD);
+    let corona_color = Color::new(255, 170, 60);
+
+    for y in y0..y1 {
+        for x in x0..x1 {
+            let (dx, dy) = (x as f32 + 0.5 - center.x, y as f32 + 0.5 - center.y);
+            let distance = (dx * dx + dy * dy).sqrt();
+            if distance >= outer_radius {
+                continue;
+            }
+            let falloff = 1.0 - ((distance - disc_radius) / (outer_radius - disc_radius)).max(0.0);
+            let angle = dy.atan2(dx);
+            let rays = 0.7 + 0.3 * perlin(&Vec3::new(angle.cos() * 3.0 + phase.cos() * 0.5, angle.sin() * 3.0 + phase.sin() * 0.5, 0.0), CORONA_SEED);
+            framebuffer.add_pixel(x, y, ndc.z, corona_color * (falloff * falloff * rays * CORONA_INTENSITY));
+        }
+    }
+}
+
+// Tamaño del texto del HUD (pixeles de la ventana por pixel de la fuente) y su margen
+const HUD_SCALE: usize = 2;
+const HUD_MARGIN: usize = 8;
+
+// Contador de FPS en la esquina superior derecha y, debajo, la velocidad del reloj de la
+// simulación; con sombra para leerse sobre el sol
+fn draw_status(framebuffer: &mut Framebuffer, fps: f32, clock: &SimClock) {
+    let scale = HUD_SCALE;
+    let lines = [format!("{:.0} FPS", fps), clock.label()];
+    for (row, text) in lines.iter().enumerate() {
+        let (width, _) = text::text_size(text, scale);
+        let x = framebuffer.width as i32 - (width + HUD_MARGIN) as i32;
+        let y = (HUD_MARGIN + row * text::LINE_HEIGHT * scale) as i32;
+        framebuffer.draw_text(x + scale as i32, y + scale as i32, text, Color::BLACK, scale);
+        framebuffer.draw_text(x, y, text, Color::new(230, 230, 230), scale);
+    }
+}
+
+// Mira: brazos de la cruz (y hueco central) en pixeles de la ventana; con un blanco cambia
+// de color y enmarca al planeta
+const RETICLE_ARM: usize = 6;
+const RETICLE_GAP: usize = 3;
+const RETICLE_COLOR: Color = Color::rgba(220, 220, 220, 200);
+const RETICLE_TARGET_COLOR: Color = Color::new(255, 170, 40);
+// Largo mínimo de los lados del marco y de sus esquinas
+const BRACKET_MIN_HALF_SIZE: f32 = 14.0;
+const BRACKET_CORNER: usize = 8;
+
+// Planeta al que apunta la mira: centro y medio lado del marco en pantalla, y su etiqueta
+struct AimTarget {
+    center: Vec2,
+    half_size: f32,
+    label: String,
+}
+
+// Lanza el rayo del centro de la pantalla contra las esferas (índice, centro, radio) y
+// devuelve el índice y la distancia de la más cercana junto con su silueta proyectada
+fn aim_target(
+    framebuffer: &Framebuffer,
+    spheres: impl Iterator<Item = (usize, Vec3, f32)> + Clone,
+    view_matrix: &Mat4,
+    projection_matrix: &Mat4,
+    viewport_matrix: &Mat4,
+) -> Option<(usize, f32, Vec2, f32)> {
+    let (width, height) = (framebuffer.width as f32, framebuffer.height as f32);
+    let ray = Ray::from_screen(width / 2.0, height / 2.0, width, height, view_matrix, projection_matrix)?;
+    let (index, distance) = picking::pick(&ray, spheres.clone())?;
+    let (_, center, radius) = spheres.into_iter().find(|(i, _, _)| *i == index)?;
+
+    // El marco cubre la esfera proyectada
+    let rect = picking::project_sphere(center, radius, view_matrix, projection_matrix, viewport_matrix)?;
+    let size = rect.size();
+    Some((index, distance, rect.center(), size.x.max(size.y) / 2.0))
+}
+
+// Cruz en el centro de la pantalla
+fn draw_reticle(framebuffer: &mut Framebuffer, target: Option<&AimTarget>) {
+    let color = if target.is_some() { RETICLE_TARGET_COLOR } else { RETICLE_COLOR };
+    let (arm, gap, thickness) = (RETICLE_ARM as i64, RETICLE_GAP as i64, 1);
+    let (cx, cy) = ((framebuffer.width / 2) as i64, (framebuffer.height / 2) as i64);
+    framebuffer.fill_rect(cx - gap - arm, cy, arm as usize, thickness, color);
+    framebuffer.fill_rect(cx + gap + 1, cy, arm as usize, thickness, color);
+    framebuffer.fill_rect(cx, cy - gap - arm, thickness, arm as usize, color);
+    framebuffer.fill_rect(cx, cy + gap + 1, thickness, arm as usize, color);
+
+    let Some(target) = target else { return };
+    let half = target.half_size.max(BRACKET_MIN_HALF_SIZE) as i64;
+    let corner = BRACKET_CORNER;
+    let (x0, y0) = (target.center.x as i64 - half, target.center.y as i64 - half);
+    let (x1, y1) = (target.center.x as i64 + half, target.center.y as i64 + half);
+    // Esquinas en L del marco
+    for (x, y, dx, dy) in [(x0, y0, 1, 1), (x1, y0, -1, 1), (x0, y1, 1, -1), (x1, y1, -1, -1)] {
+        let horizontal_x = if dx > 0 { x } else { x - corner as i64 + 1 };
+        let vertical_y = if dy > 0 { y } else { y - corner as i64 + 1 };
+        framebuffer.fill_rect(horizontal_x, y, corner, thickness, RETICLE_TARGET_COLOR);
+        framebuffer.fill_rect(x, vertical_y, thickness, corner, RETICLE_TARGET_COLOR);
+    }
+    framebuffer.draw_text(x0 as i32, (y1 + 4) as i32, &target.label, RETICLE_TARGET_COLOR, 1);
+}
+
+// Esferas que se pueden elegir con el mouse en el paso `time`: cada cuerpo y cada luna, en
+// la posición en la que se dibujan
+fn pickable_spheres(bodies: &[BodyConfig], planets: &[PlanetState], sphere_model: &Model, time: f32) -> Vec<(Selection, Vec3, f32)> {
+    let mut spheres = Vec::new();
+    for (i, (body, planet)) in bodies.iter().zip(planets).enumerate() {
+        spheres.push((Selection::Body(i), planet.position() + sphere_model.bounds.center * planet.scale, planet.scale * sphere_model.bounds.radius));
+        for (j, moon) in body.moons.iter().enumerate() {
+            let center = moon_world_position(planet.position(), moon, time) + sphere_model.bounds.center * moon.scale();
+            spheres.push((Selection::Moon { body: i, moon: j }, center, moon.scale() * sphere_model.bounds.radius));
+        }
+    }
+    spheres
+}
+
+// Lo que hay bajo el pixel (x, y) de la ventana, la esfera más cercana que toca el rayo. El
+// rayo se arma con el tamaño de la ventana, que es el de las coordenadas del mouse: la
+// escala del render no cambia la proyección.
+fn pick_selection(x: f32, y: f32, width: f32, height: f32, view_matrix: &Mat4, projection_matrix: &Mat4, spheres: &[(Selection, Vec3, f32)]) -> Option<Selection> {
+    let ray = Ray::from_screen(x, y, width, height, view_matrix, projection_matrix)?;
+    let candidates = spheres.iter().enumerate().map(|(i, &(_, center, radius))| (i, center, radius));
+    picking::pick(&ray, candidates).map(|(i, _)| spheres[i].0)
+}
+
+// Etiquetas con el nombre de cada cuerpo: separación sobre el borde de arriba de la esfera,
+// tamaño proyectado (en pixeles) entre el que aparecen de lejos y fracción de la altura de
+// la pantalla entre la que se apagan de cerca
+const LABEL_GAP: f32 = 4.0;
+const LABEL_MIN_SIZE: f32 = 2.0;
+const LABEL_FULL_SIZE: f32 = 8.0;
+const LABEL_CLOSE_START: f32 = 0.5;
+const LABEL_CLOSE_END: f32 = 0.9;
+const LABEL_COLOR: Color = Color::new(220, 220, 220);
+
+// Etiqueta sobre una esfera del mundo; la de lo seleccionado se resalta
+struct Label {
+    text: String,
+    center: Vec3,
+    radius: f32,
+    selected: bool,
+}
+
+// Cada etiqueta va centrada sobre la silueta de su esfera en pantalla. No se dibuja si la
+// esfera queda detrás de la cámara o fuera de la pantalla, y se apaga cuando es un punto o
+// cuando la llena, salvo la resaltada, que se ve siempre.
+fn draw_labels(framebuffer: &mut Framebuffer, labels: impl Iterator<Item = Label>, view_matrix: &Mat4, projection_matrix: &Mat4, viewport_matrix: &Mat4) {
+    let (width, height) = (framebuffer.width as f32, framebuffer.height as f32);
+    for label in labels {
+        let Some(rect) = picking::project_sphere(label.center, label.radius, view_matrix, projection_matrix, viewport_matrix) else { continue };
+        if !rect.is_on_screen(width, height) {
+            continue;
+        }
+        let size = rect.size().max();
+        let far = smoothstep(LABEL_MIN_SIZE, LABEL_FULL_SIZE, size);
+        let close = 1.0 - smoothstep(LABEL_CLOSE_START * height, LABEL_CLOSE_END * height, size);
+        let (color, alpha) = if label.selected { (RETICLE_TARGET_COLOR, 1.0) } else { (LABEL_COLOR, far * close) };
+        if alpha <= 0.0 {
+            continue;
+        }
+        let (text_width, text_height) = text::text_size(&label.text, 1);
+        let x = (rect.center().x - text_width as f32 / 2.0).round() as i32;
+        let y = (rect.min.y - LABEL_GAP - text_height as f32).round() as i32;
+        framebuffer.draw_text(x + 1, y + 1, &label.text, Color::BLACK.with_alpha(alpha), 1);
+        framebuffer.draw_text(x, y, &label.text, color.with_alpha(alpha), 1);
+    }
+}
+
+// Contorno de lo seleccionado: ancho en pixeles de la ventana (igual a cualquier distancia)
+// y segundos reales de cada pulso, en el que la opacidad oscila entre los dos valores
+const OUTLINE_WIDTH: i64 = 2;
+const OUTLINE_PULSE_PERIOD: f32 = 1.5;
+const OUTLINE_ALPHA: (f32, f32) = (0.45, 1.0);
+
+// Anillo de pixeles alrededor de la silueta de la esfera: los de afuera que quedan a menos de
+// OUTLINE_WIDTH de algún pixel de la silueta. `seconds` es tiempo real, así pulsa también en
+// pausa. A diferencia del resto del HUD se prueba contra la profundidad del centro de la
+// esfera: lo que esté delante (un anillo, una luna) lo tapa. El zbuffer de `framebuffer`
+// tiene que tener la escena a su tamaño (Framebuffer::resample_depth).
+fn draw_outline(framebuffer: &mut Framebuffer, center: Vec3, radius: f32, view_matrix: &Mat4, projection_matrix: &Mat4, viewport_matrix: &Mat4, seconds: f32) {
+    let clip = projection_matrix * view_matrix * Vec4::new(center.x, center.y, center.z, 1.0);
+    if clip.w <= 0.0 {
+        return;
+    }
+    let depth = clip.z / clip.w;
+    let width = OUTLINE_WIDTH;
+    // También las filas y columnas justo fuera de la pantalla, que dan el anillo en el borde
+    let margin = Vec2::new(width as f32, width as f32);
+    let area = ScreenRect { min: -margin, max: Vec2::new(framebuffer.width as f32, framebuffer.height as f32) + margin };
+    let spans = picking::silhouette_spans(center, radius, area, view_matrix, projection_matrix, viewport_matrix);
+    let (Some(&(first_row, ..)), Some(&(last_row, ..))) = (spans.first(), spans.last()) else { return };
+    // Las filas de la silueta son consecutivas
+    let span_at = |row: i64| spans.get(usize::try_from(row - first_row).ok()?).copied();
+    let pulse = 0.5 - 0.5 * (seconds * TAU / OUTLINE_PULSE_PERIOD).cos();
+    let color = RETICLE_TARGET_COLOR.with_alpha(OUTLINE_ALPHA.0 + (OUTLINE_ALPHA.1 - OUTLINE_ALPHA.0) * pulse);
+    for y in first_row - width..=last_row + width {
+        let near: Vec<(i64, i64, i64)> = (y - width..=y + width).filter_map(span_at).collect();
+        let (Some(left), Some(right)) = (near.iter().map(|span| span.1).min(), near.iter().map(|span| span.2).max()) else { continue };
+        // Se salta el tramo de la propia fila, que es la silueta
+        let (first, last) = span_at(y).map_or((right + width + 1, right + width), |(_, first, last)| (first, last));
+        for x in (left - width..first).chain(last + 1..=right + width) {
+            let touches = near.iter().any(|&(row, first, last)| {
+                let dx = (first - x).max(x - last).max(0);
+                dx * dx + (row - y) * (row - y) <= width * width
+            });
+            if touches && x >= 0 && y >= 0 {
+                framebuffer.blend_pixel(x as usize, y as usize, depth, color, color.alpha());
+            }
+        }
+    }
+}
+
+// Panel de información de lo seleccionado, arriba a la izquierda: separación del texto al
+// borde y fondo oscuro translúcido
+const PANEL_PADDING: usize = 6;
+const PANEL_BACKGROUND: Color = Color::rgba(8, 10, 18, 170);
+
+// Datos de lo seleccionado en este frame; `center` es su posición y `time` el paso del reloj.
+// Las velocidades van en unidades por segundo a velocidad 1x.
+fn selection_info(selection: Selection, bodies: &[BodyConfig], planets: &[PlanetState], center: Vec3, eye: Vec3, time: f32) -> Vec<String> {
+    let ticks_per_second = TICKS_PER_SECOND as f32;
+    let (shader, orbit_radius, speed) = match selection {
+        Selection::Body(i) => (bodies[i].shader, planets[i].position().magnitude(), planets[i].speed() * ticks_per_second),
+        Selection::Moon { body, moon } => {
+            let moon = &bodies[body].moons[moon];
+            (moon.shader, moon.orbit_radius, moon.orbit_radius * moon.orbit_speed.abs() * ticks_per_second)
+        }
     };
-    render(framebuffer, &moon_uniforms, sphere_vertex_arrays, &ShaderType::Moon);
+    vec![
+        selection.name(bodies),
+        format!("Shader: {:?}", shader),
+        format!("Radio orbital: {:.2}", orbit_radius),
+        format!("Velocidad orbital: {:.2} u/s", speed),
+        format!("Distancia a la cámara: {:.1}", (center - eye).magnitude()),
+        format!("Posición: ({:.1}, {:.1}, {:.1})", center.x, center.y, center.z),
+        format!("Tiempo: {:.1} s", time / ticks_per_second),
+    ]
 }
 
-fn draw_line(framebuffer: &mut Framebuffer, start: Vec3, end: Vec3, color: u32) {
-    let steps = 100; // Cantidad de puntos intermedios para suavidad
-    for i in 0..steps {
-        let t = i as f32 / (steps - 1) as f32;
-        let x = start.x * (1.0 - t) + end.x * t;
-        let y = start.y * (1.0 - t) + end.y * t;
-        let z = start.z * (1.0 - t) + end.z * t; // Profundidad
-        let x_screen = (framebuffer.width as f32 * (x + 1.0) / 2.0) as usize;
-        let y_screen = (framebuffer.height as f32 * (y + 1.0) / 2.0) as usize;
-        framebuffer.set_current_color(color);
-        framebuffer.point(x_screen, y_screen, z);
+// La primera línea (el nombre) va resaltada. Las líneas que no entran en el ancho de la
+// pantalla se cortan con "..."
+fn draw_info_panel(framebuffer: &mut Framebuffer, lines: &[String]) {
+    let (margin, padding) = (HUD_MARGIN, PANEL_PADDING);
+    let max_columns = framebuffer.width.saturating_sub(2 * (margin + padding)) / text::GLYPH_SIZE;
+    if max_columns == 0 || lines.is_empty() {
+        return;
     }
+    let lines: Vec<String> = lines
+        .iter()
+        .map(|line| {
+            if line.chars().count() <= max_columns {
+                line.clone()
+            } else {
+                let kept: String = line.chars().take(max_columns.saturating_sub(3)).collect();
+                format!("{}...", kept).chars().take(max_columns).collect()
+            }
+        })
+        .collect();
+    let (width, height) = text::text_size(&lines.join("\n"), 1);
+    framebuffer.fill_rect(margin as i64, margin as i64, width + 2 * padding, height + 2 * padding, PANEL_BACKGROUND);
+    for (row, line) in lines.iter().enumerate() {
+        let color = if row == 0 { RETICLE_TARGET_COLOR } else { LABEL_COLOR };
+        framebuffer.draw_text((margin + padding) as i32, (margin + padding + row * text::LINE_HEIGHT) as i32, line, color, 1);
+    }
+}
+
+/// Estado orbital de un planeta en el frame actual.
+#[derive(Debug, Clone, Copy)]
+pub struct PlanetState {
+    pub orbit: Orbit,
+    /// Ángulo de la posición alrededor del sol (en el plano XZ); marca las estaciones
+    pub angle: f32,
+    pub scale: f32,
+    position: Vec3,
+    velocity: Vec3,
 }
 
-fn place_ship_front_of_camera(camera: &Camera) -> Vec3 {
-    // Calculamos la dirección hacia donde está mirando la cámara
-    let direction = camera.center - camera.eye; // Vec3 que va del ojo (camera.eye) al centro (camera.center)
-    let distance = 10.0; // La distancia a la que queremos colocar la nave frente a la cámara
-    let ship_position = camera.eye + direction.normalize() * distance; // Coloca la nave en esa dirección
+impl PlanetState {
+    pub fn new(orbit: Orbit, scale: f32) -> Self {
+        let mut state = PlanetState { orbit, angle: 0.0, scale, position: Vec3::zeros(), velocity: Vec3::zeros() };
+        state.advance(0.0);
+        state
+    }
+
+    /// Posición y velocidad en el paso `time` del reloj, con la ecuación de Kepler de `Orbit`.
+    pub fn advance(&mut self, time: f32) {
+        self.position = self.orbit.position_at(time);
+        self.velocity = self.orbit.velocity_at(time);
+        self.angle = self.position.z.atan2(self.position.x);
+    }
 
-    ship_position
+    pub fn position(&self) -> Vec3 {
+        self.position
+    }
+
+    /// Rapidez orbital en unidades por paso
+    pub fn speed(&self) -> f32 {
+        self.velocity.magnitude()
+    }
+
+    /// Dirección de la velocidad orbital (tangente a la órbita).
+    pub fn velocity_dir(&self) -> Vec3 {
+        self.velocity.try_normalize(f32::EPSILON).unwrap_or(Vec3::new(0.0, 0.0, 1.0))
+    }
+}
+
+// 0 más allá de HEAT_DISTANCE de la superficie del sol, 1 sobre ella, con transición suave
+fn heat_factor(sun_distance: f32, sun_radius: f32) -> f32 {
+    let t = (1.0 - (sun_distance - sun_radius) / HEAT_DISTANCE).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
 }
 
-fn render(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex], current_shader: &ShaderType) {
-    // Vertex Shader Stage
-    let mut transformed_vertices = Vec::with_capacity(vertex_array.len());
-    for vertex in vertex_array {
-        let transformed = vertex_shader(vertex, uniforms);
-        transformed_vertices.push(transformed);
+// Desplazamiento aleatorio de la vista en este frame; la semilla es el número de frame
+fn camera_shake(frame: u32, amplitude: f32) -> Vec3 {
+    if amplitude <= 0.0 {
+        return Vec3::zeros();
     }
+    let mut rng = StdRng::seed_from_u64(frame as u64);
+    Vec3::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0), 0.0) * amplitude
+}
+
+fn render(framebuffer: &mut Framebuffer, uniforms: &Uniforms, model: &Model, current_shader: &ShaderType, params: &ShaderParams, stats: &mut RenderStats) {
+    render_in_tiles(framebuffer, uniforms, model, current_shader, params, stats, TILE_SIZE);
+}
+
+// render() con tiles de `tile_size` pixeles de lado
+fn render_in_tiles(framebuffer: &mut Framebuffer, uniforms: &Uniforms, model: &Model, current_shader: &ShaderType, params: &ShaderParams, stats: &mut RenderStats, tile_size: usize) {
+    stats.objects_submitted += 1;
+
+    // Frustum Culling Stage
+    let mvp = uniforms.projection_matrix * uniforms.view_matrix * uniforms.model_matrix;
+    if !model.bounds.is_in_frustum(&mvp) {
+        stats.objects_culled += 1;
+        return;
+    }
+
+    // Vertex Shader Stage: una vez por vértice único
+    let stage_start = stats.start();
+    let transformed_vertices: Vec<Vertex> = maybe_par_iter!(model.vertices)
+        .map(|vertex| vertex_shader(vertex, uniforms))
+        .collect();
+    RenderStats::elapsed(stage_start, &mut stats.vertex_time);
 
     // Primitive Assembly Stage
-    let mut triangles = Vec::new();
-    for i in (0..transformed_vertices.len()).step_by(3) {
-        if i + 2 < transformed_vertices.len() {
-            triangles.push([
-                transformed_vertices[i].clone(),
-                transformed_vertices[i + 1].clone(),
-                transformed_vertices[i + 2].clone(),
-            ]);
-        }
-    }
-
-    // Rasterization Stage
-    let mut fragments = Vec::new();
-    for tri in &triangles {
-        let normal = (tri[1].position - tri[0].position).cross(&(tri[2].position - tri[0].position));
-        let view_dir = tri[0].position - Vec3::new(0.0, 0.0, 0.0);
-    
-        if normal.dot(&view_dir) < 0.0 {
+    let stage_start = stats.start();
+    let triangles: Vec<[Vertex; 3]> = model.indices
+        .chunks_exact(3)
+        .map(|tri| [
+            transformed_vertices[tri[0] as usize].clone(),
+            transformed_vertices[tri[1] as usize].clone(),
+            transformed_vertices[tri[2] as usize].clone(),
+        ])
+        .collect();
+
+    // Clipping Stage: recorte contra el plano cercano antes de la división perspectiva
+    let triangles: Vec<[Vertex; 3]> = maybe_par_iter!(triangles)
+        .flat_map(|tri| {
+            let mut clipped = clip_near(tri);
+            for vertex in clipped.iter_mut().flatten() {
+                project_to_screen(vertex, &uniforms.viewport_matrix);
+            }
+            clipped
+        })
+        .collect();
+
+    let triangles: Vec<[Vertex; 3]> = triangles
+        .into_iter()
+        .filter(is_finite)
+        .filter(|tri| !uniforms.cull_backfaces || is_front_facing(tri))
+        .collect();
+
+    // Binning Stage: cada triángulo se asigna a los tiles que toca su bounding box. Las
+    // aristas y los puntos se rasterizan una sola vez y lo que se reparte son sus fragmentos,
+    // en orden de envío.
+    let grid = TileGrid::with_tile_size(framebuffer.width, framebuffer.height, tile_size);
+    let bounding_boxes: Vec<(f32, f32, f32, f32)> = triangles.iter().map(screen_bounds).collect();
+    let bins = grid.bin(&bounding_boxes);
+    let edge_bins = match uniforms.render_mode {
+        RenderMode::Filled => Vec::new(),
+        mode => {
+            let fragments: Vec<Vec<Fragments>> = maybe_par_iter!(triangles)
+                .map(|tri| match mode {
+                    RenderMode::Points => vec![point(&tri[0]), point(&tri[1]), point(&tri[2])],
+                    _ => [line(&tri[0], &tri[1]), line(&tri[1], &tri[2]), line(&tri[2], &tri[0])].concat(),
+                })
+                .collect();
+            let mut edge_bins: Vec<Vec<Fragments>> = vec![Vec::new(); grid.tile_count()];
+            for fragment in fragments.into_iter().flatten() {
+                if let Some(tile) = grid.tile_at(fragment.position.x, fragment.position.y) {
+                    edge_bins[tile].push(fragment);
+                }
+            }
+            edge_bins
+        }
+    };
+    stats.triangles += triangles.len();
+    RenderStats::elapsed(stage_start, &mut stats.primitive_time);
+
+    // Rasterization + Fragment Processing Stage
+    // Los tiles no comparten pixeles, así que se resuelven en paralelo. Dentro de
+    // cada tile los triángulos se procesan en orden de envío, por lo que la imagen
+    // es idéntica a la del camino en un solo hilo.
+    let stage_start = stats.start();
+    let framebuffer_ref: &Framebuffer = framebuffer;
+    let tiles: Vec<(TileBuffer, usize, usize, usize)> = maybe_par_iter!(bins)
+        .enumerate()
+        .filter(|(_, bin)| !bin.is_empty())
+        .map(|(tile_index, bin)| {
+            let rect = grid.tile_rect(tile_index);
+            let mut tile = framebuffer_ref.read_tile(rect);
+            let (mut generated, mut passed, mut skipped) = (0, 0, 0);
+
+            let mut shade = |fragment: &Fragments| {
+                if !rect.contains(fragment.position.x, fragment.position.y) {
+                    return;
+                }
+                generated += 1;
+
+                // Early-z: solo se sombrea si el fragmento quedaría visible
+                let (x, y) = (fragment.position.x as usize, fragment.position.y as usize);
+                if !tile.depth_test(x, y, fragment.depth) {
+                    skipped += 1;
+                    return;
+                }
+
+                // Apply fragment shader (None = descartado, no se escribe nada). Las aristas
+                // y los puntos van con un color fijo
+                let shaded = match uniforms.render_mode {
+                    RenderMode::Filled => fragment_shader(fragment, uniforms, current_shader, params),
+                    RenderMode::Wireframe | RenderMode::Points => Some(EDGE_COLOR),
+                };
+                let Some(shaded_color) = shaded else {
+                    return;
+                };
+                let shaded_color = if uniforms.dither { shaded_color.dithered(x, y) } else { shaded_color };
+                if shaded_color.is_opaque() {
+                    tile.write(x, y, fragment.depth, shaded_color.to_hex());
+                } else {
+                    tile.blend(x, y, fragment.depth, shaded_color);
+                }
+                passed += 1;
+            };
+
+            match uniforms.render_mode {
+                RenderMode::Filled => {
+                    for &tri_index in bin {
+                        let tri = &triangles[tri_index];
+                        triangle(&tri[0], &tri[1], &tri[2], &rect).iter().for_each(&mut shade);
+                    }
+                }
+                RenderMode::Wireframe | RenderMode::Points => edge_bins[tile_index].iter().for_each(&mut shade),
+            }
+
+            (tile, generated, passed, skipped)
+        })
+        .collect();
+    RenderStats::elapsed(stage_start, &mut stats.fragment_time);
+
+    let stage_start = stats.start();
+    for (tile, generated, passed, skipped) in &tiles {
+        framebuffer.write_tile(tile);
+        stats.fragments += generated;
+        stats.fragments_passed += passed;
+        stats.fragments_skipped += skipped;
+    }
+    RenderStats::elapsed(stage_start, &mut stats.merge_time);
+}
+
+// Nombre del archivo de textura de cada planeta; el sol conserva su shader porque emite luz
+fn texture_name(shader: &ShaderType) -> Option<&'static str> {
+    match shader {
+        ShaderType::Earth => Some("earth"),
+        ShaderType::GasPlanet => Some("gas_planet"),
+        ShaderType::RingPlanet => Some("ring_planet"),
+        ShaderType::RockyPlanet => Some("rocky_planet"),
+        ShaderType::IcyPlanet => Some("icy_planet"),
+        ShaderType::VolcanicPlanet => Some("volcanic_planet"),
+        ShaderType::OceanPlanet => Some("ocean_planet"),
+        ShaderType::DesertPlanet => Some("desert_planet"),
+        _ => None,
+    }
+}
+
+// Panorama de SKYBOX_FACES si está en disco; si no, el cielo de estrellas procedural
+fn load_skybox() -> Skybox {
+    if SKYBOX_FACES.iter().all(|path| std::path::Path::new(path).exists()) {
+        match Skybox::from_cubemap(SKYBOX_FACES) {
+            Ok(skybox) => return skybox,
+            Err(err) => eprintln!("No se pudo cargar el skybox: {}", err),
+        }
+    }
+    let config = SkyboxConfig { galactic_plane: Some(GALACTIC_PLANE), nebulae: 4, galaxies: 6, clusters: 4, ..SkyboxConfig::default() };
+    Skybox::with_seed(1000, 100.0, config, SKY_SEED)
+}
+
+// Busca la textura en TEXTURE_DIR con las extensiones soportadas; sin archivo no es un error
+fn load_texture(name: &str) -> Option<Texture> {
+    for extension in ["png", "jpg", "jpeg"] {
+        let path = format!("{}/{}.{}", TEXTURE_DIR, name, extension);
+        if !std::path::Path::new(&path).exists() {
             continue;
         }
-    
-        fragments.extend(triangle(&tri[0], &tri[1], &tri[2]));
+        match Texture::load(&path) {
+            Ok(texture) => {
+                println!("{}: {}x{}", path, texture.width, texture.height);
+                return Some(texture);
+            }
+            Err(err) => eprintln!("No se pudo cargar {}: {}", path, err),
+        }
     }
+    None
+}
 
-    // Fragment Processing Stage
-    for fragment in fragments {
-        let x = fragment.position.x as usize;
-        let y = fragment.position.y as usize;
-        if x < framebuffer.width && y < framebuffer.height {
-            // Apply fragment shader
-            let shaded_color = fragment_shader(&fragment, &uniforms, current_shader);
-            let color = shaded_color.to_hex();
-            framebuffer.set_current_color(color);
-            framebuffer.point(x, y, fragment.depth);
+// Escena de `path` (el primer argumento) o, sin él, de scene.toml; si tampoco está se usa la
+// copia incluida en el ejecutable. Un archivo con errores termina el programa con el motivo.
+fn load_scene(path: Option<String>) -> SceneConfig {
+    let (path, loaded) = match path {
+        Some(path) => {
+            let loaded = SceneConfig::load(&path);
+            (path, loaded)
         }
+        None if std::path::Path::new(scene::SCENE_PATH).exists() => (scene::SCENE_PATH.to_string(), SceneConfig::load(scene::SCENE_PATH)),
+        None => ("la escena por defecto".to_string(), SceneConfig::parse(scene::DEFAULT_SCENE)),
+    };
+    match loaded {
+        Ok(scene) => {
+            println!("Escena {}: {} cuerpos", path, scene.bodies.len());
+            scene
+        }
+        Err(err) => {
+            eprintln!("No se pudo cargar {}: {}", path, err);
+            std::process::exit(1);
+        }
+    }
+}
+
+// Sin alguno de los modelos no hay escena que mostrar: se avisa qué archivo y qué línea
+// fallaron y se sale sin el panic
+fn load_model(path: &str, weld: Option<f32>, pivot: Option<Pivot>) -> Model {
+    try_load_model(path, weld, pivot).unwrap_or_else(|err| {
+        eprintln!("No se pudo cargar {}: {}", path, err);
+        std::process::exit(1);
+    })
+}
+
+// El lector se elige por la extensión (.glb o .obj); con `weld` se fusionan los vértices a
+// esa distancia (sólo .obj) y con `pivot` el modelo se normaliza (ver Obj::normalized)
+fn try_load_model(path: &str, weld: Option<f32>, pivot: Option<Pivot>) -> Result<Model, Box<dyn std::error::Error>> {
+    let extension = std::path::Path::new(path).extension().and_then(|extension| extension.to_str());
+    let loaded: Result<Obj, Box<dyn std::error::Error>> = match (extension, weld) {
+        (Some("glb") | Some("gltf"), _) => gltf_loader::load(path, ObjOptions::default()).map_err(Into::into),
+        (_, Some(epsilon)) => Obj::load_welded(path, epsilon)
+            .map(|(obj, stats)| {
+                println!("{}: vértices fusionados, {}", path, stats);
+                obj
+            })
+            .map_err(Into::into),
+        _ => Obj::load(path).map_err(Into::into),
+    };
+    let mut obj = loaded?;
+    if let Some(pivot) = pivot {
+        let (min, max) = obj.bounds();
+        let (normalized, transform) = obj.normalized(pivot);
+        println!(
+            "{}: caja original ({:.3}, {:.3}, {:.3}) a ({:.3}, {:.3}, {:.3}), escalada x{:.4} con el pivote en {:?}",
+            path, min.x, min.y, min.z, max.x, max.y, max.z, transform[(0, 0)], pivot
+        );
+        obj = normalized;
+    }
+    let model = obj.get_model();
+    println!(
+        "{}: {} vértices expandidos -> {} únicos ({} triángulos)",
+        path,
+        model.indices.len(),
+        model.vertices.len(),
+        model.indices.len() / 3
+    );
+    Ok(model)
+}
+
+// Un .obj en `path` reemplaza a la malla generada; sin él (el anillo no trae uno, y la
+// esfera tampoco lo encuentra al correr desde otra carpeta) se usa la generada
+fn load_or_generate(path: &str, weld: Option<f32>, generate: impl FnOnce() -> Vec<Vertex>) -> Model {
+    if std::path::Path::new(path).exists() {
+        return load_model(path, weld, None);
     }
+    let model = Model::from_vertices(&generate());
+    println!("{} no está: malla generada con {} triángulos", path, model.indices.len() / 3);
+    model
 }
 
 fn main() {
-    let window_width = 800;
-    let window_height = 600;
-    let framebuffer_width = 800;
-    let framebuffer_height = 600;
+    // Tamaño de la ventana; cambia cuando el usuario la redimensiona
+    let mut window_width = 800;
+    let mut window_height = 600;
 
-    let mut framebuffer = Framebuffer::new(framebuffer_width, framebuffer_height);
+    // El framebuffer se dibuja a la escala interna y se lleva al tamaño de la ventana en
+    // window_buffer, donde también va el HUD
+    let mut render_scale = RenderScale::NATIVE;
+    let (render_width, render_height) = render_scale.render_size(window_width, window_height);
+    let mut framebuffer = Framebuffer::new(render_width, render_height);
+    let mut window_buffer = Framebuffer::new(window_width, window_height);
     let mut window = Window::new(
         "Planets Render",
         window_width,
         window_height,
-        WindowOptions::default(),
+        WindowOptions { resize: true, ..WindowOptions::default() },
     )
     .unwrap();
 
@@ -213,21 +1216,88 @@ fn main() {
         Vec3::new(0.0, 1.0, 0.0)
     );
 
+    // La nave va 10 unidades delante de la cámara y 5 por debajo en tercera persona
+    let mut ship = Ship::new(10.0);
+
     // Cargar modelos 3D
-    let sphere_loader = Obj::load("models/sphere.obj").expect("Failed to load sphere obj");
-    let sphere_vertex_arrays = sphere_loader.get_vertex_array();
+    let mut sphere_model = load_or_generate(SPHERE_MODEL, SPHERE_WELD, || mesh::generate_uv_sphere(SPHERE_STACKS, SPHERE_SLICES));
+    let mut ring_model = load_or_generate(RING_MODEL, None, || mesh::generate_ring(RING_INNER_RADIUS, RING_OUTER_RADIUS, RING_SEGMENTS));
+    let asteroid_model = Model::from_vertices(&mesh::generate_icosphere(ASTEROID_SUBDIVISIONS));
+    let mut ship_model = load_model(SHIP_MODEL, None, SHIP_PIVOT);
+    let mut model_watcher = FileWatcher::new(&[SPHERE_MODEL, RING_MODEL, SHIP_MODEL]);
+
+    let mut clock = SimClock::new();
+    // Frames dibujados; el temblor de la cámara cambia con cada uno aunque el reloj esté en pausa
+    let mut frame: u32 = 0;
+    let mut render_mode = RenderMode::Filled;
+    let mut style = ShadingStyle::Smooth;
+    let mut dither = true;
+    let mut bloom = Bloom::new();
+    let mut bloom_enabled = true;
+    let mut color_grade = ColorGrade::new();
+    let mut motion_blur = false;
+    let mut persistence = DEFAULT_PERSISTENCE;
+    let mut debug_mode = 0;
+    // Posición del cursor en el frame anterior mientras se mantiene el clic derecho
+    let mut last_mouse_pos: Option<(f32, f32)> = None;
+    // Cuerpo o luna elegido con el clic izquierdo, y si el botón estaba apretado en el frame
+    // anterior (el clic cuenta al apretarlo)
+    let mut selection: Option<Selection> = None;
+    let mut left_was_down = false;
+    let mut fov = 45.0 * PI / 180.0;
+    let mut stats = RenderStats::new(false);
 
-    let ring_loader = Obj::load("models/ring.obj").expect("Failed to load ring obj");
-    let ring_vertex_array = ring_loader.get_vertex_array();
+    let mut last_frame = Instant::now();
+    let started = last_frame;
+    // Mensaje temporal para el título y el instante en que se creó
+    let mut notice: Option<(String, Instant)> = None;
+    // Ruta de cámara: grabación con R, reproducción con P
+    let mut camera_path: Option<Path> = None;
+    let mut recorder: Option<PathRecorder> = None;
+    let mut playback: Option<PathPlayback> = None;
+    let mut thrust: f32 = 0.0;
+    let mut param_field = ParamField::NoiseScale;
 
-    let ship_loader = Obj::load("models/ship.obj").expect("Failed to load ring obj");
-    let ship_vertex_array = ship_loader.get_vertex_array();
+    let mut skybox = load_skybox();
 
-    let mut time = 0;
+    // Los cuerpos salen de la escena; cada uno lleva sus parámetros de shader, que se pueden
+    // ajustar en marcha
+    let scene = load_scene(std::env::args().nth(1));
+    let mut belt = scene.belt.as_ref().map(AsteroidBelt::new);
+    let bodies = scene.bodies;
+    // Colas de los cometas, con el índice de su cuerpo
+    let mut comet_tails: Vec<(usize, CometTail)> = bodies
+        .iter()
+        .enumerate()
+        .filter_map(|(i, body)| body.tail.as_ref().map(|tail| (i, CometTail::new(tail, body.orbit().periapsis(), i as u64))))
+        .collect();
+    let mut planet_params: Vec<ShaderParams> = bodies.iter().map(|body| ShaderParams::for_type(&body.shader)).collect();
+    let sun_index = bodies
+        .iter()
+        .position(|body| matches!(body.shader, ShaderType::Sun))
+        .unwrap_or(0);
+    let mut heat_effects = true;
+    let fog = Fog::default();
 
-    let mut last_frame = Instant::now();
+    // Texturas opcionales: si existe textures/<nombre>.png o .jpg, el planeta se pinta con
+    // ella en lugar de su shader procedural. Se cargan una sola vez.
+    let planet_textures: Vec<Option<Texture>> = bodies
+        .iter()
+        .map(|body| texture_name(&body.shader).and_then(load_texture))
+        .collect();
 
-    let skybox = Skybox::new(1000, 100.0);
+    let mut planet_states: Vec<PlanetState> = bodies
+        .iter()
+        .map(|body| PlanetState::new(body.orbit(), body.scale()))
+        .collect();
+    let orbit_styles: Vec<OrbitStyle> = bodies.iter().map(BodyConfig::orbit_style).collect();
+    // Las órbitas no cambian: sus elipses se calculan una vez (el sol, en el origen, no tiene)
+    let orbit_paths: Vec<Vec<Vec3>> = bodies
+        .iter()
+        .map(|body| if body.orbit_radius > 0.0 { body.orbit().path(PATH_SAMPLES) } else { Vec::new() })
+        .collect();
+    let mut orbit_display = OrbitDisplay::Paths;
+    let mut trails: Vec<OrbitTrail> = bodies.iter().map(|_| OrbitTrail::new(TRAIL_LENGTH, TRAIL_MIN_DISTANCE)).collect();
 
     while window.is_open() {
         if window.is_key_down(Key::Escape) {
@@ -238,108 +1308,750 @@ fn main() {
         if now - last_frame < Duration::from_millis(16) {
             continue; // Limitar a ~60 FPS
         }
+        let delta = (now - last_frame).as_secs_f32();
+        let fps = 1.0 / delta;
         last_frame = now;
-        
-        time += 1;
 
-        handle_input(&window, &mut camera);
+        // Espacio pausa el reloj de la simulación, +/- duplican o dividen su velocidad y
+        // Backspace la invierte. Con Shift, +/- ajustan la sensibilidad de rotación (el valor
+        // se muestra un momento en el título).
+        let shift = window.is_key_down(Key::LeftShift) || window.is_key_down(Key::RightShift);
+        let plus = window.is_key_pressed(Key::Equal, KeyRepeat::No) || window.is_key_pressed(Key::NumPadPlus, KeyRepeat::No);
+        let minus = window.is_key_pressed(Key::Minus, KeyRepeat::No) || window.is_key_pressed(Key::NumPadMinus, KeyRepeat::No);
+        if shift && (plus || minus) {
+            camera.adjust_sensitivity(if plus { SENSITIVITY_STEP } else { 1.0 / SENSITIVITY_STEP });
+            notice = Some((format!("Sensibilidad {:.2}", camera.sensitivity), now));
+        } else if plus {
+            clock.faster();
+        } else if minus {
+            clock.slower();
+        }
+        if window.is_key_pressed(Key::Space, KeyRepeat::No) {
+            clock.toggle_pause();
+        }
+        if window.is_key_pressed(Key::Backspace, KeyRepeat::No) {
+            clock.reverse();
+        }
+        let sim_delta = clock.advance(delta.min(MAX_FRAME_DELTA));
+        let time = clock.time();
+        frame = frame.wrapping_add(1);
+
+        // Un modelo que cambió en disco se recarga con las mismas opciones que al inicio; si
+        // no se puede leer (a medio guardar, por ejemplo) se sigue con el anterior
+        for path in model_watcher.poll() {
+            let (model, weld, pivot) = match path.as_str() {
+                SPHERE_MODEL => (&mut sphere_model, SPHERE_WELD, None),
+                RING_MODEL => (&mut ring_model, None, None),
+                _ => (&mut ship_model, None, SHIP_PIVOT),
+            };
+            let file = path.rsplit('/').next().unwrap_or(&path);
+            match try_load_model(&path, weld, pivot) {
+                Ok(reloaded) => {
+                    *model = reloaded;
+                    notice = Some((format!("Recargado {}", file), now));
+                }
+                Err(err) => {
+                    eprintln!("No se pudo recargar {}: {}", path, err);
+                    notice = Some((format!("Error al recargar {}", file), now));
+                }
+            }
+        }
+
+        // Posición orbital y escala de cada planeta en este frame (antes de la entrada,
+        // para que el modo órbita se ancle a donde realmente se dibuja el planeta)
+        for planet in planet_states.iter_mut() {
+            planet.advance(time);
+        }
+
+        // R empieza o termina la grabación (al terminar se guarda en CAMERA_PATH_FILE)
+        if window.is_key_pressed(Key::R, KeyRepeat::No) && playback.is_none() {
+            if let Some(finished) = recorder.take() {
+                let path = finished.finish();
+                let message = match path.save(CAMERA_PATH_FILE) {
+                    Ok(()) => format!("Ruta guardada ({} keyframes)", path.keyframes.len()),
+                    Err(e) => format!("No se pudo guardar la ruta: {}", e),
+                };
+                notice = Some((message, now));
+                camera_path = Some(path);
+            } else {
+                recorder = Some(PathRecorder::new());
+                notice = Some(("Grabando ruta".to_string(), now));
+            }
+        }
+
+        // P reproduce la última ruta (o la del archivo) y la detiene si ya se está reproduciendo
+        if window.is_key_pressed(Key::P, KeyRepeat::No) && recorder.is_none() {
+            if let Some(stopped) = playback.take() {
+                camera_path = Some(stopped.into_path());
+            } else {
+                match camera_path.take().map_or_else(|| Path::load(CAMERA_PATH_FILE), Ok) {
+                    Ok(path) if path.keyframes.len() > 1 => {
+                        camera.cancel_animation();
+                        playback = Some(PathPlayback::new(path));
+                    }
+                    Ok(path) => camera_path = Some(path),
+                    Err(e) => notice = Some((format!("No se pudo cargar la ruta: {}", e), now)),
+                }
+            }
+        }
+
+        // Durante la reproducción se ignora la entrada de cámara
+        let mut thrust_target = 0.0;
+        if let Some(active) = &playback {
+            if !active.update(&mut camera) {
+                camera_path = playback.take().map(PathPlayback::into_path);
+            }
+        } else {
+            let colliders: Vec<Collider> = planet_states
+                .iter()
+                .map(|planet| Collider {
+                    center: planet.position() + sphere_model.bounds.center * planet.scale,
+                    radius: planet.scale * sphere_model.bounds.radius * COLLISION_MARGIN,
+                })
+                .collect();
+            let previous_mode = camera.mode;
+            thrust_target = handle_input(&window, &mut camera, &mut debug_mode, &mut style, &planet_states, &colliders, delta.min(MAX_FRAME_DELTA));
+            // Al empezar a orbitar o perseguir un planeta (con Tab o C) queda seleccionado
+            if let CameraMode::Orbit { target_index } | CameraMode::Follow { target_index } = camera.mode {
+                if camera.mode != previous_mode {
+                    selection = Some(Selection::Body(target_index));
+                }
+            }
+            handle_mouse_look(&window, &mut camera, &mut last_mouse_pos);
+            handle_scroll(&window, &mut camera, &mut fov);
+        }
 
+        if let Some(recorder) = recorder.as_mut() {
+            recorder.update(&camera);
+        }
+
+        // Estrellas fugaces (en tiempo real) y colas de los cometas, que siguen al reloj: en
+        // pausa se congelan y en reversa el sol las sigue empujando hacia afuera
+        skybox.update(delta.min(MAX_FRAME_DELTA));
+        for (index, tail) in comet_tails.iter_mut() {
+            let nucleus = &planet_states[*index];
+            tail.update(sim_delta.abs(), nucleus.position(), bodies[*index].radius, planet_states[sun_index].position());
+        }
+
+        // El brillo del motor sube y baja de forma gradual
+        thrust += (thrust_target - thrust) * (THRUST_RESPONSE * delta).min(1.0);
+
+        // F9 desactiva el temblor y el tinte de calor (para quien se marea)
+        if window.is_key_pressed(Key::F9, KeyRepeat::No) {
+            heat_effects = !heat_effects;
+        }
+
+        // V alterna entre la vista en tercera persona y la cabina
+        if window.is_key_pressed(Key::V, KeyRepeat::No) {
+            ship.toggle_view();
+        }
+
+        // L alterna entre no dibujar las órbitas, sus estelas y las elipses completas
+        if window.is_key_pressed(Key::L, KeyRepeat::No) {
+            orbit_display = orbit_display.next();
+            let label = match orbit_display {
+                OrbitDisplay::Hidden => "ocultas",
+                OrbitDisplay::Trails => "estelas",
+                OrbitDisplay::Paths => "completas",
+            };
+            notice = Some((format!("Órbitas {}", label), now));
+        }
+
+        // F1 alterna entre relleno, wireframe y puntos
+        if window.is_key_pressed(Key::F1, KeyRepeat::No) {
+            render_mode = render_mode.next();
+        }
+
+        // F5 activa la recolección de estadísticas del pipeline
+        if window.is_key_pressed(Key::F5, KeyRepeat::No) {
+            stats.enabled = !stats.enabled;
+        }
+        stats.reset();
+
+        // G alterna la iluminación en espacio lineal (corrección gamma) y la antigua sobre sRGB
+        if window.is_key_pressed(Key::G, KeyRepeat::No) {
+            let enabled = !color::gamma_correction();
+            color::set_gamma_correction(enabled);
+            notice = Some((format!("Corrección gamma {}", if enabled { "activada" } else { "desactivada" }), now));
+        }
+
+        // B activa o desactiva el bloom, para comparar
+        if window.is_key_pressed(Key::B, KeyRepeat::No) {
+            bloom_enabled = !bloom_enabled;
+            notice = Some((format!("Bloom {}", if bloom_enabled { "activado" } else { "desactivado" }), now));
+        }
+
+        // PageUp / PageDown suben o bajan la exposición
+        let exposure_steps = i32::from(window.is_key_pressed(Key::PageUp, KeyRepeat::Yes))
+            - i32::from(window.is_key_pressed(Key::PageDown, KeyRepeat::Yes));
+        if exposure_steps != 0 {
+            color_grade.adjust_exposure(exposure_steps);
+            notice = Some((format!("Exposición x{:.2}", color_grade.exposure), now));
+        }
+
+        // M activa el desenfoque de movimiento; , y . bajan o suben cuánto dura la estela
+        if window.is_key_pressed(Key::M, KeyRepeat::No) {
+            motion_blur = !motion_blur;
+            notice = Some((format!("Desenfoque de movimiento {}", if motion_blur { "activado" } else { "desactivado" }), now));
+        }
+        let persistence_steps = i32::from(window.is_key_pressed(Key::Period, KeyRepeat::Yes))
+            - i32::from(window.is_key_pressed(Key::Comma, KeyRepeat::Yes));
+        if persistence_steps != 0 {
+            persistence = (persistence + PERSISTENCE_STEP * persistence_steps as f32).clamp(PERSISTENCE_STEP, MAX_PERSISTENCE);
+            notice = Some((format!("Persistencia {:.2}", persistence), now));
+        }
+
+        // F10 activa o desactiva el dithering, para comparar las bandas de color
+        if window.is_key_pressed(Key::F10, KeyRepeat::No) {
+            dither = !dither;
+            notice = Some((format!("Dithering {}", if dither { "activado" } else { "desactivado" }), now));
+        }
+
+        // Ajuste de los parámetros del planeta seleccionado (el de una luna elegida es su
+        // planeta): \ elige el campo y [ / ] lo bajan o suben. Cada cambio se imprime para
+        // copiar los valores buenos al código.
+        if window.is_key_pressed(Key::Backslash, KeyRepeat::No) {
+            param_field = param_field.next();
+            notice = Some((format!("Ajustando {:?}", param_field), now));
+        }
+        let nudge = if window.is_key_pressed(Key::RightBracket, KeyRepeat::Yes) {
+            1
+        } else if window.is_key_pressed(Key::LeftBracket, KeyRepeat::Yes) {
+            -1
+        } else {
+            0
+        };
+        if nudge != 0 {
+            let selected = selection.map_or_else(|| selected_planet(&camera, &planet_states), Selection::body);
+            let (shader, params) = (&bodies[selected].shader, &mut planet_params[selected]);
+            params.nudge(param_field, nudge);
+            notice = Some((format!("{:?} {:?} = {:.3}", shader, param_field, params.get(param_field)), now));
+            println!("{:?}: {:?}", shader, params);
+        }
+
+        // F2 alterna el supersampling, F3 / F4 bajan o suben la escala interna y redimensionar
+        // la ventana cambia su tamaño; en todos los casos los buffers se realocan aquí, entre
+        // frames. Minimizada la ventana mide 0 y se ignora.
+        let previous_scale = render_scale;
+        if window.is_key_pressed(Key::F2, KeyRepeat::No) {
+            render_scale = if render_scale == RenderScale::NATIVE { RenderScale::with_level(SSAA_LEVEL) } else { RenderScale::NATIVE };
+        }
+        if window.is_key_pressed(Key::F3, KeyRepeat::No) {
+            render_scale = render_scale.halved();
+        }
+        if window.is_key_pressed(Key::F4, KeyRepeat::No) {
+            render_scale = render_scale.doubled();
+        }
+        let mut resized = render_scale != previous_scale;
+        if resized {
+            notice = Some((format!("Escala de render {}", render_scale), now));
+        }
+        let (width, height) = window.get_size();
+        if width > 0 && height > 0 && (width, height) != (window_width, window_height) {
+            window_width = width;
+            window_height = height;
+            resized = true;
+        }
+        if resized {
+            let (render_width, render_height) = render_scale.render_size(window_width, window_height);
+            framebuffer = Framebuffer::new(render_width, render_height);
+            framebuffer.set_background_color(0x000000);
+            window_buffer = Framebuffer::new(window_width, window_height);
+        }
+
+        framebuffer.set_overdraw_tracking(debug_mode == DEBUG_OVERDRAW);
+        framebuffer.set_accumulation(motion_blur);
         framebuffer.clear();
 
-        // Matrices comunes
-        let view_matrix = create_view_matrix(camera.eye, camera.center, camera.up);
-        let projection_matrix = create_perspective_matrix(window_width as f32, window_height as f32);
-        let viewport_matrix = create_viewport_matrix(framebuffer_width as f32, framebuffer_height as f32);
+        // Cerca del sol la vista tiembla y todo se tiñe de naranja; el temblor sólo se aplica
+        // a la matriz de vista, las posiciones en el mundo no cambian
+        let heat = if heat_effects {
+            let sun = &planet_states[sun_index];
+            heat_factor((camera.eye - sun.position()).magnitude(), sun.scale * sphere_model.bounds.radius)
+        } else {
+            0.0
+        };
 
-        // Coloca la nave frente a la cámara
-        let ship_position = place_ship_front_of_camera(&camera);
+        let light_position = planet_states[sun_index].position();
 
-        // Rotación de 90 grados alrededor del eje Y
-        let rotation = Mat4::new_rotation(Vec3::new(0.0, 90.0_f32.to_radians(), 0.0));
-        let scale = 0.5;
+        // Matrices comunes
+        let view_matrix = translation(&camera_shake(frame, heat * MAX_SHAKE)) * create_view_matrix(&camera);
+        let projection_matrix = create_perspective_matrix(fov, window_width as f32, window_height as f32);
+        let viewport_matrix = create_viewport_matrix(framebuffer.width as f32, framebuffer.height as f32);
 
-        // Creamos la matriz de modelo para la nave
-        let ship_uniforms = Uniforms {
-            model_matrix: create_model_matrix(ship_position + Vec3::new(0.0, -5.0, 0.0), scale, Vec3::new(0.0, 0.0, 0.0)) * rotation,
+        // Clic izquierdo: elige lo que hay bajo el cursor y en el vacío quita la selección. En
+        // el modo órbita la cámara vuela hacia lo elegido (hacia su planeta si es una luna).
+        let pickable = pickable_spheres(&bodies, &planet_states, &sphere_model, time);
+        let left_down = window.get_mouse_down(MouseButton::Left);
+        if left_down && !left_was_down {
+            if let Some((x, y)) = window.get_mouse_pos(MouseMode::Discard) {
+                selection = pick_selection(x, y, window_width as f32, window_height as f32, &view_matrix, &projection_matrix, &pickable);
+                if let (Some(selected), CameraMode::Orbit { target_index }) = (selection, camera.mode) {
+                    let planet = &planet_states[selected.body()];
+                    if selected.body() != target_index {
+                        camera.warp_to(selected.body(), planet.position(), planet.scale * ORBIT_DISTANCE_FACTOR, WARP_DURATION);
+                    }
+                }
+            }
+        }
+        left_was_down = left_down;
+
+        // Fondo: el skybox se dibuja una sola vez por frame, antes que cualquier objeto y con
+        // la matriz de modelo identidad (la misma que usan después las órbitas)
+        let sky_uniforms = Uniforms {
+            model_matrix: Mat4::identity(),
             view_matrix,
             projection_matrix,
             viewport_matrix,
             time,
-            debug_mode: 0,
+            debug_mode,
+            cull_backfaces: true,
+            render_mode,
+            style,
+            dither,
+            light_position,
+            camera_position: camera.eye,
+            shadow_spheres: &[],
+            heat,
+            texture: None,
+            materials: &[],
+            skybox: None,
+            thrust: 0.0,
+            season: 0.0,
+            fog: None,
         };
+        skybox.render_sb(&mut framebuffer, &sky_uniforms, camera.eye);
 
-        // Renderiza la nave
-        render(&mut framebuffer, &ship_uniforms, &ship_vertex_array, &ShaderType::Ship);
-
-        // Renderizar cada planeta con las escalas y distancias
-        let planet_positions = vec![
-            (Vec3::new(0.0, 0.0, 0.0), ShaderType::Sun, 10.0),
-            (Vec3::new(10.0, 0.0, 0.0), ShaderType::VolcanicPlanet, 1.0),
-            (Vec3::new(20.0, 0.0, 0.0), ShaderType::Earth, 1.5),
-            (Vec3::new(30.0, 0.0, 0.0), ShaderType::RockyPlanet, 1.3),
-            (Vec3::new(40.0, 0.0, 0.0), ShaderType::GasPlanet, 4.0),
-            (Vec3::new(50.0, 0.0, 0.0), ShaderType::RingPlanet, 3.5),
-            (Vec3::new(60.0, 0.0, 0.0), ShaderType::IcyPlanet, 0.8),
-        ];    
-
-        let mut orbits: Vec<Vec<Vec3>> = vec![vec![]; planet_positions.len()];  
-
-        for (i, (base_position, shader, scale)) in planet_positions.iter().enumerate() {           
-            
-            let orbital_speed = 0.01 + i as f32 * 0.002; // Variar velocidades por índice de planeta
-            let orbital_radius = base_position.x; // Usar la posición inicial como radio de la órbita
-            let orbital_position = planet_orbit(time as f32, orbital_radius, orbital_speed);
-
-            if orbits[i].len() > 1000 {
-                orbits[i].remove(0); // Eliminar posiciones antiguas para limitar el tamaño
-            }
-            orbits[i].push(orbital_position);
+        for (trail, planet) in trails.iter_mut().zip(planet_states.iter()) {
+            trail.push(planet.position());
+        }
 
-            let uniforms = Uniforms {
-                model_matrix: create_model_matrix(orbital_position, *scale, Vec3::new(0.0, 0.0, 0.0)),
+        // Renderiza la nave salvo en primera persona
+        if ship.is_visible() {
+            let ship_uniforms = Uniforms {
+                model_matrix: ship.model_matrix(&camera),
                 view_matrix,
                 projection_matrix,
                 viewport_matrix,
                 time,
-                debug_mode: 0,
+                debug_mode,
+                cull_backfaces: true,
+                render_mode,
+                style,
+                dither,
+                light_position,
+                camera_position: camera.eye,
+                shadow_spheres: &[],
+                heat,
+                texture: None,
+                materials: &ship_model.materials,
+                skybox: Some(&skybox),
+                thrust,
+                season: 0.0,
+                fog: Some(fog),
             };
+            render(&mut framebuffer, &ship_uniforms, &ship_model, &ShaderType::Ship, &ShaderParams::for_type(&ShaderType::Ship), &mut stats);
+        }
 
-            // Renderiza el skybox
-            skybox.render_sb(&mut framebuffer, &uniforms, camera.eye);
-            
-            // Renderizar las orbitas
-            for (_i, orbit) in orbits.iter().enumerate() {
-                let color = 0xFF0000;
-                for j in 0..orbit.len().saturating_sub(1) {
-                    draw_line(&mut framebuffer, orbit[j], orbit[j + 1], color);
-                }
-            }            
+        // Esferas que hacen sombra en cada sistema: el planeta primero y después sus lunas.
+        // Viven todo el frame porque los dibujos translúcidos guardan sus Uniforms.
+        let shadow_casters: Vec<Vec<(Vec3, f32)>> = bodies.iter().zip(&planet_states)
+            .map(|(body, state)| {
+                let position = state.position();
+                std::iter::once((position, body.scale() * sphere_model.bounds.radius))
+                    .chain(body.moons.iter().map(|moon| (moon_world_position(position, moon, time), moon.scale() * sphere_model.bounds.radius)))
+                    .collect()
+            })
+            .collect();
+
+        let mut corona = None;
+        let mut transparent = Vec::new();
+
+        for (i, (body, params)) in bodies.iter().zip(&planet_params).enumerate() {
+            let (shader, scale) = (&body.shader, body.scale());
+            let orbital_position = planet_states[i].position();
+
+            // Cualquiera de sus lunas puede eclipsar al planeta
+            let (planet_shadow, moon_shadows) = shadow_casters[i].split_first().expect("el planeta siempre está");
+
+            // El giro sólo mueve la superficie: la luz sale de las posiciones y normales en el
+            // mundo, así que el lado de día sigue mirando al sol. La inclinación (en Z) se
+            // aplica después del giro (en Y), así el eje de giro es el polo inclinado, que no
+            // cambia de dirección a lo largo de la órbita.
+            let spin = body.spin_angle(time, planet_states[i].angle);
+            let tilt = body.axial_tilt.to_radians();
+            let uniforms = Uniforms {
+                model_matrix: create_model_matrix(orbital_position, scale, Vec3::new(0.0, spin, tilt)),
+                view_matrix,
+                projection_matrix,
+                viewport_matrix,
+                time,
+                debug_mode,
+                cull_backfaces: true,
+                render_mode,
+                style,
+                dither,
+                light_position,
+                camera_position: camera.eye,
+                shadow_spheres: moon_shadows,
+                heat,
+                texture: planet_textures[i].as_ref(),
+                materials: &sphere_model.materials,
+                skybox: None,
+                thrust: 0.0,
+                season: season(body, orbital_position, light_position),
+                fog: Some(fog),
+            };
 
             // Renderizar planeta
-            render(&mut framebuffer, &uniforms, &sphere_vertex_arrays, &shader);
+            let surface_shader = if uniforms.texture.is_some() { &ShaderType::Textured } else { shader };
+            render(&mut framebuffer, &uniforms, &sphere_model, surface_shader, params, &mut stats);
 
-            // Renderizar anillos o lunas si aplica
-            match shader {
-                ShaderType::RingPlanet => {
-                    render_rings(&mut framebuffer, orbital_position, &uniforms, &ring_vertex_array);
-                }
-                ShaderType::RockyPlanet => {
-                    moon_render(&mut framebuffer, orbital_position, time, view_matrix, projection_matrix, viewport_matrix, &sphere_vertex_arrays);
-                }
-                _ => {}
+            // Anillos y lunas de la escena
+            let planet_radius = scale * sphere_model.bounds.radius;
+            if let Some(rings) = &body.rings {
+                render_rings(&mut framebuffer, &mut transparent, orbital_position, &shadow_casters[i], rings.scale(), tilt, &uniforms, &ring_model, &mut stats);
+            }
+            for moon in &body.moons {
+                moon_render(&mut framebuffer, orbital_position, planet_shadow, moon, &uniforms, &sphere_model, &mut stats);
+            }
+            if matches!(shader, ShaderType::Sun) {
+                corona = Some((orbital_position, planet_radius, uniforms));
+            }
+        }
+
+        if let Some(belt) = belt.as_mut() {
+            belt.update(time, camera.eye, camera.forward());
+            let belt_uniforms = Uniforms {
+                materials: &asteroid_model.materials,
+                fog: Some(fog),
+                ..sky_uniforms
+            };
+            render_belt(&mut framebuffer, belt, &belt_uniforms, &asteroid_model, &mut stats);
+        }
+
+        for (_, tail) in &comet_tails {
+            tail.render(&mut framebuffer, &sky_uniforms);
+        }
+
+        // Las órbitas van después de los opacos para que los planetas tapen el tramo de atrás
+        for (i, style) in orbit_styles.iter().enumerate() {
+            match orbit_display {
+                OrbitDisplay::Hidden => {}
+                OrbitDisplay::Trails => orbit::draw_polyline(&mut framebuffer, trails[i].points(), &sky_uniforms, style),
+                OrbitDisplay::Paths => orbit::draw_polyline(&mut framebuffer, orbit_paths[i].iter().copied(), &sky_uniforms, style),
+            }
+        }
+
+        render_transparent(&mut framebuffer, transparent, &mut stats);
+
+        // El halo va al final, cuando ya está todo lo que puede taparlo
+        if let Some((position, radius, uniforms)) = corona.filter(|_| debug_mode == 0) {
+            render_corona(&mut framebuffer, position, radius, &uniforms);
+        }
+
+        // El bloom trabaja sobre la imagen terminada; en los modos de depuración estorbaría
+        if bloom_enabled && debug_mode == 0 {
+            let stage_start = stats.start();
+            bloom.apply(&mut framebuffer, render_scale.factor());
+            RenderStats::elapsed(stage_start, &mut stats.post_time);
+        }
+        // La exposición y la viñeta van después, así también afectan al brillo del bloom
+        if debug_mode == 0 {
+            let stage_start = stats.start();
+            color_grade.apply(&mut framebuffer);
+            RenderStats::elapsed(stage_start, &mut stats.post_time);
+        }
+
+        if debug_mode == DEBUG_OVERDRAW {
+            framebuffer.apply_overdraw_heatmap();
+        }
+
+        // La estela se mezcla con la imagen ya procesada, antes de la captura
+        framebuffer.accumulate(persistence);
+
+        // F12 guarda el frame terminado como PNG (con Shift también la profundidad); la
+        // escritura va en otro hilo
+        if window.is_key_pressed(Key::F12, KeyRepeat::No) {
+            let path = screenshot::file_name("screenshot");
+            screenshot::save_in_background(path.clone(), framebuffer.width, framebuffer.height, screenshot::color_pixels(&framebuffer));
+            let mut message = format!("Captura: {}", path);
+            if window.is_key_down(Key::LeftShift) || window.is_key_down(Key::RightShift) {
+                let depth_path = screenshot::file_name("depth");
+                screenshot::save_in_background(depth_path.clone(), framebuffer.width, framebuffer.height, screenshot::depth_pixels(&framebuffer));
+                message.push_str(&format!(" y {}", depth_path));
             }
-            
+            notice = Some((message, now));
         }
 
+        // Se lleva la imagen al tamaño de la ventana: filtro caja al reducir y vecino más
+        // cercano al ampliar, que conserva el aspecto pixelado
+        let output = match render_scale.level {
+            0 => &mut framebuffer,
+            level if level > 0 => {
+                framebuffer.downsample(1 << level, &mut window_buffer.buffer);
+                framebuffer.resample_depth(&mut window_buffer);
+                &mut window_buffer
+            }
+            _ => {
+                framebuffer.upsample(window_width, window_height, &mut window_buffer.buffer);
+                framebuffer.resample_depth(&mut window_buffer);
+                &mut window_buffer
+            }
+        };
+
+        // HUD después de la captura, para que las imágenes salgan limpias, y a la resolución
+        // de la ventana para que se vea nítido con cualquier escala. No usa profundidad (salvo
+        // el contorno de lo seleccionado): va encima de todo lo 3D.
+        draw_status(output, fps, &clock);
+        let spheres = planet_states.iter().enumerate().map(|(i, planet)| {
+            (i, planet.position() + sphere_model.bounds.center * planet.scale, planet.scale * sphere_model.bounds.radius)
+        });
+        let window_viewport = create_viewport_matrix(window_width as f32, window_height as f32);
+        let labels = pickable.iter().filter_map(|&(item, center, radius)| {
+            let selected = selection == Some(item);
+            // De las lunas sólo se etiqueta la elegida
+            if matches!(item, Selection::Moon { .. }) && !selected {
+                return None;
+            }
+            Some(Label { text: item.name(&bodies), center, radius, selected })
+        });
+        let selected = pickable.iter().find(|(item, _, _)| selection == Some(*item)).copied();
+        if let Some((_, center, radius)) = selected {
+            draw_outline(output, center, radius, &view_matrix, &projection_matrix, &window_viewport, now.duration_since(started).as_secs_f32());
+        }
+        draw_labels(output, labels, &view_matrix, &projection_matrix, &window_viewport);
+        if let Some((selected, center, _)) = selected {
+            draw_info_panel(output, &selection_info(selected, &bodies, &planet_states, center, camera.eye, time));
+        }
+        if matches!(camera.mode, CameraMode::Free) {
+            let target = aim_target(output, spheres, &view_matrix, &projection_matrix, &window_viewport).map(|(index, distance, center, half_size)| AimTarget {
+                center,
+                half_size,
+                label: format!("{} {:.1}", bodies[index].name, distance),
+            });
+            draw_reticle(output, target.as_ref());
+        }
+
+        let mut title = format!("Planets Render | {:.0} FPS | Escala {}", fps, render_scale);
+        if stats.enabled {
+            title.push_str(" | ");
+            title.push_str(&stats.summary());
+        }
+        if let Some((text, since)) = &notice {
+            if now - *since < NOTICE_DURATION {
+                title.push_str(" | ");
+                title.push_str(text);
+            } else {
+                notice = None;
+            }
+        }
+        window.set_title(&title);
+
         window
-            .update_with_buffer(&framebuffer.buffer, framebuffer_width, framebuffer_height)
+            .update_with_buffer(&output.buffer, window_width, window_height)
             .unwrap();
     }
 }
 
 
-fn handle_input(window: &Window, camera: &mut Camera) {
-    let movement_speed = 1.0;
+// Mouse-look: con el clic derecho presionado, el movimiento horizontal gira sobre Y
+// y el vertical inclina la cámara. Se detiene al soltar o al salir de la ventana.
+fn handle_mouse_look(window: &Window, camera: &mut Camera, last_mouse_pos: &mut Option<(f32, f32)>) {
+    let mouse_pos = window.get_mouse_pos(MouseMode::Discard);
+
+    match (window.get_mouse_down(MouseButton::Right), mouse_pos) {
+        (true, Some((x, y))) => {
+            if let Some((last_x, last_y)) = *last_mouse_pos {
+                let rotation = Vec3::new((x - last_x) * MOUSE_SENSITIVITY, (last_y - y) * MOUSE_SENSITIVITY, 0.0);
+                if rotation.magnitude() > 0.0 {
+                    camera.cancel_animation();
+                    match camera.mode {
+                        CameraMode::Free => camera.move_center(rotation),
+                        CameraMode::Orbit { .. } => camera.orbit_rotate(rotation),
+                        // La cámara de seguimiento ignora el mouse
+                        CameraMode::Follow { .. } => {}
+                    }
+                }
+            }
+            *last_mouse_pos = Some((x, y));
+        }
+        _ => *last_mouse_pos = None,
+    }
+}
+
+// Planeta que se ajusta con [ y ] si no hay nada seleccionado: el que sigue la cámara o, en
+// cámara libre, el más cercano
+fn selected_planet(camera: &Camera, planets: &[PlanetState]) -> usize {
+    match camera.mode {
+        CameraMode::Orbit { target_index } | CameraMode::Follow { target_index } => target_index,
+        CameraMode::Free => planets
+            .iter()
+            .map(|planet| (camera.eye - planet.position()).magnitude() - planet.scale)
+            .enumerate()
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map_or(0, |(i, _)| i),
+    }
+}
+
+// Velocidad de traslación en unidades/s: crece con la distancia al planeta más cercano,
+// así cruzar el sistema no tarda una eternidad y cerca de una luna no se pasa de largo
+fn translation_speed(window: &Window, camera: &Camera, planets: &[PlanetState]) -> f32 {
+    let surface_distance = planets
+        .iter()
+        .map(|planet| (camera.eye - planet.position()).magnitude() - planet.scale)
+        .fold(f32::INFINITY, f32::min);
+    let mut speed = (surface_distance * SPEED_PER_DISTANCE).clamp(MIN_SPEED, MAX_SPEED);
+
+    if window.is_key_down(Key::LeftShift) || window.is_key_down(Key::RightShift) {
+        speed *= SPEED_BOOST;
+    }
+    if window.is_key_down(Key::LeftCtrl) || window.is_key_down(Key::RightCtrl) {
+        speed /= SPEED_BOOST;
+    }
+    speed
+}
+
+// Teclas de movimiento y rotación manual de la cámara
+fn manual_input(window: &Window) -> bool {
+    [Key::W, Key::A, Key::S, Key::D, Key::Q, Key::E, Key::Z, Key::X, Key::Left, Key::Right, Key::Up, Key::Down]
+        .iter()
+        .any(|key| window.is_key_down(*key))
+}
+
+// En modo órbita las flechas giran alrededor del planeta y W/S cambian el radio
+fn handle_orbit_input(window: &Window, camera: &mut Camera, rotation_speed: f32, delta: f32) {
+    let mut rotation = Vec3::new(0.0, 0.0, 0.0);
+    if window.is_key_down(Key::Left) {
+        rotation.x -= rotation_speed;
+    }
+    if window.is_key_down(Key::Right) {
+        rotation.x += rotation_speed;
+    }
+    if window.is_key_down(Key::Up) {
+        rotation.y += rotation_speed;
+    }
+    if window.is_key_down(Key::Down) {
+        rotation.y -= rotation_speed;
+    }
+    if rotation.magnitude() > 0.0 {
+        camera.orbit_rotate(rotation);
+    }
+
+    if window.is_key_down(Key::W) {
+        camera.zoom(ORBIT_ZOOM_SPEED * delta);
+    }
+    if window.is_key_down(Key::S) {
+        camera.zoom(-ORBIT_ZOOM_SPEED * delta);
+    }
+}
+
+// Rueda: zoom de la cámara; con Ctrl cambia el FOV en lugar de mover el ojo
+fn handle_scroll(window: &Window, camera: &mut Camera, fov: &mut f32) {
+    let Some((_, scroll)) = window.get_scroll_wheel() else { return };
+    if scroll == 0.0 {
+        return;
+    }
+
+    if window.is_key_down(Key::LeftCtrl) || window.is_key_down(Key::RightCtrl) {
+        *fov = (*fov - scroll.signum() * 2.0_f32.to_radians()).clamp(MIN_FOV, MAX_FOV);
+    } else {
+        camera.cancel_animation();
+        camera.zoom(scroll.signum());
+    }
+}
+
+// `delta` es el tiempo real del frame en segundos: todas las velocidades van por segundo
+fn handle_input(
+    window: &Window,
+    camera: &mut Camera,
+    debug_mode: &mut u32,
+    style: &mut ShadingStyle,
+    planets: &[PlanetState],
+    colliders: &[Collider],
+    delta: f32,
+) -> f32 {
+    let movement_speed = translation_speed(window, camera, planets) * delta;
+    let rotation_speed = ROTATION_SPEED * delta;
+
+    // F6 cicla los modos de depuración (0 normal, 1 profundidad, 2 overdraw, luego capas de cada shader)
+    if window.is_key_pressed(Key::F6, KeyRepeat::No) {
+        *debug_mode = (*debug_mode + 1) % DEBUG_MODE_COUNT;
+    }
+
+    // T alterna el estilo toon (bandas de luz y contorno)
+    if window.is_key_pressed(Key::T, KeyRepeat::No) {
+        *style = style.toggle();
+    }
+
+    // F8 desactiva las colisiones para explorar libremente
+    if window.is_key_pressed(Key::F8, KeyRepeat::No) {
+        camera.collisions = !camera.collisions;
+    }
+
+    // Tab cicla el planeta a orbitar; O vuelve a la cámara libre
+    if window.is_key_pressed(Key::Tab, KeyRepeat::No) && !planets.is_empty() {
+        let target_index = match camera.mode {
+            CameraMode::Orbit { target_index } => (target_index + 1) % planets.len(),
+            _ => 0,
+        };
+        let planet = &planets[target_index];
+        camera.orbit(target_index, planet.position(), planet.scale * ORBIT_DISTANCE_FACTOR);
+    }
+    // C cicla el planeta a perseguir
+    if window.is_key_pressed(Key::C, KeyRepeat::No) && !planets.is_empty() {
+        let target_index = match camera.mode {
+            CameraMode::Follow { target_index } => (target_index + 1) % planets.len(),
+            _ => 0,
+        };
+        camera.chase(target_index);
+    }
+
+    // 1-9 y 0 vuelan hacia el planeta correspondiente (en el orden de la escena)
+    let warp_keys = [
+        Key::Key1, Key::Key2, Key::Key3, Key::Key4, Key::Key5,
+        Key::Key6, Key::Key7, Key::Key8, Key::Key9, Key::Key0,
+    ];
+    for (target_index, key) in warp_keys.iter().enumerate().take(planets.len()) {
+        if window.is_key_pressed(*key, KeyRepeat::No) {
+            let planet = &planets[target_index];
+            camera.warp_to(target_index, planet.position(), planet.scale * ORBIT_DISTANCE_FACTOR, WARP_DURATION);
+        }
+    }
+
+    if let Some(animation) = &camera.animation {
+        if manual_input(window) {
+            camera.cancel_animation();
+        } else {
+            let target = planets[animation.target_index].position();
+            camera.update_animation(target);
+            // Durante el vuelo hacia un planeta los motores van a tope
+            return 1.0;
+        }
+    }
+    if window.is_key_pressed(Key::O, KeyRepeat::No) {
+        camera.free();
+    }
+
+    match camera.mode {
+        CameraMode::Orbit { target_index } => {
+            handle_orbit_input(window, camera, rotation_speed, delta);
+            camera.anchor_orbit(planets[target_index].position());
+            return 0.0;
+        }
+        CameraMode::Follow { target_index } => {
+            let planet = &planets[target_index];
+            camera.follow(
+                planet.position(),
+                planet.velocity_dir(),
+                planet.scale * FOLLOW_DISTANCE_FACTOR,
+                planet.scale * FOLLOW_HEIGHT_FACTOR,
+                delta,
+            );
+            return 0.0;
+        }
+        CameraMode::Free => {}
+    }
    
     // Movimiento de la cámara (A/D para mover a la izquierda/derecha, W/S para adelante/atrás)
     let mut movement = Vec3::new(0.0, 0.0, 0.0);
@@ -368,25 +2080,142 @@ fn handle_input(window: &Window, camera: &mut Camera) {
     if movement.magnitude() > 0.0 {
         camera.move_ship(movement);
     }
+    // Se revisa cada frame: aunque la cámara esté quieta, los planetas siguen orbitando
+    camera.resolve_collisions(colliders);
 
     // Movimiento de la cámara (flechas para rotar)
     let mut rotation = Vec3::new(0.0, 0.0, 0.0);
     if window.is_key_down(Key::Left) {
-        rotation.x -= movement_speed; // Rotar hacia la izquierda
+        rotation.x -= rotation_speed; // Rotar hacia la izquierda
     }
     if window.is_key_down(Key::Right) {
-        rotation.x += movement_speed; // Rotar hacia la derecha
+        rotation.x += rotation_speed; // Rotar hacia la derecha
     }
     if window.is_key_down(Key::Up) {
-        rotation.y += movement_speed; // Rotar hacia arriba
+        rotation.y += rotation_speed; // Rotar hacia arriba
     }
     if window.is_key_down(Key::Down) {
-        rotation.y -= movement_speed; // Rotar hacia abajo
+        rotation.y -= rotation_speed; // Rotar hacia abajo
     }
 
     if rotation.magnitude() > 0.0 {
         camera.move_center(rotation);
         camera.rotate_ship(rotation);
     }
+
+    // Z/X giran la cámara alrededor de la dirección de vista; F7 alterna el modo 6DOF
+    if window.is_key_down(Key::Z) {
+        camera.roll(-ROLL_SPEED * camera.sensitivity * delta);
+    }
+    if window.is_key_down(Key::X) {
+        camera.roll(ROLL_SPEED * camera.sensitivity * delta);
+    }
+    if window.is_key_pressed(Key::F7, KeyRepeat::No) {
+        camera.toggle_six_dof();
+    }
+
+    // Empuje de los motores para el shader de la nave: cualquier traslación los enciende
+    if movement.magnitude() > 0.0 { 1.0 } else { 0.0 }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra_glm::Vec2;
+    use clipping::BoundingSphere;
+
+    const WIDTH: usize = 160;
+    const HEIGHT: usize = 120;
+
+    // Uniforms mínimos para dibujar `model_matrix` visto desde `eye` hacia el origen
+    fn test_uniforms(model_matrix: Mat4, eye: Vec3, render_mode: RenderMode) -> Uniforms<'static> {
+        let camera = Camera::new(eye, Vec3::zeros(), Vec3::new(0.0, 1.0, 0.0));
+        Uniforms {
+            model_matrix,
+            view_matrix: create_view_matrix(&camera),
+            projection_matrix: create_perspective_matrix(45.0f32.to_radians(), WIDTH as f32, HEIGHT as f32),
+            viewport_matrix: create_viewport_matrix(WIDTH as f32, HEIGHT as f32),
+            time: 0.0,
+            debug_mode: 0,
+            cull_backfaces: true,
+            render_mode,
+            style: ShadingStyle::Smooth,
+            dither: false,
+            light_position: Vec3::new(10.0, 10.0, 10.0),
+            camera_position: eye,
+            shadow_spheres: &[],
+            heat: 0.0,
+            texture: None,
+            materials: &[],
+            skybox: None,
+            thrust: 0.0,
+            season: 0.0,
+            fog: None,
+        }
+    }
+
+    // Cubo de lado 1 centrado en el origen, con las caras en sentido antihorario vistas desde afuera
+    fn cube() -> Model {
+        let mut vertices = Vec::new();
+        for normal in [Vec3::x(), -Vec3::x(), Vec3::y(), -Vec3::y(), Vec3::z(), -Vec3::z()] {
+            let u = if normal.x.abs() > 0.5 { Vec3::y() } else { Vec3::x() }.cross(&normal);
+            let v = normal.cross(&u);
+            let corner = |a: f32, b: f32| Vertex::new((normal + u * a + v * b) * 0.5, normal, Vec2::zeros());
+            vertices.extend([corner(-1.0, -1.0), corner(1.0, -1.0), corner(1.0, 1.0)]);
+            vertices.extend([corner(-1.0, -1.0), corner(1.0, 1.0), corner(-1.0, 1.0)]);
+        }
+        let positions: Vec<Vec3> = vertices.iter().map(|vertex| vertex.position).collect();
+        Model::from_vertex_array(&vertices, BoundingSphere::from_points(&positions))
+    }
+
+    // Se corre con y sin la feature parallel: en los dos casos un único tile se resuelve en
+    // un solo hilo, en orden de envío, así que es la referencia del camino secuencial
+    #[test]
+    fn tiled_render_matches_single_tile() {
+        let sphere = Obj::load("models/sphere.obj").unwrap().get_model();
+        let shader = ShaderType::Earth;
+        let model_matrix = create_model_matrix(Vec3::new(0.1, -0.05, 0.0), 2.6, Vec3::new(0.3, 0.8, 0.2));
+        for mode in [RenderMode::Filled, RenderMode::Wireframe, RenderMode::Points] {
+            let draw = |tile_size: usize| {
+                let mut framebuffer = Framebuffer::new(WIDTH, HEIGHT);
+                framebuffer.clear();
+                let mut stats = RenderStats::new(false);
+                let uniforms = test_uniforms(model_matrix, Vec3::new(0.0, 0.5, 3.0), mode);
+                render_in_tiles(&mut framebuffer, &uniforms, &sphere, &shader, &ShaderParams::for_type(&shader), &mut stats, tile_size);
+                let depth_bits: Vec<u32> = framebuffer.zbuffer.iter().map(|depth| depth.to_bits()).collect();
+                (framebuffer.buffer, depth_bits, stats.fragments)
+            };
+            let single = draw(WIDTH.max(HEIGHT));
+            assert!(single.2 > 0, "{:?}: no se dibujó nada", mode);
+            for tile_size in [TILE_SIZE, 7] {
+                let tiled = draw(tile_size);
+                assert!(tiled.0 == single.0, "{:?} con tiles de {}: los colores no coinciden", mode, tile_size);
+                assert!(tiled.1 == single.1, "{:?} con tiles de {}: la profundidad no coincide", mode, tile_size);
+                assert_eq!(tiled.2, single.2, "{:?} con tiles de {}", mode, tile_size);
+            }
+        }
+    }
+
+    #[test]
+    fn cube_shows_three_faces_from_every_corner() {
+        let cube = cube();
+        let shader = ShaderType::Moon;
+        for corner in 0..8 {
+            let sign = |bit: usize| if corner & bit == 0 { 1.0 } else { -1.0 };
+            let eye = Vec3::new(sign(1), sign(2) * 0.8, sign(4) * 1.2) * 3.0;
+            let draw = |cull_backfaces: bool| {
+                let mut framebuffer = Framebuffer::new(WIDTH, HEIGHT);
+                framebuffer.clear();
+                let mut stats = RenderStats::new(false);
+                let uniforms = Uniforms { cull_backfaces, ..test_uniforms(Mat4::identity(), eye, RenderMode::Filled) };
+                render(&mut framebuffer, &uniforms, &cube, &shader, &ShaderParams::for_type(&shader), &mut stats);
+                (framebuffer.zbuffer, stats.triangles)
+            };
+            let ((culled_depth, culled), (all_depth, all)) = (draw(true), draw(false));
+            // Tres caras de dos triángulos, y son las de adelante: sin descartar nada la
+            // profundidad que queda es la misma
+            assert_eq!((culled, all), (6, 12), "desde {:?}", eye);
+            assert!(culled_depth == all_depth, "desde {:?} se descartaron caras de adelante", eye);
+        }
+    }
+}
@@ -0,0 +1,22 @@
+use crate::color::Color;
+
+/// Material de un .mtl: color difuso (Kd), especular (Ks) con su exponente (Ns) y emisivo (Ke)
+#[derive(Debug, Clone, Copy)]
+pub struct Material {
+    pub diffuse: Color,
+    pub specular: Color,
+    pub shininess: f32,
+    pub emissive: Color,
+}
+
+impl Default for Material {
+    // Los valores que asume el formato cuando el .mtl no trae la línea
+    fn default() -> Self {
+        Material {
+            diffuse: Color::from_linear([0.8, 0.8, 0.8]),
+            specular: Color::BLACK,
+            shininess: 0.0,
+            emissive: Color::BLACK,
+        }
+    }
+}
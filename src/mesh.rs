@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+use std::f32::consts::PI;
+use nalgebra_glm::{Vec2, Vec3};
+use crate::vertex::Vertex;
+
+// Mallas generadas en código, como arreglos de triángulos expandidos (igual que
+// Obj::get_vertex_array) listos para Model::from_vertex_array.
+
+// Radio de las esferas; es el de models/sphere.obj, al que están ajustadas las escalas de
+// ruido de los shaders y las escalas de los planetas
+pub const SPHERE_RADIUS: f32 = 0.5;
+
+/// Esfera de `stacks` franjas de latitud por `slices` gajos de longitud. Las uv siguen la
+/// convención de `sphere_uv` (u = 0 en el meridiano -X, v = 0 en el polo norte) y la costura
+/// repite la columna de vértices en u = 0 y u = 1.
+pub fn generate_uv_sphere(stacks: usize, slices: usize) -> Vec<Vertex> {
+    let (stacks, slices) = (stacks.max(2), slices.max(3));
+    let vertex = |stack: usize, slice: usize| {
+        let uv = Vec2::new(slice as f32 / slices as f32, stack as f32 / stacks as f32);
+        let (latitude, longitude) = (uv.y * PI, (uv.x - 0.5) * 2.0 * PI);
+        let normal = Vec3::new(latitude.sin() * longitude.cos(), latitude.cos(), -latitude.sin() * longitude.sin());
+        Vertex::new(normal * SPHERE_RADIUS, normal, uv)
+    };
+
+    let mut vertices = Vec::with_capacity(stacks * slices * 6);
+    for stack in 0..stacks {
+        for slice in 0..slices {
+            let (top_left, top_right) = (vertex(stack, slice), vertex(stack, slice + 1));
+            let (bottom_left, bottom_right) = (vertex(stack + 1, slice), vertex(stack + 1, slice + 1));
+            // En los polos la fila de arriba (o la de abajo) es un solo punto: un triángulo
+            if stack != 0 {
+                vertices.extend([top_left.clone(), bottom_right.clone(), top_right]);
+            }
+            if stack != stacks - 1 {
+                vertices.extend([top_left, bottom_left, bottom_right]);
+            }
+        }
+    }
+    vertices
+}
+
+/// Corona circular en el plano XZ entre `inner_radius` y `outer_radius`, con `segments`
+/// divisiones. La u es la posición a lo ancho (0 en el borde interior, 1 en el exterior) y la
+/// v la vuelta. Tiene una sola cara, hacia +Y: para verla desde abajo se dibuja sin descartar
+/// caras traseras.
+pub fn generate_ring(inner_radius: f32, outer_radius: f32, segments: usize) -> Vec<Vertex> {
+    let segments = segments.max(3);
+    let normal = Vec3::new(0.0, 1.0, 0.0);
+    let vertex = |segment: usize, outer: bool| {
+        let (radius, u) = if outer { (outer_radius, 1.0) } else { (inner_radius, 0.0) };
+        let v = segment as f32 / segments as f32;
+        let (sin, cos) = (v * 2.0 * PI).sin_cos();
+        Vertex::new(Vec3::new(radius * cos, 0.0, radius * sin), normal, Vec2::new(u, v))
+    };
+
+    (0..segments)
+        .flat_map(|segment| {
+            let (inner, outer) = (vertex(segment, false), vertex(segment, true));
+            let (next_inner, next_outer) = (vertex(segment + 1, false), vertex(segment + 1, true));
+            [inner.clone(), next_outer.clone(), outer, inner, next_inner, next_outer]
+        })
+        .collect()
+}
+
+/// Icosaedro con cada cara dividida en 4 `subdivisions` veces (20 * 4^n triángulos). Los
+/// triángulos son casi iguales en toda la esfera, sin la acumulación de los polos.
+pub fn generate_icosphere(subdivisions: u32) -> Vec<Vertex> {
+    let t = (1.0 + 5.0f32.sqrt()) / 2.0;
+    let mut positions: Vec<Vec3> = [
+        (-1.0, t, 0.0), (1.0, t, 0.0), (-1.0, -t, 0.0), (1.0, -t, 0.0),
+        (0.0, -1.0, t), (0.0, 1.0, t), (0.0, -1.0, -t), (0.0, 1.0, -t),
+        (t, 0.0, -1.0), (t, 0.0, 1.0), (-t, 0.0, -1.0), (-t, 0.0, 1.0),
+    ]
+    .iter()
+    .map(|&(x, y, z)| Vec3::new(x, y, z).normalize())
+    .collect();
+    let mut faces: Vec<[usize; 3]> = vec![
+        [0, 11, 5], [0, 5, 1], [0, 1, 7], [0, 7, 10], [0, 10, 11],
+        [1, 5, 9], [5, 11, 4], [11, 10, 2], [10, 7, 6], [7, 1, 8],
+        [3, 9, 4], [3, 4, 2], [3, 2, 6], [3, 6, 8], [3, 8, 9],
+        [4, 9, 5], [2, 4, 11], [6, 2, 10], [8, 6, 7], [9, 8, 1],
+    ];
+
+    for _ in 0..subdivisions {
+        // Los puntos medios se comparten entre las dos caras de cada arista
+        let mut midpoints: HashMap<(usize, usize), usize> = HashMap::new();
+        let mut midpoint = |a: usize, b: usize, positions: &mut Vec<Vec3>| {
+            *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                positions.push(((positions[a] + positions[b]) / 2.0).normalize());
+                positions.len() - 1
+            })
+        };
+        faces = faces
+            .iter()
+            .flat_map(|&[a, b, c]| {
+                let (ab, bc, ca) = (midpoint(a, b, &mut positions), midpoint(b, c, &mut positions), midpoint(c, a, &mut positions));
+                [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+            })
+            .collect();
+    }
+
+    faces
+        .iter()
+        .flat_map(|face| {
+            let normals = face.map(|index| positions[index]);
+            let uvs = seam_safe_uvs(&normals);
+            (0..3).map(move |i| Vertex::new(normals[i] * SPHERE_RADIUS, normals[i], uvs[i]))
+        })
+        .collect()
+}
+
+// uv de longitud y latitud de las esquinas de un triángulo. Si cruza la costura, las u del
+// lado de 0 se pasan a 1 para no interpolar a través de toda la textura; en un polo la u de
+// esa esquina es la de las otras dos, que si no queda indefinida.
+fn seam_safe_uvs(normals: &[Vec3; 3]) -> [Vec2; 3] {
+    let mut uvs = normals.map(|n| Vec2::new(0.5 + (-n.z).atan2(n.x) / (2.0 * PI), 0.5 - n.y.clamp(-1.0, 1.0).asin() / PI));
+    let at_pole = normals.map(|n| n.y.abs() > 1.0 - 1e-5);
+
+    let (min, max) = uvs
+        .iter()
+        .zip(at_pole)
+        .filter(|(_, pole)| !pole)
+        .fold((f32::MAX, f32::MIN), |(min, max), (uv, _)| (min.min(uv.x), max.max(uv.x)));
+    if max - min > 0.5 {
+        for (uv, pole) in uvs.iter_mut().zip(at_pole) {
+            if !pole && uv.x < 0.5 {
+                uv.x += 1.0;
+            }
+        }
+    }
+
+    for i in 0..3 {
+        if at_pole[i] {
+            let others: Vec<f32> = (0..3).filter(|&j| !at_pole[j]).map(|j| uvs[j].x).collect();
+            uvs[i].x = others.iter().sum::<f32>() / others.len().max(1) as f32;
+        }
+    }
+    uvs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 1e-5;
+
+    // Normal geométrica del triángulo según su orden (antihorario = cara frontal)
+    fn face_normal(triangle: &[Vertex]) -> Vec3 {
+        (triangle[1].position - triangle[0].position).cross(&(triangle[2].position - triangle[0].position))
+    }
+
+    // Normales unitarias, vértices sobre la esfera y todas las caras hacia afuera
+    fn assert_closed_sphere(vertices: &[Vertex]) {
+        assert_eq!(vertices.len() % 3, 0);
+        for vertex in vertices {
+            assert!((vertex.normal.magnitude() - 1.0).abs() < EPSILON, "normal {:?}", vertex.normal);
+            assert!((vertex.position - vertex.normal * SPHERE_RADIUS).magnitude() < EPSILON);
+            assert!((0.0..=1.0).contains(&vertex.tex_coords.y));
+        }
+        for triangle in vertices.chunks_exact(3) {
+            let centroid = (triangle[0].position + triangle[1].position + triangle[2].position) / 3.0;
+            let normal = face_normal(triangle);
+            assert!(normal.magnitude() > 0.0, "triángulo degenerado en {centroid:?}");
+            assert!(normal.dot(&centroid) > 0.0, "cara hacia adentro en {centroid:?}");
+        }
+    }
+
+    #[test]
+    fn uv_sphere_counts_and_faces() {
+        for (stacks, slices) in [(2, 3), (8, 12), (24, 32)] {
+            let vertices = generate_uv_sphere(stacks, slices);
+            // Dos triángulos por celda, menos uno en cada celda de los polos
+            assert_eq!(vertices.len(), 3 * slices * (2 * stacks - 2));
+            assert_closed_sphere(&vertices);
+        }
+        // Por debajo del mínimo se usa el mínimo
+        assert_eq!(generate_uv_sphere(0, 0).len(), generate_uv_sphere(2, 3).len());
+    }
+
+    #[test]
+    fn icosphere_counts_and_faces() {
+        for subdivisions in 0..4 {
+            let vertices = generate_icosphere(subdivisions);
+            assert_eq!(vertices.len(), 3 * 20 * 4usize.pow(subdivisions));
+            assert_closed_sphere(&vertices);
+        }
+    }
+
+    #[test]
+    fn ring_counts_and_faces() {
+        let (inner, outer, segments) = (0.6, 1.2, 48);
+        let vertices = generate_ring(inner, outer, segments);
+        assert_eq!(vertices.len(), 6 * segments);
+        for vertex in &vertices {
+            assert_eq!(vertex.normal, Vec3::new(0.0, 1.0, 0.0));
+            let radius = Vec2::new(vertex.position.x, vertex.position.z).magnitude();
+            assert!(vertex.position.y == 0.0 && radius > inner - EPSILON && radius < outer + EPSILON);
+        }
+        // La única cara mira hacia +Y, como la normal
+        for triangle in vertices.chunks_exact(3) {
+            assert!(face_normal(triangle).y > 0.0);
+        }
+    }
+}
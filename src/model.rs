@@ -0,0 +1,50 @@
+use std::collections::HashMap;
+use crate::vertex::Vertex;
+use crate::clipping::BoundingSphere;
+use crate::material::Material;
+
+/// Malla indexada lista para render(): cada vértice único se sombrea una sola vez
+pub struct Model {
+    pub vertices: Vec<Vertex>,
+    pub indices: Vec<u32>,
+    pub bounds: BoundingSphere,
+    // Materiales a los que apunta `Vertex::material`
+    pub materials: Vec<Material>,
+}
+
+impl Model {
+    /// Construye la malla a partir de un arreglo de triángulos expandido, fusionando
+    /// solo los vértices cuyos atributos (posición, normal, uv, color y material) coinciden exactamente
+    pub fn from_vertex_array(vertex_array: &[Vertex], bounds: BoundingSphere) -> Self {
+        let mut vertices: Vec<Vertex> = Vec::new();
+        let mut indices = Vec::with_capacity(vertex_array.len());
+        let mut unique: HashMap<[u32; 12], u32> = HashMap::new();
+
+        for vertex in vertex_array {
+            let index = *unique.entry(attribute_key(vertex)).or_insert_with(|| {
+                vertices.push(vertex.clone());
+                (vertices.len() - 1) as u32
+            });
+            indices.push(index);
+        }
+
+        Model { vertices, indices, bounds, materials: Vec::new() }
+    }
+
+    /// Igual que from_vertex_array, con la esfera envolvente calculada de los vértices
+    pub fn from_vertices(vertex_array: &[Vertex]) -> Self {
+        let positions: Vec<_> = vertex_array.iter().map(|vertex| vertex.position).collect();
+        Model::from_vertex_array(vertex_array, BoundingSphere::from_points(&positions))
+    }
+}
+
+// Bits exactos de los atributos, para comparar vértices sin tolerancia
+fn attribute_key(vertex: &Vertex) -> [u32; 12] {
+    [
+        vertex.position.x.to_bits(), vertex.position.y.to_bits(), vertex.position.z.to_bits(),
+        vertex.normal.x.to_bits(), vertex.normal.y.to_bits(), vertex.normal.z.to_bits(),
+        vertex.tex_coords.x.to_bits(), vertex.tex_coords.y.to_bits(),
+        vertex.color.r.to_bits(), vertex.color.g.to_bits(), vertex.color.b.to_bits(),
+        vertex.material.unwrap_or(u32::MAX),
+    ]
+}
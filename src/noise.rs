@@ -0,0 +1,252 @@
+use nalgebra_glm::{Vec3, dot};
+
+/// Parámetros del ruido fractal: cuántas capas, cuánto sube la frecuencia y cuánto
+/// baja la amplitud de una capa a la siguiente.
+#[derive(Debug, Clone, Copy)]
+pub struct Fractal {
+    pub octaves: u32,
+    pub lacunarity: f32,
+    pub gain: f32,
+}
+
+impl Fractal {
+    pub const fn new(octaves: u32, lacunarity: f32, gain: f32) -> Self {
+        Fractal { octaves, lacunarity, gain }
+    }
+}
+
+// Direcciones de gradiente de Perlin (centros de las aristas de un cubo)
+const GRADIENTS: [[f32; 3]; 12] = [
+    [1.0, 1.0, 0.0], [-1.0, 1.0, 0.0], [1.0, -1.0, 0.0], [-1.0, -1.0, 0.0],
+    [1.0, 0.0, 1.0], [-1.0, 0.0, 1.0], [1.0, 0.0, -1.0], [-1.0, 0.0, -1.0],
+    [0.0, 1.0, 1.0], [0.0, -1.0, 1.0], [0.0, 1.0, -1.0], [0.0, -1.0, -1.0],
+];
+
+// Hash entero de una celda de la rejilla; sin tablas, así cualquier semilla sirve
+fn hash(x: i32, y: i32, z: i32, seed: u32) -> u32 {
+    let mut h = seed
+        ^ (x as u32).wrapping_mul(0x8da6_b343)
+        ^ (y as u32).wrapping_mul(0xd816_3841)
+        ^ (z as u32).wrapping_mul(0xcb1a_b31f);
+    h ^= h >> 13;
+    h = h.wrapping_mul(0x5bd1_e995);
+    h ^ (h >> 15)
+}
+
+fn gradient(x: i32, y: i32, z: i32, seed: u32, offset: &Vec3) -> f32 {
+    let [gx, gy, gz] = GRADIENTS[(hash(x, y, z, seed) % 12) as usize];
+    gx * offset.x + gy * offset.y + gz * offset.z
+}
+
+// Curva de suavizado de Perlin (6t^5 - 15t^4 + 10t^3): continua en primera y segunda derivada
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+/// Ruido de Perlin 3D en [-1, 1]. Determinista para una misma semilla.
+pub fn perlin(p: &Vec3, seed: u32) -> f32 {
+    let cell = Vec3::new(p.x.floor(), p.y.floor(), p.z.floor());
+    let (x, y, z) = (cell.x as i32, cell.y as i32, cell.z as i32);
+    let local = p - cell;
+    let (u, v, w) = (fade(local.x), fade(local.y), fade(local.z));
+
+    let corner = |dx: i32, dy: i32, dz: i32| {
+        let offset = local - Vec3::new(dx as f32, dy as f32, dz as f32);
+        gradient(x + dx, y + dy, z + dz, seed, &offset)
+    };
+
+    let x00 = lerp(corner(0, 0, 0), corner(1, 0, 0), u);
+    let x10 = lerp(corner(0, 1, 0), corner(1, 1, 0), u);
+    let x01 = lerp(corner(0, 0, 1), corner(1, 0, 1), u);
+    let x11 = lerp(corner(0, 1, 1), corner(1, 1, 1), u);
+
+    lerp(lerp(x00, x10, v), lerp(x01, x11, v), w).clamp(-1.0, 1.0)
+}
+
+/// Movimiento browniano fractal: suma de octavas de Perlin, normalizada a [-1, 1].
+pub fn fbm(p: &Vec3, seed: u32, fractal: &Fractal) -> f32 {
+    let mut sum = 0.0;
+    let mut amplitude = 1.0;
+    let mut total_amplitude = 0.0;
+    let mut frequency = 1.0;
+
+    for octave in 0..fractal.octaves {
+        sum += perlin(&(p * frequency), seed.wrapping_add(octave)) * amplitude;
+        total_amplitude += amplitude;
+        amplitude *= fractal.gain;
+        frequency *= fractal.lacunarity;
+    }
+
+    if total_amplitude > 0.0 { sum / total_amplitude } else { 0.0 }
+}
+
+/// Ruido con crestas (1 - |perlin|, al cuadrado) en [0, 1]: líneas finas y brillantes
+/// donde el Perlin cruza por cero, útil para grietas y cordilleras.
+pub fn ridged(p: &Vec3, seed: u32, fractal: &Fractal) -> f32 {
+    let mut sum = 0.0;
+    let mut amplitude = 1.0;
+    let mut total_amplitude = 0.0;
+    let mut frequency = 1.0;
+
+    for octave in 0..fractal.octaves {
+        let ridge = 1.0 - perlin(&(p * frequency), seed.wrapping_add(octave)).abs();
+        sum += ridge * ridge * amplitude;
+        total_amplitude += amplitude;
+        amplitude *= fractal.gain;
+        frequency *= fractal.lacunarity;
+    }
+
+    if total_amplitude > 0.0 { sum / total_amplitude } else { 0.0 }
+}
+
+// Valor pseudoaleatorio en [0, 1) para una celda y un canal
+fn cell_random(x: i32, y: i32, z: i32, seed: u32) -> f32 {
+    (hash(x, y, z, seed) >> 8) as f32 / (1u32 << 24) as f32
+}
+
+/// Puntos de luz dispersos: cada celda de la rejilla tiene, con probabilidad `density`,
+/// un punto en una posición aleatoria. Devuelve el brillo en [0, 1], que cae con la distancia
+/// al punto de la celda hasta anularse en `radius` (en unidades de celda).
+pub fn sparkle(p: &Vec3, seed: u32, density: f32, radius: f32) -> f32 {
+    let (x, y, z) = (p.x.floor() as i32, p.y.floor() as i32, p.z.floor() as i32);
+    if cell_random(x, y, z, seed) >= density {
+        return 0.0;
+    }
+
+    // El punto queda dentro de la celda con margen, así no se corta en el borde
+    let jitter = |channel: u32| 0.25 + 0.5 * cell_random(x, y, z, seed.wrapping_add(channel));
+    let point = Vec3::new(x as f32 + jitter(1), y as f32 + jitter(2), z as f32 + jitter(3));
+
+    let falloff = 1.0 - (p - point).magnitude() / radius;
+    falloff.max(0.0) * falloff.max(0.0)
+}
+
+/// Cráter sobre la esfera unitaria: centro (unitario) y radio (distancia de cuerda)
+#[derive(Debug, Clone, Copy)]
+pub struct Crater {
+    pub center: Vec3,
+    pub radius: f32,
+}
+
+/// Cráter más cercano a un punto: distancia al centro en radios del cráter y dirección
+/// (tangente a la esfera) desde el centro hacia el punto
+#[derive(Debug, Clone, Copy)]
+pub struct CraterHit {
+    pub distance: f32,
+    pub direction: Vec3,
+}
+
+/// Conjunto determinista de cráteres, ordenado por la y del centro: la búsqueda sólo
+/// revisa la franja de latitudes que un cráter puede alcanzar.
+pub struct CraterField {
+    craters: Vec<Crater>,
+    max_radius: f32,
+}
+
+impl CraterField {
+    /// `count` cráteres repartidos uniformemente sobre la esfera. Los radios siguen una ley
+    /// de potencias: muchos pequeños y pocos grandes.
+    pub fn new(seed: u32, count: usize, min_radius: f32, max_radius: f32) -> Self {
+        let mut craters: Vec<Crater> = (0..count as i32)
+            .map(|i| {
+                let y = cell_random(i, 0, 0, seed) * 2.0 - 1.0;
+                let angle = cell_random(i, 1, 0, seed) * 2.0 * std::f32::consts::PI;
+                let ring = (1.0 - y * y).sqrt();
+                let size = cell_random(i, 2, 0, seed).powi(3);
+                Crater {
+                    center: Vec3::new(ring * angle.cos(), y, ring * angle.sin()),
+                    radius: min_radius + (max_radius - min_radius) * size,
+                }
+            })
+            .collect();
+        craters.sort_by(|a, b| a.center.y.total_cmp(&b.center.y));
+        CraterField { craters, max_radius }
+    }
+
+    /// El cráter en cuyo interior relativo está más metido `p` (punto de la esfera unitaria),
+    /// si alguno queda a menos de `reach` radios
+    pub fn nearest(&self, p: &Vec3, reach: f32) -> Option<CraterHit> {
+        let band = self.max_radius * reach;
+        let start = self.craters.partition_point(|crater| crater.center.y < p.y - band);
+
+        let mut best: Option<(f32, &Crater)> = None;
+        for crater in self.craters[start..].iter().take_while(|crater| crater.center.y <= p.y + band) {
+            let distance = (p - crater.center).magnitude() / crater.radius;
+            if distance < reach && best.is_none_or(|(closest, _)| distance < closest) {
+                best = Some((distance, crater));
+            }
+        }
+
+        best.map(|(distance, crater)| {
+            let offset = p - crater.center;
+            let tangent = offset - p * dot(&offset, p);
+            CraterHit { distance, direction: tangent.try_normalize(f32::EPSILON).unwrap_or(Vec3::zeros()) }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+
+    const FRACTAL: Fractal = Fractal::new(5, 2.0, 0.5);
+
+    fn random_points(count: usize) -> Vec<Vec3> {
+        let mut rng = StdRng::seed_from_u64(30);
+        (0..count)
+            .map(|_| Vec3::new(rng.gen_range(-50.0..50.0), rng.gen_range(-50.0..50.0), rng.gen_range(-50.0..50.0)))
+            .collect()
+    }
+
+    #[test]
+    fn values_stay_in_range() {
+        for p in random_points(20_000) {
+            assert!((-1.0..=1.0).contains(&perlin(&p, 7)), "perlin fuera de rango en {p:?}");
+            assert!((-1.0..=1.0).contains(&fbm(&p, 7, &FRACTAL)), "fbm fuera de rango en {p:?}");
+            assert!((0.0..=1.0).contains(&ridged(&p, 7, &FRACTAL)), "ridged fuera de rango en {p:?}");
+            assert!((0.0..=1.0).contains(&sparkle(&p, 7, 0.5, 0.3)), "sparkle fuera de rango en {p:?}");
+        }
+    }
+
+    // Un paso pequeño cambia poco el valor, también al cruzar las aristas de la rejilla
+    #[test]
+    fn perlin_is_continuous() {
+        let step = 1e-3;
+        // Cota holgada de la pendiente: gradientes de largo √2 sumados sobre 8 esquinas
+        let max_slope = 8.0;
+        let offsets = [Vec3::new(step, 0.0, 0.0), Vec3::new(0.0, step, 0.0), Vec3::new(0.0, 0.0, step)];
+
+        let mut points = random_points(5_000);
+        points.extend((-5..5).map(|i| Vec3::new(i as f32 - step / 2.0, 0.3, -0.7)));
+        for p in points {
+            let value = perlin(&p, 3);
+            for offset in &offsets {
+                let change = (perlin(&(p + offset), 3) - value).abs();
+                assert!(change <= max_slope * step, "salto de {change} en {p:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn same_seed_gives_same_values() {
+        let points = random_points(1_000);
+        let sample = |seed: u32| -> Vec<f32> { points.iter().map(|p| fbm(p, seed, &FRACTAL)).collect() };
+        assert_eq!(sample(42), sample(42));
+
+        // Otra semilla da otro campo, no el mismo desplazado por redondeo
+        let differing = sample(42).iter().zip(sample(43)).filter(|(a, b)| (*a - b).abs() > 1e-3).count();
+        assert!(differing > points.len() * 9 / 10, "sólo {differing} puntos cambian con la semilla");
+
+        let first = CraterField::new(9, 200, 0.02, 0.2);
+        let second = CraterField::new(9, 200, 0.02, 0.2);
+        for (a, b) in first.craters.iter().zip(&second.craters) {
+            assert_eq!((a.center, a.radius), (b.center, b.radius));
+        }
+    }
+}
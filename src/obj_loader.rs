@@ -1,9 +1,74 @@
-use tobj;
-use nalgebra_glm::{Vec2, Vec3};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::io;
+use std::path::Path;
+use nalgebra_glm::{Vec2, Vec3, Mat4, scaling, translation};
 use crate::vertex::Vertex;
+use crate::clipping::BoundingSphere;
+use crate::model::Model;
+use crate::material::Material;
+use crate::color::Color;
+
+/// Error al leer un .obj; `line` es la línea del archivo (desde 1) donde ocurrió
+#[derive(Debug)]
+pub enum ObjError {
+    /// No se pudo leer el archivo
+    Io { source: io::Error },
+    /// Una línea tiene bytes que no son UTF-8
+    InvalidUtf8 { line: usize, source: std::str::Utf8Error },
+    /// Un número de v, vt o vn que falta o no se puede leer
+    ParseFloat { line: usize },
+    /// Índice de una cara fuera de la lista a la que apunta, que tiene `max` elementos
+    InvalidIndex { line: usize, index: i64, max: usize },
+    /// Cara con menos de tres esquinas o con una esquina mal escrita
+    InvalidFace { line: usize },
+    /// Directiva que no es parte del formato (las válidas que no se usan se saltan)
+    UnsupportedDirective { line: usize, directive: String },
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ObjError::Io { source } => write!(f, "no se pudo leer el archivo: {}", source),
+            ObjError::InvalidUtf8 { line, source } => write!(f, "línea {}: texto que no es UTF-8 ({})", line, source),
+            ObjError::ParseFloat { line } => write!(f, "línea {}: número inválido", line),
+            ObjError::InvalidIndex { line, index, max } => write!(f, "línea {}: índice {} fuera de rango (hay {})", line, index, max),
+            ObjError::InvalidFace { line } => write!(f, "línea {}: cara inválida", line),
+            ObjError::UnsupportedDirective { line, directive } => write!(f, "línea {}: directiva no soportada '{}'", line, directive),
+        }
+    }
+}
+
+impl std::error::Error for ObjError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ObjError::Io { source } => Some(source),
+            ObjError::InvalidUtf8 { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// Opciones de carga de un .obj
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ObjOptions {
+    // Normales planas de cada cara en vez de suavizadas, aunque el archivo traiga las suyas
+    pub flat_normals: bool,
+}
+
+/// Punto del modelo que queda en el origen al normalizarlo
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pivot {
+    /// Centro de la caja envolvente
+    #[allow(dead_code)]
+    Center,
+    /// Centro de la cara de abajo de la caja (para apoyar el modelo, como la nave)
+    Bottom,
+}
 
 pub struct Obj {
     meshes: Vec<Mesh>,
+    materials: Vec<Material>,
 }
 
 struct Mesh {
@@ -11,33 +76,331 @@ struct Mesh {
     normals: Vec<Vec3>,
     texcoords: Vec<Vec2>,
     indices: Vec<u32>,
+    // Índice en `Obj::materials`; None sin usemtl o con un material que no está en el .mtl
+    material: Option<u32>,
+}
+
+// Normal para los vértices que no tocan ninguna cara válida
+const FALLBACK_NORMAL: Vec3 = Vec3::new(0.0, 1.0, 0.0);
+
+impl Mesh {
+    // Normal de un triángulo según su orden (antihorario = cara frontal), con largo igual al
+    // doble de su área; None si es degenerado
+    fn face_normal(&self, triangle: &[u32]) -> Option<Vec3> {
+        let [a, b, c] = [0, 1, 2].map(|i| self.vertices[triangle[i] as usize]);
+        let normal = (b - a).cross(&(c - a));
+        (normal.magnitude() > f32::EPSILON).then_some(normal)
+    }
+
+    // Promedio de las normales de las caras que comparten cada posición, pesado por área. Se
+    // agrupa por posición y no por índice para que las costuras de las uv no se vean.
+    fn smooth_normals(&self) -> Vec<Vec3> {
+        let key = |position: &Vec3| [position.x.to_bits(), position.y.to_bits(), position.z.to_bits()];
+        let mut sums: HashMap<[u32; 3], Vec3> = HashMap::new();
+        for triangle in self.indices.chunks_exact(3) {
+            let Some(normal) = self.face_normal(triangle) else { continue };
+            for &index in triangle {
+                *sums.entry(key(&self.vertices[index as usize])).or_insert_with(Vec3::zeros) += normal;
+            }
+        }
+        self.vertices.iter()
+            .map(|position| {
+                sums.get(&key(position))
+                    .and_then(|sum| sum.try_normalize(f32::EPSILON))
+                    .unwrap_or(FALLBACK_NORMAL)
+            })
+            .collect()
+    }
+
+    // Separa los vértices de cada triángulo para que lleven la normal de su cara; los
+    // degenerados no cubren ningún pixel y se descartan
+    fn flatten(&mut self) {
+        let mut vertices = Vec::with_capacity(self.indices.len());
+        let mut normals = Vec::with_capacity(self.indices.len());
+        let mut texcoords = Vec::new();
+        for triangle in self.indices.chunks_exact(3) {
+            let Some(normal) = self.face_normal(triangle).and_then(|normal| normal.try_normalize(f32::EPSILON)) else {
+                continue;
+            };
+            for &index in triangle {
+                vertices.push(self.vertices[index as usize]);
+                normals.push(normal);
+                if let Some(&uv) = self.texcoords.get(index as usize) {
+                    texcoords.push(uv);
+                }
+            }
+        }
+        // Si faltaban uv en algún vértice, mejor ninguna que desalineadas
+        if texcoords.len() != vertices.len() {
+            texcoords.clear();
+        }
+        self.indices = (0..vertices.len() as u32).collect();
+        self.vertices = vertices;
+        self.normals = normals;
+        self.texcoords = texcoords;
+    }
+
+    // Fusiona las posiciones a menos de `epsilon` entre sí (en una grilla de celdas de ese
+    // lado, mirando las vecinas). Los vértices de una misma posición siguen separados si sus
+    // uv son distintas, pero comparten la normal promedio, así la costura no se ilumina
+    // distinto de cada lado. Se reconstruye todo desde los índices: los vértices sin usar se
+    // pierden y los triángulos que quedan degenerados se quitan.
+    fn weld(&mut self, epsilon: f32, stats: &mut WeldStats) {
+        let epsilon = epsilon.max(f32::EPSILON);
+        let cell = |p: &Vec3| (p / epsilon).map(|c| c.floor() as i64);
+        let mut grid: HashMap<(i64, i64, i64), Vec<usize>> = HashMap::new();
+        let mut representatives: Vec<Vec3> = Vec::new();
+        let cluster: Vec<usize> = self.vertices.iter()
+            .map(|position| {
+                let c = cell(position);
+                let neighbours = (-1..=1).flat_map(|x| (-1..=1).flat_map(move |y| (-1..=1).map(move |z| (c.x + x, c.y + y, c.z + z))));
+                let found = neighbours
+                    .filter_map(|key| grid.get(&key))
+                    .flatten()
+                    .copied()
+                    .find(|&r| (representatives[r] - position).magnitude() <= epsilon);
+                found.unwrap_or_else(|| {
+                    representatives.push(*position);
+                    grid.entry((c.x, c.y, c.z)).or_default().push(representatives.len() - 1);
+                    representatives.len() - 1
+                })
+            })
+            .collect();
+
+        let mut normal_sums = vec![Vec3::zeros(); representatives.len()];
+        for (&index, normal) in cluster.iter().zip(&self.normals) {
+            normal_sums[index] += normal;
+        }
+
+        let mut welded = Mesh { vertices: Vec::new(), normals: Vec::new(), texcoords: Vec::new(), indices: Vec::new(), material: self.material };
+        let mut lookup: HashMap<(usize, [u32; 2]), u32> = HashMap::new();
+        for triangle in self.indices.chunks_exact(3) {
+            let corners = [0, 1, 2].map(|i| {
+                let index = triangle[i] as usize;
+                let uv = self.texcoords.get(index).copied().unwrap_or_else(Vec2::zeros);
+                (cluster[index], uv)
+            });
+            let [a, b, c] = corners.map(|(r, _)| representatives[r]);
+            if corners[0].0 == corners[1].0 || corners[1].0 == corners[2].0 || corners[0].0 == corners[2].0
+                || (b - a).cross(&(c - a)).magnitude() <= f32::EPSILON {
+                stats.degenerate_removed += 1;
+                continue;
+            }
+            for (r, uv) in corners {
+                let index = *lookup.entry((r, [uv.x.to_bits(), uv.y.to_bits()])).or_insert_with(|| {
+                    welded.vertices.push(representatives[r]);
+                    welded.normals.push(normal_sums[r].try_normalize(f32::EPSILON).unwrap_or(FALLBACK_NORMAL));
+                    welded.texcoords.push(uv);
+                    (welded.vertices.len() - 1) as u32
+                });
+                welded.indices.push(index);
+            }
+        }
+
+        stats.vertices_before += self.vertices.len();
+        stats.vertices_after += welded.vertices.len();
+        stats.triangles += welded.indices.len() / 3;
+        *self = welded;
+    }
+}
+
+/// Resultado de Obj::load_welded
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WeldStats {
+    pub vertices_before: usize,
+    pub vertices_after: usize,
+    pub triangles: usize,
+    pub degenerate_removed: usize,
+}
+
+impl fmt::Display for WeldStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} -> {} vértices, {} triángulos, {} degenerados quitados",
+            self.vertices_before, self.vertices_after, self.triangles, self.degenerate_removed
+        )
+    }
 }
 
 impl Obj {
-    pub fn load(filename: &str) -> Result<Self, tobj::LoadError> {
-        let (models, _) = tobj::load_obj(filename, &tobj::LoadOptions {
-            single_index: true,
-            triangulate: true,
-            ..Default::default()
-        })?;
-
-        let meshes = models.into_iter().map(|model| {
-            let mesh = model.mesh;
-            Mesh {
-                vertices: mesh.positions.chunks(3)
-                    .map(|v| Vec3::new(v[0], v[1], v[2]))
-                    .collect(),
-                normals: mesh.normals.chunks(3)
-                    .map(|n| Vec3::new(n[0], n[1], n[2]))
-                    .collect(),
-                texcoords: mesh.texcoords.chunks(2)
-                    .map(|t| Vec2::new(t[0], 1.0 - t[1]))
-                    .collect(),
-                indices: mesh.indices,
-            }
-        }).collect();
-
-        Ok(Obj { meshes })
+    pub fn load(filename: &str) -> Result<Self, ObjError> {
+        Self::load_with_options(filename, ObjOptions::default())
+    }
+
+    /// Carga el modelo y fusiona los vértices repetidos (ver Mesh::weld); para mallas suaves
+    /// como la esfera, cuyas costuras de uv duplican posiciones
+    pub fn load_welded(filename: &str, epsilon: f32) -> Result<(Self, WeldStats), ObjError> {
+        let mut obj = Self::load(filename)?;
+        let mut stats = WeldStats::default();
+        for mesh in &mut obj.meshes {
+            mesh.weld(epsilon, &mut stats);
+        }
+        Ok((obj, stats))
+    }
+
+    /// Carga el modelo; si no trae normales (sin `vn`) se calculan suavizadas a partir de
+    /// las caras, o planas con `options.flat_normals`
+    pub fn load_with_options(filename: &str, options: ObjOptions) -> Result<Self, ObjError> {
+        let bytes = std::fs::read(filename).map_err(|source| ObjError::Io { source })?;
+        let directory = Path::new(filename).parent().unwrap_or(Path::new(""));
+        Self::parse(&bytes, directory, options)
+    }
+
+    // Lee el contenido de un .obj ya cargado; los mtllib se buscan relativos a `directory`
+    fn parse(bytes: &[u8], directory: &Path, options: ObjOptions) -> Result<Self, ObjError> {
+        let (mut positions, mut normals, mut texcoords) = (Vec::new(), Vec::new(), Vec::new());
+        let mut materials: Vec<Material> = Vec::new();
+        let mut material_names: HashMap<String, u32> = HashMap::new();
+        let mut builders = vec![MeshBuilder::new(None)];
+        let mut ignored: HashSet<&str> = HashSet::new();
+
+        // Se decodifica línea por línea para poder decir dónde está un byte que no es UTF-8
+        for (number, bytes) in bytes.split(|&byte| byte == b'\n').enumerate() {
+            let line = number + 1;
+            let text = std::str::from_utf8(bytes).map_err(|source| ObjError::InvalidUtf8 { line, source })?;
+            let mut words = text.split_whitespace();
+            let Some(directive) = words.next() else { continue };
+            match directive {
+                _ if directive.starts_with('#') => {}
+                "v" => positions.push(parse_vec3(words, line)?),
+                "vn" => normals.push(parse_vec3(words, line)?),
+                "vt" => {
+                    // La v es opcional y la w no se usa; la imagen tiene la fila 0 arriba
+                    let mut values = parse_floats(words, line)?.into_iter();
+                    let u = values.next().ok_or(ObjError::ParseFloat { line })?;
+                    texcoords.push(Vec2::new(u, 1.0 - values.next().unwrap_or(0.0)));
+                }
+                "f" => {
+                    let corners = words
+                        .map(|word| Corner::parse(word, line, positions.len(), texcoords.len(), normals.len()))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    if corners.len() < 3 {
+                        return Err(ObjError::InvalidFace { line });
+                    }
+                    let builder = builders.last_mut().expect("siempre hay una malla abierta");
+                    // Abanico desde la primera esquina: (v0, vi, vi+1)
+                    for i in 1..corners.len() - 1 {
+                        for corner in [corners[0], corners[i], corners[i + 1]] {
+                            builder.push(corner, &positions, &texcoords, &normals);
+                        }
+                    }
+                }
+                // Cada objeto, grupo o cambio de material abre una malla nueva
+                "o" | "g" => {
+                    let material = builders.last().and_then(|builder| builder.mesh.material);
+                    builders.push(MeshBuilder::new(material));
+                }
+                "usemtl" => {
+                    let name = words.collect::<Vec<_>>().join(" ");
+                    builders.push(MeshBuilder::new(material_names.get(&name).copied()));
+                }
+                // Sin .mtl (o con uno roto) el modelo se carga igual, con el color del shader
+                "mtllib" => {
+                    let path = directory.join(words.collect::<Vec<_>>().join(" "));
+                    match tobj::load_mtl(&path) {
+                        Ok((loaded, names)) => {
+                            let offset = materials.len() as u32;
+                            materials.extend(loaded.iter().map(convert_material));
+                            material_names.extend(names.into_iter().map(|(name, index)| (name, offset + index as u32)));
+                        }
+                        Err(err) => eprintln!("{}: materiales ignorados ({})", path.display(), err),
+                    }
+                }
+                // Los grupos de suavizado no cambian nada: las normales vienen del archivo
+                "s" => {}
+                // Partes válidas del formato que no se dibujan (líneas, puntos, curvas y
+                // superficies, atributos de render); se avisa una vez por directiva
+                _ if IGNORED_DIRECTIVES.contains(&directive) => {
+                    if ignored.insert(directive) {
+                        eprintln!("línea {}: se ignora la directiva '{}'", line, directive);
+                    }
+                }
+                _ => return Err(ObjError::UnsupportedDirective { line, directive: directive.to_string() }),
+            }
+        }
+
+        let mut meshes: Vec<Mesh> = builders.into_iter()
+            .filter(|builder| !builder.mesh.indices.is_empty())
+            .map(|builder| builder.finish(options))
+            .collect();
+        // Un usemtl anterior al mtllib no encuentra su material
+        for mesh in &mut meshes {
+            mesh.material = mesh.material.filter(|&id| (id as usize) < materials.len());
+        }
+
+        Ok(Obj { meshes, materials })
+    }
+
+    /// Modelo de una sola malla a partir de listas ya resueltas (un vértice por índice), para
+    /// los formatos que no pasan por el parser de .obj. Sin normales se calculan como en load.
+    pub fn from_triangles(
+        positions: Vec<Vec3>,
+        normals: Option<Vec<Vec3>>,
+        texcoords: Option<Vec<Vec2>>,
+        indices: Vec<u32>,
+        options: ObjOptions,
+    ) -> Self {
+        let count = positions.len();
+        let missing_normals = normals.is_none();
+        let mut mesh = Mesh {
+            vertices: positions,
+            normals: normals.unwrap_or_else(|| vec![FALLBACK_NORMAL; count]),
+            texcoords: texcoords.unwrap_or_else(|| vec![Vec2::zeros(); count]),
+            indices,
+            material: None,
+        };
+        if options.flat_normals {
+            mesh.flatten();
+        } else if missing_normals {
+            mesh.normals = mesh.smooth_normals();
+        }
+        Obj { meshes: vec![mesh], materials: Vec::new() }
+    }
+
+    /// Caja envolvente (mínimo y máximo) de todas las mallas
+    pub fn bounds(&self) -> (Vec3, Vec3) {
+        self.meshes.iter()
+            .flat_map(|mesh| mesh.vertices.iter())
+            .fold(None, |bounds: Option<(Vec3, Vec3)>, p| {
+                Some(bounds.map_or((*p, *p), |(min, max)| (min.inf(p), max.sup(p))))
+            })
+            .unwrap_or((Vec3::zeros(), Vec3::zeros()))
+    }
+
+    /// Lleva el `pivot` de la caja envolvente al origen y escala el modelo para que su lado
+    /// más largo mida 1. Devuelve también la transformación aplicada, para quien necesite
+    /// volver a las unidades del archivo. Las normales no cambian: la escala es uniforme.
+    pub fn normalized(mut self, pivot: Pivot) -> (Self, Mat4) {
+        let (min, max) = self.bounds();
+        let extent = (max - min).max();
+        let mut origin = (min + max) / 2.0;
+        if pivot == Pivot::Bottom {
+            origin.y = min.y;
+        }
+        let scale = if extent > f32::EPSILON { 1.0 / extent } else { 1.0 };
+
+        for mesh in &mut self.meshes {
+            for position in &mut mesh.vertices {
+                *position = (*position - origin) * scale;
+            }
+        }
+        (self, scaling(&Vec3::new(scale, scale, scale)) * translation(&-origin))
+    }
+
+    pub fn bounding_sphere(&self) -> BoundingSphere {
+        let positions: Vec<Vec3> = self.meshes.iter()
+            .flat_map(|mesh| mesh.vertices.iter().cloned())
+            .collect();
+        BoundingSphere::from_points(&positions)
+    }
+
+    /// Malla indexada sin vértices duplicados
+    pub fn get_model(&self) -> Model {
+        Model {
+            materials: self.materials.clone(),
+            ..Model::from_vertex_array(&self.get_vertex_array(), self.bounding_sphere())
+        }
     }
 
     pub fn get_vertex_array(&self) -> Vec<Vertex> {
@@ -48,15 +411,264 @@ impl Obj {
                 let position = mesh.vertices[index as usize];
                 let normal = mesh.normals.get(index as usize)
                     .cloned()
-                    .unwrap_or(Vec3::new(0.0, 1.0, 0.0));
+                    .unwrap_or(FALLBACK_NORMAL);
                 let tex_coords = mesh.texcoords.get(index as usize)
                     .cloned()
                     .unwrap_or(Vec2::new(0.0, 0.0));
 
-                vertices.push(Vertex::new(position, normal, tex_coords));
+                vertices.push(Vertex { material: mesh.material, ..Vertex::new(position, normal, tex_coords) });
             }
         }
 
         vertices
     }
 }
+
+// Directivas del formato .obj que el cargador no usa
+const IGNORED_DIRECTIVES: [&str; 29] = [
+    "l", "p", "vp", "cstype", "deg", "bmat", "step", "curv", "curv2", "surf", "parm", "trim", "hole", "scrv",
+    "sp", "end", "con", "mg", "lod", "bevel", "c_interp", "d_interp", "usemap", "maplib", "shadow_obj",
+    "trace_obj", "ctech", "stech", "call",
+];
+
+// Esquina de una cara: índices (ya resueltos, desde 0) de posición, uv y normal. Acepta
+// v, v/vt, v//vn y v/vt/vn, con índices negativos relativos al final de cada lista.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Corner {
+    position: usize,
+    texcoord: Option<usize>,
+    normal: Option<usize>,
+}
+
+impl Corner {
+    fn parse(word: &str, line: usize, positions: usize, texcoords: usize, normals: usize) -> Result<Corner, ObjError> {
+        let mut parts = word.split('/');
+        let position = resolve_index(parts.next().unwrap_or(""), line, positions)?;
+        let mut optional = |count: usize| match parts.next() {
+            None | Some("") => Ok(None),
+            Some(index) => resolve_index(index, line, count).map(Some),
+        };
+        let texcoord = optional(texcoords)?;
+        let normal = optional(normals)?;
+        if parts.next().is_some() {
+            return Err(ObjError::InvalidFace { line });
+        }
+        Ok(Corner { position, texcoord, normal })
+    }
+}
+
+// Índice del .obj (desde 1, o negativo desde el final) a posición en una lista de `count`
+fn resolve_index(index: &str, line: usize, count: usize) -> Result<usize, ObjError> {
+    let index: i64 = index.parse().map_err(|_| ObjError::InvalidFace { line })?;
+    let resolved = if index < 0 { count as i64 + index } else { index - 1 };
+    if (0..count as i64).contains(&resolved) {
+        Ok(resolved as usize)
+    } else {
+        Err(ObjError::InvalidIndex { line, index, max: count })
+    }
+}
+
+fn parse_floats<'a>(words: impl Iterator<Item = &'a str>, line: usize) -> Result<Vec<f32>, ObjError> {
+    words.map(|word| word.parse().map_err(|_| ObjError::ParseFloat { line })).collect()
+}
+
+fn parse_vec3<'a>(words: impl Iterator<Item = &'a str>, line: usize) -> Result<Vec3, ObjError> {
+    // Los colores por vértice que agregan algunos exportadores después de xyz se ignoran
+    match parse_floats(words, line)?.as_slice() {
+        [x, y, z, ..] => Ok(Vec3::new(*x, *y, *z)),
+        _ => Err(ObjError::ParseFloat { line }),
+    }
+}
+
+// Malla en construcción: cada combinación distinta de índices es un vértice
+struct MeshBuilder {
+    mesh: Mesh,
+    corners: HashMap<Corner, u32>,
+    // Hay esquinas sin vn, cuyas normales se calculan al terminar
+    missing_normals: bool,
+}
+
+impl MeshBuilder {
+    fn new(material: Option<u32>) -> Self {
+        MeshBuilder {
+            mesh: Mesh { vertices: Vec::new(), normals: Vec::new(), texcoords: Vec::new(), indices: Vec::new(), material },
+            corners: HashMap::new(),
+            missing_normals: false,
+        }
+    }
+
+    fn push(&mut self, corner: Corner, positions: &[Vec3], texcoords: &[Vec2], normals: &[Vec3]) {
+        let mesh = &mut self.mesh;
+        let missing_normals = &mut self.missing_normals;
+        let index = *self.corners.entry(corner).or_insert_with(|| {
+            mesh.vertices.push(positions[corner.position]);
+            mesh.texcoords.push(corner.texcoord.map_or(Vec2::zeros(), |index| texcoords[index]));
+            *missing_normals |= corner.normal.is_none();
+            mesh.normals.push(corner.normal.map_or(FALLBACK_NORMAL, |index| normals[index]));
+            (mesh.vertices.len() - 1) as u32
+        });
+        mesh.indices.push(index);
+    }
+
+    fn finish(self, options: ObjOptions) -> Mesh {
+        let MeshBuilder { mut mesh, corners, missing_normals } = self;
+        if options.flat_normals {
+            mesh.flatten();
+        } else if missing_normals {
+            // Sólo se reemplazan las normales que faltaban
+            let smooth = mesh.smooth_normals();
+            for (corner, &index) in &corners {
+                if corner.normal.is_none() {
+                    mesh.normals[index as usize] = smooth[index as usize];
+                }
+            }
+        }
+        mesh
+    }
+}
+
+// Colores del .mtl (lineales, en [0, 1]); lo que falta queda con el valor por defecto.
+// Ke no es parte del formato original, tobj lo deja entre los parámetros desconocidos.
+fn convert_material(material: &tobj::Material) -> Material {
+    let default = Material::default();
+    let emissive = material.unknown_param.get("Ke").and_then(|value| {
+        let channels: Vec<f32> = value.split_whitespace().filter_map(|v| v.parse().ok()).collect();
+        (channels.len() == 3).then(|| [channels[0], channels[1], channels[2]])
+    });
+    Material {
+        diffuse: material.diffuse.map_or(default.diffuse, Color::from_linear),
+        specular: material.specular.map_or(default.specular, Color::from_linear),
+        shininess: material.shininess.unwrap_or(default.shininess),
+        emissive: emissive.map_or(default.emissive, Color::from_linear),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str, options: ObjOptions) -> Result<Obj, ObjError> {
+        Obj::parse(text.as_bytes(), Path::new(""), options)
+    }
+
+    // Cubo unitario centrado en el origen, con las caras antihorarias vistas desde afuera
+    const CUBE: &str = "\
+v -0.5 -0.5 -0.5
+v 0.5 -0.5 -0.5
+v 0.5 0.5 -0.5
+v -0.5 0.5 -0.5
+v -0.5 -0.5 0.5
+v 0.5 -0.5 0.5
+v 0.5 0.5 0.5
+v -0.5 0.5 0.5
+f 1 4 3 2
+f 5 6 7 8
+f 1 5 8 4
+f 2 3 7 6
+f 1 2 6 5
+f 4 8 7 3
+";
+
+    #[test]
+    fn cube_without_normals_gets_outward_normals() {
+        let obj = parse(CUBE, ObjOptions::default()).unwrap();
+        let mesh = &obj.meshes[0];
+        assert_eq!(mesh.vertices.len(), 8);
+        // Suavizadas: cada esquina promedia los triángulos de sus tres caras (uno o dos según
+        // el abanico), así apunta hacia afuera, entre las tres caras
+        for (position, normal) in mesh.vertices.iter().zip(&mesh.normals) {
+            assert!((normal.magnitude() - 1.0).abs() < 1e-5);
+            assert!((0..3).all(|i| normal[i] * position[i] > 0.0), "{normal:?} en {position:?}");
+        }
+
+        let obj = parse(CUBE, ObjOptions { flat_normals: true }).unwrap();
+        let mesh = &obj.meshes[0];
+        assert_eq!(mesh.vertices.len(), 36);
+        for (triangle, normals) in mesh.vertices.chunks_exact(3).zip(mesh.normals.chunks_exact(3)) {
+            let center = (triangle[0] + triangle[1] + triangle[2]) / 3.0;
+            for normal in normals {
+                // Planas: unitarias, sobre un eje y del lado de afuera de la cara
+                assert!((normal.magnitude() - 1.0).abs() < 1e-5);
+                assert!(normal.dot(&center) > 0.0, "normal {normal:?} hacia adentro");
+                assert_eq!(normal.iter().filter(|c| c.abs() > 0.5).count(), 1);
+            }
+        }
+    }
+
+    const SQUARE: &str = "\
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vt 0 0
+vt 1 0
+vt 1 1
+vt 0 1
+vn 0 0 1
+";
+
+    fn positions(obj: &Obj) -> Vec<[f32; 3]> {
+        let mesh = &obj.meshes[0];
+        mesh.indices.iter().map(|&index| mesh.vertices[index as usize].into()).collect()
+    }
+
+    #[test]
+    fn quads_and_ngons_are_fan_triangulated() {
+        let quad = parse(&format!("{SQUARE}f 1 2 3 4\n"), ObjOptions::default()).unwrap();
+        assert_eq!(quad.meshes[0].indices, vec![0, 1, 2, 0, 2, 3]);
+
+        // Pentágono: tres triángulos que salen de la primera esquina
+        let pentagon = format!("{SQUARE}v 0.5 1.5 0\nf 1 2 3 5 4\n");
+        let pentagon = parse(&pentagon, ObjOptions::default()).unwrap();
+        assert_eq!(pentagon.meshes[0].indices, vec![0, 1, 2, 0, 2, 3, 0, 3, 4]);
+        assert_eq!(pentagon.meshes[0].vertices[3], Vec3::new(0.5, 1.5, 0.0));
+    }
+
+    #[test]
+    fn negative_indices_count_from_the_end() {
+        let positive = parse(&format!("{SQUARE}f 1/1/1 2/2/1 3/3/1 4/4/1\n"), ObjOptions::default()).unwrap();
+        let negative = parse(&format!("{SQUARE}f -4/-4/-1 -3/-3/-1 -2/-2/-1 -1/-1/-1\n"), ObjOptions::default()).unwrap();
+        assert_eq!(positions(&negative), positions(&positive));
+        assert_eq!(negative.meshes[0].texcoords, positive.meshes[0].texcoords);
+        assert_eq!(negative.meshes[0].normals, positive.meshes[0].normals);
+
+        // Relativos a lo leído hasta la cara, no al final del archivo
+        let relative = parse(&format!("{SQUARE}f -4//-1 -3//-1 -2//-1\nv 5 5 5\nf -1 -2 -3\n"), ObjOptions::default()).unwrap();
+        let corners = positions(&relative);
+        assert_eq!(corners[..3], [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0]]);
+        assert_eq!(corners[3..], [[5.0, 5.0, 5.0], [0.0, 1.0, 0.0], [1.0, 1.0, 0.0]]);
+    }
+
+    #[test]
+    fn valid_but_unused_directives_are_skipped() {
+        let text = format!("{SQUARE}l 1 2 3\np 4\nvp 0.5 0.5\ncstype bspline\ndeg 3\nf 1 2 3\n");
+        let obj = parse(&text, ObjOptions::default()).unwrap();
+        assert_eq!(obj.meshes[0].indices.len(), 3);
+
+        assert!(matches!(
+            parse("v 0 0 0\nfoo 1 2\n", ObjOptions::default()),
+            Err(ObjError::UnsupportedDirective { line: 2, ref directive }) if directive == "foo"
+        ));
+    }
+
+    #[test]
+    fn malformed_input_maps_to_specific_errors() {
+        let error = |text: &[u8]| Obj::parse(text, Path::new(""), ObjOptions::default()).err().expect("debería fallar");
+
+        // Archivos cortados a media línea
+        assert!(matches!(error(b"v 1 2"), ObjError::ParseFloat { line: 1 }));
+        assert!(matches!(error(b"v 0 0 0\nvt"), ObjError::ParseFloat { line: 2 }));
+        assert!(matches!(error(b"v 0 0 0\nv 1 0 0\nv 1 1 0\nf 1 2"), ObjError::InvalidFace { line: 4 }));
+        assert!(matches!(error(b"v 0 0 0\nf 1/"), ObjError::InvalidFace { line: 2 }));
+        assert!(matches!(error(b"v 0 0 0\nv 1 x 0"), ObjError::ParseFloat { line: 2 }));
+
+        // Índices fuera de rango, hacia adelante y hacia atrás
+        assert!(matches!(error(b"v 0 0 0\nv 1 0 0\nv 1 1 0\nf 1 2 4"), ObjError::InvalidIndex { line: 4, index: 4, max: 3 }));
+        assert!(matches!(error(b"v 0 0 0\nv 1 0 0\nv 1 1 0\nf 1 2 -4"), ObjError::InvalidIndex { line: 4, index: -4, max: 3 }));
+        assert!(matches!(error(b"v 0 0 0\nv 1 0 0\nv 1 1 0\nf 0 1 2"), ObjError::InvalidIndex { line: 4, index: 0, max: 3 }));
+        assert!(matches!(error(b"v 0 0 0\nv 1 0 0\nv 1 1 0\nf 1/1 2/1 3/1"), ObjError::InvalidIndex { line: 4, index: 1, max: 0 }));
+
+        // Latin-1 en vez de UTF-8
+        assert!(matches!(error(b"# cubo\no caf\xe9\nv 0 0 0"), ObjError::InvalidUtf8 { line: 2, .. }));
+    }
+}
@@ -0,0 +1,339 @@
+use std::collections::VecDeque;
+use std::f32::consts::{PI, TAU};
+use nalgebra_glm::{Vec2, Vec3, Vec4};
+use crate::color::Color;
+use crate::framebuffer::Framebuffer;
+use crate::Uniforms;
+
+// Iteraciones máximas de Newton y error aceptado (en radianes) al resolver la ecuación de Kepler
+const KEPLER_ITERATIONS: usize = 16;
+const KEPLER_TOLERANCE: f32 = 1e-6;
+// Con excentricidades mayores la elipse es casi una recta y Newton deja de ser fiable
+const MAX_ECCENTRICITY: f32 = 0.95;
+
+/// Órbita kepleriana alrededor del origen. El plano de referencia es XZ y la línea de nodos
+/// el eje X; los ángulos están en radianes y `speed` es el movimiento medio en radianes por
+/// paso del reloj (el periodo es 2π / speed). Con excentricidad 0 es el círculo de siempre.
+#[derive(Debug, Clone, Copy)]
+pub struct Orbit {
+    pub semi_major: f32,
+    pub eccentricity: f32,
+    pub inclination: f32,
+    pub arg_periapsis: f32,
+    /// Anomalía media en el paso 0
+    pub phase: f32,
+    pub speed: f32,
+}
+
+impl Orbit {
+    /// Posición en el paso `time` del reloj
+    pub fn position_at(&self, time: f32) -> Vec3 {
+        let (e, eccentric) = self.eccentric_anomaly(time);
+        self.point_at(e, eccentric)
+    }
+
+    /// Velocidad en unidades por paso; es mayor cerca del periapsis
+    pub fn velocity_at(&self, time: f32) -> Vec3 {
+        let (e, eccentric) = self.eccentric_anomaly(time);
+        let minor = self.semi_major * (1.0 - e * e).sqrt();
+        // dE/dt sale de derivar la ecuación de Kepler
+        let rate = self.speed / (1.0 - e * eccentric.cos());
+        self.in_world(-self.semi_major * eccentric.sin() * rate, minor * eccentric.cos() * rate)
+    }
+
+    /// Distancia al foco en el punto más cercano
+    pub fn periapsis(&self) -> f32 {
+        self.semi_major * (1.0 - self.eccentricity.clamp(0.0, MAX_ECCENTRICITY))
+    }
+
+    /// La elipse completa en `samples` puntos, repartidos en anomalía excéntrica: respecto a
+    /// repartirlos en el tiempo quedan más juntos cerca del periapsis, donde la curva se
+    /// cierra más. El primer punto se repite al final para cerrarla.
+    pub fn path(&self, samples: usize) -> Vec<Vec3> {
+        let e = self.eccentricity.clamp(0.0, MAX_ECCENTRICITY);
+        let samples = samples.max(1);
+        (0..=samples).map(|i| self.point_at(e, (i % samples) as f32 / samples as f32 * TAU)).collect()
+    }
+
+    fn point_at(&self, e: f32, eccentric: f32) -> Vec3 {
+        let minor = self.semi_major * (1.0 - e * e).sqrt();
+        self.in_world(self.semi_major * (eccentric.cos() - e), minor * eccentric.sin())
+    }
+
+    // Resuelve M = E - e sin E por Newton. La anomalía media se lleva a [0, 2π) para no
+    // perder precisión cuando `time` es grande.
+    fn eccentric_anomaly(&self, time: f32) -> (f32, f32) {
+        let e = self.eccentricity.clamp(0.0, MAX_ECCENTRICITY);
+        let mean = self.phase + self.speed * time;
+        if e == 0.0 {
+            return (0.0, mean);
+        }
+        let mean = mean.rem_euclid(TAU);
+        let mut eccentric = if e < 0.8 { mean } else { PI };
+        for _ in 0..KEPLER_ITERATIONS {
+            let step = (eccentric - e * eccentric.sin() - mean) / (1.0 - e * eccentric.cos());
+            eccentric -= step;
+            if step.abs() < KEPLER_TOLERANCE {
+                break;
+            }
+        }
+        (e, eccentric)
+    }
+
+    // Del plano de la órbita (x hacia el periapsis) al mundo: se gira el periapsis dentro del
+    // plano y luego se inclina el plano alrededor de la línea de nodos
+    fn in_world(&self, x: f32, y: f32) -> Vec3 {
+        let (sin_w, cos_w) = self.arg_periapsis.sin_cos();
+        let (x, z) = (x * cos_w - y * sin_w, x * sin_w + y * cos_w);
+        let (sin_i, cos_i) = self.inclination.sin_cos();
+        Vec3::new(x, -z * sin_i, z * cos_i)
+    }
+}
+
+/// Color (con su opacidad) y ancho en pixeles de la línea de una órbita
+#[derive(Debug, Clone, Copy)]
+pub struct OrbitStyle {
+    pub color: Color,
+    pub width: f32,
+}
+
+impl Default for OrbitStyle {
+    fn default() -> Self {
+        OrbitStyle { color: Color::new(255, 0, 0).with_alpha(0.6), width: 1.5 }
+    }
+}
+
+/// Estela de las últimas posiciones de un cuerpo, de la más vieja a la más nueva. Guarda
+/// hasta `capacity` puntos y sólo agrega uno cuando el cuerpo se alejó `min_distance` del
+/// último, así con el reloj en pausa no se amontonan puntos repetidos.
+pub struct OrbitTrail {
+    points: VecDeque<Vec3>,
+    capacity: usize,
+    min_distance: f32,
+}
+
+impl OrbitTrail {
+    pub fn new(capacity: usize, min_distance: f32) -> Self {
+        OrbitTrail { points: VecDeque::with_capacity(capacity), capacity, min_distance }
+    }
+
+    pub fn push(&mut self, position: Vec3) {
+        if self.points.back().is_some_and(|last| (position - last).magnitude() < self.min_distance) {
+            return;
+        }
+        if self.points.len() >= self.capacity {
+            self.points.pop_front();
+        }
+        self.points.push_back(position);
+    }
+
+    pub fn points(&self) -> impl Iterator<Item = Vec3> + '_ {
+        self.points.iter().copied()
+    }
+}
+
+// Punto en pantalla con la profundidad que usa el zbuffer
+#[derive(Debug, Clone, Copy)]
+struct ScreenPoint {
+    position: Vec2,
+    depth: f32,
+}
+
+impl ScreenPoint {
+    fn lerp(&self, other: &ScreenPoint, t: f32) -> ScreenPoint {
+        ScreenPoint {
+            position: self.position + (other.position - self.position) * t,
+            depth: self.depth + (other.depth - self.depth) * t,
+        }
+    }
+}
+
+/// Dibuja una polilínea en el mundo uniendo cada punto con el siguiente. Cada vértice se
+/// pinta una sola vez: los segmentos no dibujan su extremo final salvo el último, y tampoco
+/// ése si la polilínea se cierra sobre su primer punto.
+pub fn draw_polyline(framebuffer: &mut Framebuffer, points: impl IntoIterator<Item = Vec3>, uniforms: &Uniforms, style: &OrbitStyle) {
+    let mut points = points.into_iter().peekable();
+    let Some(first) = points.next() else { return };
+    let mut start = first;
+    while let Some(end) = points.next() {
+        let include_end = points.peek().is_none() && end != first;
+        draw_segment(framebuffer, start, end, uniforms, style, include_end);
+        start = end;
+    }
+}
+
+/// Segmento entre dos puntos del mundo: se proyecta con las matrices de `uniforms`, se
+/// recorta contra el plano cercano y el borde de la pantalla, y se rasteriza con
+/// anti-aliasing y profundidad interpolada, así los planetas tapan lo que queda detrás.
+/// `include_end` indica si se pinta el pixel del extremo final (ver draw_polyline).
+pub fn draw_segment(framebuffer: &mut Framebuffer, start: Vec3, end: Vec3, uniforms: &Uniforms, style: &OrbitStyle, include_end: bool) {
+    let view_projection = uniforms.projection_matrix * uniforms.view_matrix;
+    let mut a = view_projection * Vec4::new(start.x, start.y, start.z, 1.0);
+    let mut b = view_projection * Vec4::new(end.x, end.y, end.z, 1.0);
+
+    // Plano cercano en clip space (z >= -w), igual que clip_near con los triángulos
+    let (near_a, near_b) = (a.z + a.w, b.z + b.w);
+    if near_a < 0.0 && near_b < 0.0 {
+        return;
+    }
+    if near_a < 0.0 {
+        a = a + (b - a) * (near_a / (near_a - near_b));
+    } else if near_b < 0.0 {
+        b = b + (a - b) * (near_b / (near_b - near_a));
+    }
+
+    let project = |clip: Vec4| {
+        let screen = uniforms.viewport_matrix * Vec4::new(clip.x / clip.w, clip.y / clip.w, clip.z / clip.w, 1.0);
+        ScreenPoint { position: Vec2::new(screen.x, screen.y), depth: screen.z }
+    };
+    let (from, to) = (project(a), project(b));
+
+    // Las líneas de un pixel van con Bresenham y la opacidad del estilo; las más anchas con
+    // cobertura para suavizar los bordes
+    if style.width <= 1.0 {
+        framebuffer.line(
+            Vec3::new(from.position.x, from.position.y, from.depth),
+            Vec3::new(to.position.x, to.position.y, to.depth),
+            style.color,
+            include_end,
+        );
+        return;
+    }
+    let Some((from, to)) = clip_to_screen(from, to, framebuffer, style.width) else { return };
+    wu_line(framebuffer, from, to, style, include_end);
+}
+
+// Liang-Barsky contra el rectángulo de la pantalla ampliado por el ancho de la línea. La
+// profundidad se interpola en pantalla, donde es lineal.
+fn clip_to_screen(from: ScreenPoint, to: ScreenPoint, framebuffer: &Framebuffer, width: f32) -> Option<(ScreenPoint, ScreenPoint)> {
+    let delta = to.position - from.position;
+    let margin = width + 1.0;
+    let (min, max) = (Vec2::new(-margin, -margin), Vec2::new(framebuffer.width as f32 + margin, framebuffer.height as f32 + margin));
+    let (mut t0, mut t1) = (0.0f32, 1.0f32);
+
+    for (p, q) in [
+        (-delta.x, from.position.x - min.x),
+        (delta.x, max.x - from.position.x),
+        (-delta.y, from.position.y - min.y),
+        (delta.y, max.y - from.position.y),
+    ] {
+        if p == 0.0 {
+            if q < 0.0 {
+                return None;
+            }
+        } else if p < 0.0 {
+            t0 = t0.max(q / p);
+        } else {
+            t1 = t1.min(q / p);
+        }
+    }
+    (t0 <= t1).then(|| (from.lerp(&to, t0), from.lerp(&to, t1)))
+}
+
+// Xiaolin Wu con ancho: en cada paso del eje mayor, cada pixel del eje menor recibe la parte
+// de la franja [centro - ancho/2, centro + ancho/2] que cae dentro de él, y se mezcla con
+// esa cobertura
+fn wu_line(framebuffer: &mut Framebuffer, from: ScreenPoint, to: ScreenPoint, style: &OrbitStyle, include_end: bool) {
+    let delta = to.position - from.position;
+    let steep = delta.y.abs() > delta.x.abs();
+    let steps = delta.x.abs().max(delta.y.abs()).ceil().max(1.0) as usize;
+    // El ancho se mide perpendicular a la línea; sobre el eje menor se estira con la pendiente
+    let slope = if steep { delta.x / delta.y } else if delta.x != 0.0 { delta.y / delta.x } else { 0.0 };
+    let half_width = style.width.max(0.0) / 2.0 * (1.0 + slope * slope).sqrt();
+    let opacity = style.color.alpha();
+
+    let last_step = if include_end { steps } else { steps - 1 };
+    for step in 0..=last_step {
+        let point = from.lerp(&to, step as f32 / steps as f32);
+        let (major, minor) = if steep { (point.position.y, point.position.x) } else { (point.position.x, point.position.y) };
+        // Los centros de los pixeles están en k + 0.5
+        let (low, high) = (minor - half_width, minor + half_width);
+
+        let major = major.floor();
+        for pixel in (low.floor() as i64)..=(high.floor() as i64) {
+            let coverage = (high.min(pixel as f32 + 1.0) - low.max(pixel as f32)).clamp(0.0, 1.0);
+            let (x, y) = if steep { (pixel as f32, major) } else { (major, pixel as f32) };
+            if coverage > 0.0 && x >= 0.0 && y >= 0.0 {
+                framebuffer.blend_pixel(x as usize, y as usize, point.depth, style.color, opacity * coverage);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(x: f32, y: f32) -> ScreenPoint {
+        ScreenPoint { position: Vec2::new(x, y), depth: 0.5 }
+    }
+
+    fn pixel(framebuffer: &Framebuffer, x: usize, y: usize) -> u32 {
+        framebuffer.buffer[y * framebuffer.width + x]
+    }
+
+    // Dos tramos que comparten el vértice (8, 4): ese pixel debe quedar igual que uno del medio
+    #[test]
+    fn shared_vertices_blend_once() {
+        let style = OrbitStyle { color: Color::new(255, 255, 255).with_alpha(0.5), width: 2.0 };
+        let mut framebuffer = Framebuffer::new(16, 8);
+        wu_line(&mut framebuffer, point(2.0, 4.0), point(8.0, 4.0), &style, false);
+        wu_line(&mut framebuffer, point(8.0, 4.0), point(14.0, 4.0), &style, true);
+        assert_eq!(pixel(&framebuffer, 8, 4), pixel(&framebuffer, 5, 4));
+        assert_eq!(pixel(&framebuffer, 14, 4), pixel(&framebuffer, 5, 4));
+
+        let mut framebuffer = Framebuffer::new(16, 8);
+        framebuffer.line(Vec3::new(2.0, 4.0, 0.5), Vec3::new(8.0, 4.0, 0.5), style.color, false);
+        framebuffer.line(Vec3::new(8.0, 4.0, 0.5), Vec3::new(14.0, 4.0, 0.5), style.color, true);
+        assert_eq!(pixel(&framebuffer, 8, 4), pixel(&framebuffer, 5, 4));
+    }
+
+    #[test]
+    fn thin_lines_keep_their_alpha() {
+        let white = Color::new(255, 255, 255);
+        let mut framebuffer = Framebuffer::new(16, 8);
+        framebuffer.line(Vec3::new(2.0, 4.0, 0.5), Vec3::new(14.0, 4.0, 0.5), white.with_alpha(0.5), true);
+        let value = pixel(&framebuffer, 8, 4);
+        assert_eq!(value, white.with_alpha(0.5).over(&Color::BLACK).to_hex());
+        assert_ne!(value, white.to_hex());
+    }
+
+    fn orbit(eccentricity: f32) -> Orbit {
+        Orbit { semi_major: 20.0, eccentricity, inclination: 0.3, arg_periapsis: 1.1, phase: 0.4, speed: 0.013 }
+    }
+
+    #[test]
+    fn every_eccentricity_keeps_the_period() {
+        for e in [0.0, 0.2, 0.6, 0.9] {
+            let orbit = orbit(e);
+            let period = TAU / orbit.speed;
+            for time in [0.0, 17.0, 250.0, 10_000.0] {
+                let (start, end) = (orbit.position_at(time), orbit.position_at(time + period));
+                assert!((start - end).magnitude() < 1e-2, "e = {e}, t = {time}: {start:?} != {end:?}");
+                let (start, end) = (orbit.velocity_at(time), orbit.velocity_at(time + period));
+                assert!((start - end).magnitude() < 1e-4, "e = {e}, t = {time}: {start:?} != {end:?}");
+            }
+            // En medio periodo pasa por el otro extremo de la elipse, no vuelve al inicio
+            let (start, half) = (orbit.position_at(0.0), orbit.position_at(period / 2.0));
+            assert!((start - half).magnitude() > orbit.semi_major);
+        }
+    }
+
+    #[test]
+    fn zero_eccentricity_is_the_old_circle() {
+        // La órbita de antes: un círculo en XZ a velocidad angular constante
+        let circle = |time: f32, radius: f32, speed: f32| {
+            let angle = time * speed;
+            Vec3::new(radius * angle.cos(), 0.0, radius * angle.sin())
+        };
+        let orbit = Orbit { semi_major: 20.0, eccentricity: 0.0, inclination: 0.0, arg_periapsis: 0.0, phase: 0.0, speed: 0.013 };
+        for step in 0..200 {
+            let time = step as f32 * 7.0;
+            assert!((orbit.position_at(time) - circle(time, 20.0, 0.013)).magnitude() < 1e-3, "t = {time}");
+            // La velocidad es tangente y de módulo radio * velocidad angular
+            let velocity = orbit.velocity_at(time);
+            assert!((velocity.magnitude() - 20.0 * 0.013).abs() < 1e-5);
+            assert!(velocity.dot(&orbit.position_at(time)).abs() < 1e-3);
+        }
+    }
+}
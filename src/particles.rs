@@ -0,0 +1,55 @@
+use nalgebra_glm::Vec3;
+
+/// Partícula en el mundo; `age` y `lifetime` están en segundos y `velocity` en unidades por
+/// segundo
+#[derive(Debug, Clone, Copy)]
+pub struct Particle {
+    pub position: Vec3,
+    pub velocity: Vec3,
+    pub age: f32,
+    pub lifetime: f32,
+    pub brightness: f32,
+}
+
+impl Particle {
+    /// Fracción de la vida que ya pasó, en [0, 1]
+    pub fn life(&self) -> f32 {
+        (self.age / self.lifetime).min(1.0)
+    }
+}
+
+/// Conjunto de partículas con un máximo: lo que se lanza con el conjunto lleno se descarta
+pub struct Particles {
+    particles: Vec<Particle>,
+    capacity: usize,
+}
+
+impl Particles {
+    pub fn new(capacity: usize) -> Self {
+        Particles { particles: Vec::with_capacity(capacity), capacity }
+    }
+
+    /// False si no había lugar
+    pub fn spawn(&mut self, particle: Particle) -> bool {
+        if self.particles.len() >= self.capacity {
+            return false;
+        }
+        self.particles.push(particle);
+        true
+    }
+
+    /// Avanza `delta` segundos: cada partícula acelera según `acceleration`, se mueve y
+    /// envejece, y las que cumplieron su vida se quitan
+    pub fn update(&mut self, delta: f32, acceleration: impl Fn(&Particle) -> Vec3) {
+        for particle in self.particles.iter_mut() {
+            particle.velocity += acceleration(particle) * delta;
+            particle.position += particle.velocity * delta;
+            particle.age += delta;
+        }
+        self.particles.retain(|particle| particle.age < particle.lifetime);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Particle> {
+        self.particles.iter()
+    }
+}
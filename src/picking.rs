@@ -0,0 +1,141 @@
+use nalgebra_glm::{Mat4, Vec2, Vec3, Vec4};
+
+/// Rayo en el mundo con dirección normalizada
+#[derive(Debug, Clone, Copy)]
+pub struct Ray {
+    pub origin: Vec3,
+    pub direction: Vec3,
+}
+
+impl Ray {
+    /// Rayo desde la cámara a través del pixel (x, y) de una pantalla de `width` x `height`.
+    /// Se desproyectan los puntos del plano cercano y del lejano con la inversa de
+    /// proyección * vista, así sirve igual para el centro (la mira) que para el mouse.
+    pub fn from_screen(x: f32, y: f32, width: f32, height: f32, view_matrix: &Mat4, projection_matrix: &Mat4) -> Option<Ray> {
+        let inverse = (projection_matrix * view_matrix).try_inverse()?;
+        // El viewport invierte Y: la fila 0 es la parte de arriba
+        let (ndc_x, ndc_y) = (2.0 * x / width - 1.0, 1.0 - 2.0 * y / height);
+        let unproject = |ndc_z: f32| {
+            let point = inverse * Vec4::new(ndc_x, ndc_y, ndc_z, 1.0);
+            point.xyz() / point.w
+        };
+        let (near, far) = (unproject(-1.0), unproject(1.0));
+        let direction = (far - near).try_normalize(f32::EPSILON)?;
+        Some(Ray { origin: near, direction })
+    }
+
+    /// Distancia a lo largo del rayo hasta la primera intersección con la esfera que quede
+    /// por delante; si el origen está dentro, la salida
+    pub fn intersect_sphere(&self, center: Vec3, radius: f32) -> Option<f32> {
+        let offset = self.origin - center;
+        let b = offset.dot(&self.direction);
+        let c = offset.magnitude_squared() - radius * radius;
+        let discriminant = b * b - c;
+        if discriminant < 0.0 {
+            return None;
+        }
+        let root = discriminant.sqrt();
+        [-b - root, -b + root].into_iter().find(|&t| t >= 0.0)
+    }
+}
+
+/// Índice y distancia de la esfera más cercana que toca el rayo, de una lista de
+/// (índice, centro, radio)
+pub fn pick(ray: &Ray, spheres: impl IntoIterator<Item = (usize, Vec3, f32)>) -> Option<(usize, f32)> {
+    spheres
+        .into_iter()
+        .filter_map(|(index, center, radius)| ray.intersect_sphere(center, radius).map(|t| (index, t)))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+}
+
+/// Rectángulo de pantalla (en pixeles, `min` arriba a la izquierda) que cubre una esfera
+#[derive(Debug, Clone, Copy)]
+pub struct ScreenRect {
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+impl ScreenRect {
+    pub fn center(&self) -> Vec2 {
+        (self.min + self.max) / 2.0
+    }
+
+    pub fn size(&self) -> Vec2 {
+        self.max - self.min
+    }
+
+    /// Si toca la pantalla de `width` x `height`
+    pub fn is_on_screen(&self, width: f32, height: f32) -> bool {
+        self.max.x >= 0.0 && self.max.y >= 0.0 && self.min.x < width && self.min.y < height
+    }
+}
+
+/// Rectángulo que ocupa en pantalla la esfera de `center` y `radius`, o None si su centro
+/// queda detrás de la cámara o la cámara está dentro. Es el exacto: se usan las rectas desde
+/// el ojo tangentes a la esfera en los planos X-Z e Y-Z de la vista, así de cerca la silueta
+/// no se sale del rectángulo.
+pub fn project_sphere(center: Vec3, radius: f32, view_matrix: &Mat4, projection_matrix: &Mat4, viewport_matrix: &Mat4) -> Option<ScreenRect> {
+    let view_center = view_matrix * Vec4::new(center.x, center.y, center.z, 1.0);
+    // La cámara mira hacia -Z en la vista
+    let depth = -view_center.z;
+    if depth <= 0.0 {
+        return None;
+    }
+    let (min_x, max_x) = tangent_slopes(view_center.x, depth, radius)?;
+    let (min_y, max_y) = tangent_slopes(view_center.y, depth, radius)?;
+    // Un punto a profundidad 1 sobre cada tangente da el borde en pantalla
+    let to_screen = viewport_matrix * projection_matrix;
+    let project = |x: f32, y: f32| {
+        let clip = to_screen * Vec4::new(x, y, -1.0, 1.0);
+        Vec2::new(clip.x / clip.w, clip.y / clip.w)
+    };
+    let (corner_a, corner_b) = (project(min_x, min_y), project(max_x, max_y));
+    Some(ScreenRect { min: corner_a.inf(&corner_b), max: corner_a.sup(&corner_b) })
+}
+
+// Pendientes (desplazamiento sobre el eje por unidad de profundidad) de las dos tangentes
+// desde el ojo a un círculo de centro (`offset`, `depth`). Si una tangente apunta hacia
+// atrás la esfera se extiende sin límite hacia ese lado.
+fn tangent_slopes(offset: f32, depth: f32, radius: f32) -> Option<(f32, f32)> {
+    let distance = (offset * offset + depth * depth).sqrt();
+    if distance <= radius {
+        return None;
+    }
+    let (sin, cos) = (radius / distance, (1.0 - (radius / distance).powi(2)).sqrt());
+    let (along, forward) = (offset / distance, depth / distance);
+    let slope = |along: f32, forward: f32| {
+        if forward > f32::EPSILON { along / forward } else { along.signum() * f32::MAX.sqrt() }
+    };
+    let low = slope(along * cos - forward * sin, along * sin + forward * cos);
+    let high = slope(along * cos + forward * sin, forward * cos - along * sin);
+    Some((low.min(high), low.max(high)))
+}
+
+/// Silueta en pantalla de la esfera: por cada fila de pixeles que toca, la primera y la
+/// última columna cuyo centro ve la esfera, como (fila, primera, última). La silueta es
+/// convexa, así que en cada fila es un único tramo. Se recorre sólo lo que cubre
+/// project_sphere dentro de `area`; vacía en los mismos casos que ésta.
+pub fn silhouette_spans(center: Vec3, radius: f32, area: ScreenRect, view_matrix: &Mat4, projection_matrix: &Mat4, viewport_matrix: &Mat4) -> Vec<(i64, i64, i64)> {
+    let Some(rect) = project_sphere(center, radius, view_matrix, projection_matrix, viewport_matrix) else { return Vec::new() };
+    let Some(inverse) = (viewport_matrix * projection_matrix).try_inverse() else { return Vec::new() };
+    let view_center = (view_matrix * Vec4::new(center.x, center.y, center.z, 1.0)).xyz();
+    // En la vista el ojo está en el origen: el pixel ve la esfera si la recta hacia su punto
+    // del plano lejano pasa a menos de `radius` del centro, por delante
+    let sees = |x: i64, y: i64| {
+        let point = inverse * Vec4::new(x as f32 + 0.5, y as f32 + 0.5, 1.0, 1.0);
+        let direction = point.xyz() / point.w;
+        let along = view_center.dot(&direction);
+        let length_squared = direction.magnitude_squared();
+        along > 0.0 && view_center.magnitude_squared() * length_squared - along * along <= radius * radius * length_squared
+    };
+    let (min, max) = (rect.min.sup(&area.min), rect.max.inf(&area.max));
+    let (x0, x1) = (min.x.floor() as i64, max.x.ceil() as i64);
+    let (y0, y1) = (min.y.floor() as i64, max.y.ceil() as i64);
+    (y0..y1)
+        .filter_map(|y| {
+            let first = (x0..x1).find(|&x| sees(x, y))?;
+            let last = (first..x1).rev().find(|&x| sees(x, y))?;
+            Some((y, first, last))
+        })
+        .collect()
+}
@@ -0,0 +1,250 @@
+use crate::color::{self, Color};
+use crate::framebuffer::Framebuffer;
+use crate::shaders::smoothstep;
+
+// Bloom: lo que supera el umbral de luminancia (sol, grietas de lava, motor) se copia a un
+// buffer reducido, se desenfoca con una gaussiana separable y se suma de vuelta a la imagen.
+// Todo en espacio lineal, que es como se suma la luz.
+
+// Luminancia (lineal) desde la que un pixel empieza a brillar, y ancho de la rodilla suave
+// para que el efecto no aparezca de golpe
+const BLOOM_THRESHOLD: f32 = 0.55;
+const BLOOM_KNEE: f32 = 0.2;
+// Radio del desenfoque en pixeles del buffer reducido
+const BLOOM_RADIUS: usize = 6;
+// Cuánto del desenfoque se suma a la imagen
+const BLOOM_INTENSITY: f32 = 1.5;
+// Reducción respecto a la ventana (con otra escala de render se ajusta para que el halo
+// mida lo mismo en pantalla)
+const BLOOM_DOWNSCALE: usize = 4;
+// Brillo por debajo del cual una celda no cambia la imagen (menos de un nivel de 8 bits)
+const GLOW_EPSILON: f32 = 1.0 / 1024.0;
+
+pub struct Bloom {
+    width: usize,
+    height: usize,
+    bright: Vec<[f32; 3]>,
+    scratch: Vec<[f32; 3]>,
+    kernel: Vec<f32>,
+}
+
+impl Bloom {
+    pub fn new() -> Self {
+        // Gaussiana con el radio en 2 sigmas, normalizada
+        let sigma = BLOOM_RADIUS as f32 / 2.0;
+        let weights: Vec<f32> = (0..=2 * BLOOM_RADIUS)
+            .map(|i| {
+                let x = i as f32 - BLOOM_RADIUS as f32;
+                (-x * x / (2.0 * sigma * sigma)).exp()
+            })
+            .collect();
+        let total: f32 = weights.iter().sum();
+        Bloom {
+            width: 0,
+            height: 0,
+            bright: Vec::new(),
+            scratch: Vec::new(),
+            kernel: weights.iter().map(|weight| weight / total).collect(),
+        }
+    }
+
+    /// Aplica el bloom sobre el framebuffer terminado; `render_scale` (framebuffer / ventana)
+    /// mantiene el tamaño del halo igual en pantalla con cualquier resolución interna
+    pub fn apply(&mut self, framebuffer: &mut Framebuffer, render_scale: f32) {
+        let factor = ((BLOOM_DOWNSCALE as f32 * render_scale).round() as usize).max(1);
+        self.width = framebuffer.width.div_ceil(factor);
+        self.height = framebuffer.height.div_ceil(factor);
+        self.bright.resize(self.width * self.height, [0.0; 3]);
+        self.scratch.resize(self.width * self.height, [0.0; 3]);
+
+        self.extract(framebuffer, factor);
+        self.blur();
+        self.composite(framebuffer, factor);
+    }
+
+    // Promedia cada bloque y se queda con lo que pasa el umbral. Promediar antes de cortar
+    // hace que las estrellas de un pixel no alcancen a brillar.
+    fn extract(&mut self, framebuffer: &Framebuffer, factor: usize) {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let (x0, y0) = (x * factor, y * factor);
+                let (x1, y1) = ((x0 + factor).min(framebuffer.width), (y0 + factor).min(framebuffer.height));
+                let mut sum = [0.0; 3];
+                for sy in y0..y1 {
+                    for &pixel in &framebuffer.buffer[sy * framebuffer.width + x0..sy * framebuffer.width + x1] {
+                        let [r, g, b] = Color::from_hex(pixel).to_linear();
+                        sum = [sum[0] + r, sum[1] + g, sum[2] + b];
+                    }
+                }
+                let samples = ((x1 - x0) * (y1 - y0)) as f32;
+                let [r, g, b] = sum.map(|channel| channel / samples);
+
+                let luminance = color::luminance([r, g, b]);
+                let excess = luminance - BLOOM_THRESHOLD;
+                // Rodilla cuadrática alrededor del umbral, lineal por encima
+                let soft = (excess + BLOOM_KNEE).clamp(0.0, 2.0 * BLOOM_KNEE);
+                let soft = soft * soft / (4.0 * BLOOM_KNEE);
+                let keep = if luminance > 0.0 { excess.max(soft) / luminance } else { 0.0 };
+                self.bright[y * self.width + x] = [r * keep, g * keep, b * keep];
+            }
+        }
+    }
+
+    // Gaussiana separable: horizontal hacia scratch y vertical de vuelta a bright
+    fn blur(&mut self) {
+        let (width, height) = (self.width, self.height);
+        let radius = BLOOM_RADIUS as isize;
+        for y in 0..height {
+            for x in 0..width {
+                let mut sum = [0.0; 3];
+                for (i, weight) in self.kernel.iter().enumerate() {
+                    let sx = (x as isize + i as isize - radius).clamp(0, width as isize - 1) as usize;
+                    let [r, g, b] = self.bright[y * width + sx];
+                    sum = [sum[0] + r * weight, sum[1] + g * weight, sum[2] + b * weight];
+                }
+                self.scratch[y * width + x] = sum;
+            }
+        }
+        for y in 0..height {
+            for x in 0..width {
+                let mut sum = [0.0; 3];
+                for (i, weight) in self.kernel.iter().enumerate() {
+                    let sy = (y as isize + i as isize - radius).clamp(0, height as isize - 1) as usize;
+                    let [r, g, b] = self.scratch[sy * width + x];
+                    sum = [sum[0] + r * weight, sum[1] + g * weight, sum[2] + b * weight];
+                }
+                self.bright[y * width + x] = sum;
+            }
+        }
+    }
+
+    // Suma el buffer desenfocado con interpolación bilineal para que no se vean los bloques.
+    // Los pixeles cuyas cuatro celdas vecinas no tienen brillo se saltan, que son casi todos.
+    fn composite(&self, framebuffer: &mut Framebuffer, factor: usize) {
+        let scale = 1.0 / factor as f32;
+        let cell = |position: usize, cells: usize| {
+            let f = ((position as f32 + 0.5) * scale - 0.5).clamp(0.0, (cells - 1) as f32);
+            (f as usize, (f as usize + 1).min(cells - 1), f.fract())
+        };
+        let columns: Vec<(usize, usize, f32)> = (0..framebuffer.width).map(|x| cell(x, self.width)).collect();
+        let lit: Vec<bool> = self.bright.iter().map(|glow| glow.iter().any(|&channel| channel > GLOW_EPSILON)).collect();
+        let mut row_lit = vec![false; self.width];
+
+        for y in 0..framebuffer.height {
+            let (y0, y1, ty) = cell(y, self.height);
+            for (x, flag) in row_lit.iter_mut().enumerate() {
+                *flag = lit[y0 * self.width + x] || lit[y1 * self.width + x];
+            }
+
+            for (x, &(x0, x1, tx)) in columns.iter().enumerate() {
+                if !row_lit[x0] && !row_lit[x1] {
+                    continue;
+                }
+                let sample = |sx: usize, sy: usize| self.bright[sy * self.width + sx];
+                let (a, b, c, d) = (sample(x0, y0), sample(x1, y0), sample(x0, y1), sample(x1, y1));
+                let glow: [f32; 3] = std::array::from_fn(|i| {
+                    let top = a[i] + (b[i] - a[i]) * tx;
+                    let bottom = c[i] + (d[i] - c[i]) * tx;
+                    (top + (bottom - top) * ty) * BLOOM_INTENSITY
+                });
+
+                let index = y * framebuffer.width + x;
+                let [r, g, b] = Color::from_hex(framebuffer.buffer[index]).to_linear();
+                framebuffer.buffer[index] = Color::from_linear([r + glow[0], g + glow[1], b + glow[2]]).to_hex();
+            }
+        }
+    }
+}
+
+// Viñeta: oscurecimiento radial hacia las esquinas. `strength` es cuánto se oscurecen las
+// esquinas y empieza a notarse desde VIGNETTE_INNER (fracción de la semidiagonal).
+const VIGNETTE_STRENGTH: f32 = 0.3;
+const VIGNETTE_INNER: f32 = 0.45;
+// Exposición: PageUp / PageDown la multiplican o dividen por este paso, dentro de los límites
+const EXPOSURE_STEP: f32 = 1.189_207_1; // 2^(1/4), un cuarto de paso de diafragma
+const EXPOSURE_MIN: f32 = 1.0 / 16.0;
+const EXPOSURE_MAX: f32 = 16.0;
+// Niveles en que se cuantiza la viñeta
+const VIGNETTE_LEVELS: usize = 256;
+
+/// Exposición global y viñeta sobre la imagen terminada. Ambas son un factor por pixel en
+/// espacio lineal, así que se combinan en una tabla nivel de viñeta x canal sRGB -> canal
+/// sRGB; cada pixel cuesta tres consultas.
+pub struct ColorGrade {
+    pub exposure: f32,
+    pub vignette_strength: f32,
+    width: usize,
+    height: usize,
+    // Nivel de viñeta de cada pixel (0 = centro, sin oscurecer)
+    levels: Vec<u8>,
+    // Canal de salida por nivel de viñeta y canal de entrada
+    table: Vec<[u8; 256]>,
+    // Valores con los que se armaron `levels` y `table`
+    built_for: Option<(f32, f32)>,
+}
+
+impl ColorGrade {
+    pub fn new() -> Self {
+        ColorGrade {
+            exposure: 1.0,
+            vignette_strength: VIGNETTE_STRENGTH,
+            width: 0,
+            height: 0,
+            levels: Vec::new(),
+            table: Vec::new(),
+            built_for: None,
+        }
+    }
+
+    /// Sube (`steps` > 0) o baja la exposición en cuartos de paso
+    pub fn adjust_exposure(&mut self, steps: i32) {
+        self.exposure = (self.exposure * EXPOSURE_STEP.powi(steps)).clamp(EXPOSURE_MIN, EXPOSURE_MAX);
+    }
+
+    pub fn apply(&mut self, framebuffer: &mut Framebuffer) {
+        if (self.width, self.height) != (framebuffer.width, framebuffer.height) {
+            self.width = framebuffer.width;
+            self.height = framebuffer.height;
+            self.levels = vignette_levels(self.width, self.height);
+        }
+        if self.built_for != Some((self.exposure, self.vignette_strength)) {
+            self.table = self.build_table();
+            self.built_for = Some((self.exposure, self.vignette_strength));
+        }
+
+        for (pixel, &level) in framebuffer.buffer.iter_mut().zip(&self.levels) {
+            let row = &self.table[level as usize];
+            let (r, g, b) = ((*pixel >> 16) as u8, (*pixel >> 8) as u8, *pixel as u8);
+            *pixel = ((row[r as usize] as u32) << 16) | ((row[g as usize] as u32) << 8) | row[b as usize] as u32;
+        }
+    }
+
+    // Para cada nivel de viñeta, resultado de decodificar, escalar y volver a codificar cada
+    // canal (el encode recorta, así que una exposición alta satura en vez de dar la vuelta)
+    fn build_table(&self) -> Vec<[u8; 256]> {
+        (0..VIGNETTE_LEVELS)
+            .map(|level| {
+                let darkening = self.vignette_strength * level as f32 / (VIGNETTE_LEVELS - 1) as f32;
+                let factor = self.exposure * (1.0 - darkening);
+                std::array::from_fn(|channel| {
+                    let [linear, _, _] = Color::new(channel as u8, 0, 0).to_linear();
+                    (Color::from_linear([linear * factor, 0.0, 0.0]).to_hex() >> 16) as u8
+                })
+            })
+            .collect()
+    }
+}
+
+// Nivel de oscurecimiento (0..255) de cada pixel según su distancia al centro
+fn vignette_levels(width: usize, height: usize) -> Vec<u8> {
+    let center = (width as f32 / 2.0, height as f32 / 2.0);
+    let half_diagonal = (center.0 * center.0 + center.1 * center.1).sqrt().max(f32::EPSILON);
+    (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            let (dx, dy) = (x as f32 + 0.5 - center.0, y as f32 + 0.5 - center.1);
+            let amount = smoothstep(VIGNETTE_INNER, 1.0, (dx * dx + dy * dy).sqrt() / half_diagonal);
+            (amount * (VIGNETTE_LEVELS - 1) as f32).round() as u8
+        })
+        .collect()
+}
@@ -0,0 +1,321 @@
+use std::f32::consts::TAU;
+use std::fmt;
+use std::io;
+use nalgebra_glm::Vec3;
+use serde::{Deserialize, Deserializer};
+use crate::mesh::SPHERE_RADIUS;
+use crate::color::Color;
+use crate::orbit::{Orbit, OrbitStyle};
+use crate::shaders::{ShaderType, RING_OUTER_RADIUS};
+use crate::toml_reader::{self, TomlError};
+
+// Escena por defecto: se usa si no se pasa otra por la línea de comandos ni hay un
+// scene.toml junto al ejecutable
+pub const SCENE_PATH: &str = "scene.toml";
+pub const DEFAULT_SCENE: &str = include_str!("../scene.toml");
+
+// Velocidad de las lunas que no dicen la suya
+const MOON_ORBIT_SPEED: f32 = 0.01;
+
+// Nombres de los shaders en el archivo de escena
+const SHADER_NAMES: [(&str, ShaderType); 12] = [
+    ("sun", ShaderType::Sun),
+    ("earth", ShaderType::Earth),
+    ("gas_planet", ShaderType::GasPlanet),
+    ("ring_planet", ShaderType::RingPlanet),
+    ("rocky_planet", ShaderType::RockyPlanet),
+    ("icy_planet", ShaderType::IcyPlanet),
+    ("volcanic_planet", ShaderType::VolcanicPlanet),
+    ("ocean_planet", ShaderType::OceanPlanet),
+    ("desert_planet", ShaderType::DesertPlanet),
+    ("moon", ShaderType::Moon),
+    ("asteroid", ShaderType::Asteroid),
+    ("comet", ShaderType::Comet),
+];
+
+#[derive(Debug)]
+pub enum SceneError {
+    Io(io::Error),
+    Toml(TomlError),
+    /// El TOML es válido pero no describe una escena (faltan campos, shader desconocido...)
+    Invalid(String),
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SceneError::Io(err) => write!(f, "no se pudo leer el archivo: {}", err),
+            SceneError::Toml(err) => write!(f, "{}", err),
+            SceneError::Invalid(reason) => write!(f, "escena inválida: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for SceneError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SceneError::Io(err) => Some(err),
+            SceneError::Toml(err) => Some(err),
+            SceneError::Invalid(_) => None,
+        }
+    }
+}
+
+/// Sistema solar: los cuerpos en el orden en que se numeran (1-9 y 0 vuelan hacia ellos)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SceneConfig {
+    pub bodies: Vec<BodyConfig>,
+    #[serde(default)]
+    pub belt: Option<BeltConfig>,
+}
+
+/// Un cuerpo que orbita el origen. Las distancias y radios están en unidades del mundo y los
+/// ángulos de la órbita en grados; sin ellos la órbita es un círculo en el plano XZ.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BodyConfig {
+    pub name: String,
+    #[serde(deserialize_with = "shader_type")]
+    pub shader: ShaderType,
+    pub radius: f32,
+    /// Semieje mayor (el radio si la órbita es circular)
+    pub orbit_radius: f32,
+    /// Movimiento medio en radianes por paso del reloj
+    pub orbit_speed: f32,
+    #[serde(default)]
+    pub eccentricity: f32,
+    #[serde(default)]
+    pub inclination: f32,
+    #[serde(default)]
+    pub arg_periapsis: f32,
+    /// Posición inicial en la órbita (anomalía media)
+    #[serde(default)]
+    pub phase: f32,
+    /// Giro sobre su eje en radianes por paso
+    #[serde(default)]
+    pub rotation_speed: f32,
+    /// Inclinación del eje de giro respecto a la normal de la eclíptica, en grados
+    #[serde(default)]
+    pub axial_tilt: f32,
+    /// Gira al mismo ritmo que recorre la órbita, mostrando siempre la misma cara a lo que
+    /// orbita (ignora rotation_speed)
+    #[serde(default)]
+    pub tidally_locked: bool,
+    /// Color de la línea de la órbita (con #RRGGBBAA también su opacidad); si no se da, el
+    /// rojo translúcido de OrbitStyle
+    #[serde(default)]
+    pub orbit_color: Option<Color>,
+    #[serde(default)]
+    pub moons: Vec<MoonConfig>,
+    #[serde(default)]
+    pub rings: Option<RingConfig>,
+    /// Si es un cometa, su cola
+    #[serde(default)]
+    pub tail: Option<TailConfig>,
+}
+
+/// Luna alrededor de su cuerpo, en una órbita circular; `orbit_radius` se mide desde el
+/// centro de éste y los ángulos están en grados
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MoonConfig {
+    #[serde(default = "moon_shader", deserialize_with = "shader_type")]
+    pub shader: ShaderType,
+    pub radius: f32,
+    pub orbit_radius: f32,
+    /// Radianes por paso
+    #[serde(default = "moon_orbit_speed")]
+    pub orbit_speed: f32,
+    /// Respecto a la eclíptica, alrededor del eje X
+    #[serde(default)]
+    pub inclination: f32,
+    #[serde(default)]
+    pub phase: f32,
+    #[serde(default)]
+    pub rotation_speed: f32,
+    #[serde(default)]
+    pub tidally_locked: bool,
+}
+
+/// Anillo en el plano del cuerpo. El radio interior sale de la proporción de la malla.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RingConfig {
+    pub outer_radius: f32,
+}
+
+/// Cinturón de asteroides: `count` rocas en órbitas circulares entre dos radios, cerca de la
+/// eclíptica. La misma semilla da siempre el mismo cinturón.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BeltConfig {
+    pub inner_radius: f32,
+    pub outer_radius: f32,
+    #[serde(default = "belt_count")]
+    pub count: usize,
+    #[serde(default)]
+    pub seed: u64,
+    /// Radianes por paso en el borde interior; hacia afuera baja como en una órbita de Kepler
+    pub orbit_speed: f32,
+    /// Inclinación máxima de las órbitas, en grados
+    #[serde(default = "belt_inclination")]
+    pub max_inclination: f32,
+    /// Radios de la roca más chica y la más grande
+    #[serde(default = "belt_min_size")]
+    pub min_size: f32,
+    #[serde(default = "belt_max_size")]
+    pub max_size: f32,
+}
+
+/// Cola de un cometa: `particles` es el máximo de partículas a la vez, `speed` el empuje
+/// hacia afuera del sol en el perihelio (unidades por segundo al cuadrado) y `lifetime` los
+/// segundos que dura cada una
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TailConfig {
+    #[serde(default = "tail_particles")]
+    pub particles: usize,
+    #[serde(default = "tail_speed")]
+    pub speed: f32,
+    #[serde(default = "tail_lifetime")]
+    pub lifetime: f32,
+}
+
+impl SceneConfig {
+    pub fn load(path: &str) -> Result<Self, SceneError> {
+        let text = std::fs::read_to_string(path).map_err(SceneError::Io)?;
+        SceneConfig::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> Result<Self, SceneError> {
+        let value = toml_reader::parse(text).map_err(SceneError::Toml)?;
+        let scene: SceneConfig = serde_json::from_value(value).map_err(|err| SceneError::Invalid(err.to_string()))?;
+        if scene.bodies.is_empty() {
+            return Err(SceneError::Invalid("no hay ningún cuerpo ([[bodies]])".to_string()));
+        }
+        if let Some(body) = scene.bodies.iter().find(|body| body.tail.as_ref().is_some_and(|tail| tail.lifetime <= 0.0)) {
+            return Err(SceneError::Invalid(format!("la cola de {} necesita lifetime > 0", body.name)));
+        }
+        if let Some(belt) = &scene.belt {
+            if !(0.0 < belt.inner_radius && belt.inner_radius <= belt.outer_radius) {
+                return Err(SceneError::Invalid("el cinturón necesita 0 < inner_radius <= outer_radius".to_string()));
+            }
+            if !(0.0 < belt.min_size && belt.min_size <= belt.max_size) {
+                return Err(SceneError::Invalid("el cinturón necesita 0 < min_size <= max_size".to_string()));
+            }
+        }
+        Ok(scene)
+    }
+}
+
+// Las mallas de esfera y del anillo tienen un tamaño fijo; las escalas que piden los radios
+impl BodyConfig {
+    pub fn scale(&self) -> f32 {
+        self.radius / SPHERE_RADIUS
+    }
+
+    pub fn orbit(&self) -> Orbit {
+        Orbit {
+            semi_major: self.orbit_radius,
+            eccentricity: self.eccentricity,
+            inclination: self.inclination.to_radians(),
+            arg_periapsis: self.arg_periapsis.to_radians(),
+            phase: self.phase.to_radians(),
+            speed: self.orbit_speed,
+        }
+    }
+
+    pub fn orbit_style(&self) -> OrbitStyle {
+        match self.orbit_color {
+            Some(color) => OrbitStyle { color, ..OrbitStyle::default() },
+            None => OrbitStyle::default(),
+        }
+    }
+
+    /// Ángulo de giro alrededor del eje Y del modelo en el paso `time`; `orbit_angle` es el
+    /// de la posición en la órbita (ver PlanetState::angle)
+    pub fn spin_angle(&self, time: f32, orbit_angle: f32) -> f32 {
+        spin_angle(self.rotation_speed, self.tidally_locked, time, orbit_angle)
+    }
+
+    /// Polo norte en el mundo: el eje Y del modelo inclinado `axial_tilt` alrededor del eje Z
+    pub fn pole(&self) -> Vec3 {
+        let (sin, cos) = self.axial_tilt.to_radians().sin_cos();
+        Vec3::new(-sin, cos, 0.0)
+    }
+}
+
+impl MoonConfig {
+    pub fn scale(&self) -> f32 {
+        self.radius / SPHERE_RADIUS
+    }
+
+    pub fn spin_angle(&self, time: f32, orbit_angle: f32) -> f32 {
+        spin_angle(self.rotation_speed, self.tidally_locked, time, orbit_angle)
+    }
+}
+
+// Con la rotación en Y de create_model_matrix, girar -orbit_angle lleva el eje +X del modelo
+// a la dirección de la posición: la misma cara queda siempre del lado de afuera
+fn spin_angle(rotation_speed: f32, tidally_locked: bool, time: f32, orbit_angle: f32) -> f32 {
+    if tidally_locked {
+        -orbit_angle
+    } else {
+        (time * rotation_speed).rem_euclid(TAU)
+    }
+}
+
+impl RingConfig {
+    pub fn scale(&self) -> f32 {
+        self.outer_radius / RING_OUTER_RADIUS
+    }
+}
+
+fn tail_particles() -> usize {
+    600
+}
+
+fn tail_speed() -> f32 {
+    2.5
+}
+
+fn tail_lifetime() -> f32 {
+    3.0
+}
+
+fn belt_count() -> usize {
+    2000
+}
+
+fn belt_inclination() -> f32 {
+    2.0
+}
+
+fn belt_min_size() -> f32 {
+    0.03
+}
+
+fn belt_max_size() -> f32 {
+    0.15
+}
+
+fn moon_shader() -> ShaderType {
+    ShaderType::Moon
+}
+
+fn moon_orbit_speed() -> f32 {
+    MOON_ORBIT_SPEED
+}
+
+fn shader_type<'de, D: Deserializer<'de>>(deserializer: D) -> Result<ShaderType, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    SHADER_NAMES
+        .iter()
+        .find(|(known, _)| *known == name)
+        .map(|&(_, shader)| shader)
+        .ok_or_else(|| {
+            let valid: Vec<&str> = SHADER_NAMES.iter().map(|(known, _)| *known).collect();
+            serde::de::Error::custom(format!("shader desconocido \"{}\" (los válidos son: {})", name, valid.join(", ")))
+        })
+}
@@ -0,0 +1,74 @@
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::framebuffer::Framebuffer;
+
+/// Nombre con fecha y hora (UTC) como `screenshot_2024-05-01_123456.png`
+pub fn file_name(prefix: &str) -> String {
+    let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs());
+    let (year, month, day) = civil_date((seconds / 86_400) as i64);
+    let time = seconds % 86_400;
+    format!(
+        "{}_{:04}-{:02}-{:02}_{:02}{:02}{:02}.png",
+        prefix, year, month, day, time / 3600, time / 60 % 60, time % 60
+    )
+}
+
+// Fecha del calendario gregoriano para un número de días desde 1970-01-01
+// (algoritmo de Howard Hinnant)
+fn civil_date(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Colores del framebuffer (0RGB empaquetado) como RGB8
+pub fn color_pixels(framebuffer: &Framebuffer) -> Vec<u8> {
+    framebuffer
+        .buffer
+        .iter()
+        .flat_map(|&pixel| [(pixel >> 16) as u8, (pixel >> 8) as u8, pixel as u8])
+        .collect()
+}
+
+/// Profundidad en escala de grises: lo más cercano en blanco y lo más lejano en gris oscuro,
+/// así se distingue de los pixeles sin geometría (cielo), que quedan en negro
+pub fn depth_pixels(framebuffer: &Framebuffer) -> Vec<u8> {
+    let depths: Vec<f32> = (0..framebuffer.height)
+        .flat_map(|y| (0..framebuffer.width).map(move |x| (x, y)))
+        .map(|(x, y)| framebuffer.depth_at(x, y).unwrap_or(Framebuffer::MAX_DEPTH))
+        .collect();
+
+    // El cielo y los pixeles sin dibujar están a MAX_DEPTH; no cuentan para el rango
+    let geometry = depths.iter().copied().filter(|&depth| depth < Framebuffer::MAX_DEPTH);
+    let (near, far) = geometry.fold((f32::INFINITY, f32::NEG_INFINITY), |(near, far), depth| (near.min(depth), far.max(depth)));
+    let range = (far - near).max(f32::EPSILON);
+
+    depths
+        .iter()
+        .flat_map(|&depth| {
+            let value = if depth < Framebuffer::MAX_DEPTH { 32.0 + 223.0 * (1.0 - (depth - near) / range) } else { 0.0 };
+            [value as u8; 3]
+        })
+        .collect()
+}
+
+/// Escribe el PNG en otro hilo para no frenar el render; los errores (carpeta de solo
+/// lectura, disco lleno) sólo se informan por consola
+pub fn save_in_background(path: String, width: usize, height: usize, pixels: Vec<u8>) {
+    thread::spawn(move || {
+        let Some(image) = image::RgbImage::from_raw(width as u32, height as u32, pixels) else {
+            eprintln!("No se pudo guardar {}: tamaño de imagen inválido", path);
+            return;
+        };
+        if let Err(e) = image.save(&path) {
+            eprintln!("No se pudo guardar {}: {}", path, e);
+        }
+    });
+}
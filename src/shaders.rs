@@ -1,9 +1,38 @@
-use nalgebra_glm::{Vec2, Vec3, Vec4, Mat3, dot, mat4_to_mat3};
+use nalgebra_glm::{Vec2, Vec3, Vec4, Mat3, dot, mat4_to_mat3, rotate_vec3};
 use crate::vertex::Vertex;
-use crate::Uniforms;
+use crate::{ShadingStyle, Uniforms};
 use crate::fragments::Fragments;
-use crate::color::Color;
+use crate::color::{Color, srgb_factor};
+use crate::{NEAR_PLANE, FAR_PLANE};
+use crate::noise::{Fractal, CraterField, perlin, fbm, ridged, sparkle};
+use crate::texture::{Filter, Sampler};
 use std::f32::consts::PI;
+use std::sync::OnceLock;
+
+// Modos de depuración globales (Uniforms::debug_mode), ciclados desde handle_input
+pub const DEBUG_DEPTH: u32 = 1;
+pub const DEBUG_OVERDRAW: u32 = 2;
+// Capas internas de cada shader (color base, patrón, efecto)
+const DEBUG_LAYER_1: u32 = 3;
+const DEBUG_LAYER_2: u32 = 4;
+const DEBUG_LAYER_3: u32 = 5;
+pub const DEBUG_MODE_COUNT: u32 = 6;
+
+// Semillas de ruido fijas: cada planeta se ve igual en todas las ejecuciones
+const EARTH_SEED: u32 = 3;
+const ROCKY_SEED: u32 = 11;
+const VOLCANIC_SEED: u32 = 23;
+const RING_SEED: u32 = 31;
+const GAS_SEED: u32 = 41;
+const ICY_SEED: u32 = 53;
+const SUN_SEED: u32 = 61;
+const SHIP_SEED: u32 = 83;
+const MOON_SEED: u32 = 97;
+const OCEAN_SEED: u32 = 101;
+const DESERT_SEED: u32 = 113;
+const AURORA_SEED: u32 = 127;
+const ASTEROID_SEED: u32 = 149;
+const COMET_SEED: u32 = 151;
 
 pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
   let position = Vec4::new(
@@ -12,33 +41,29 @@ pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
     vertex.position.z,
     1.0
   );
-  let transformed = uniforms.projection_matrix * uniforms.view_matrix * uniforms.model_matrix * position;
-
-  let w = transformed.w;
-  let ndc_position = Vec4::new(
-    transformed.x / w,
-    transformed.y / w,
-    transformed.z / w,
-    1.0
-  );
-
-  let screen_position = uniforms.viewport_matrix * ndc_position;
+  // Solo se llega hasta clip space; la división perspectiva se hace después del recorte
+  let clip_position = uniforms.projection_matrix * uniforms.view_matrix * uniforms.model_matrix * position;
 
   let model_mat3 = mat4_to_mat3(&uniforms.model_matrix); 
   let normal_matrix = model_mat3.transpose().try_inverse().unwrap_or(Mat3::identity());
 
   let transformed_normal = normal_matrix * vertex.normal;
+  let world_position = (uniforms.model_matrix * position).xyz();
 
   Vertex {
     position: vertex.position,
     normal: vertex.normal,
     tex_coords: vertex.tex_coords,
     color: vertex.color,
-    transformed_position: Vec3::new(screen_position.x, screen_position.y, screen_position.z),
+    clip_position,
+    transformed_position: Vec3::zeros(), // Se calcula en project_to_screen
     transformed_normal,
+    world_position,
+    material: vertex.material,
   }
 }
 
+#[derive(Debug, Clone, Copy)]
 pub enum ShaderType {
   Sun,
   Earth,
@@ -47,284 +72,957 @@ pub enum ShaderType {
   RockyPlanet,
   IcyPlanet,
   VolcanicPlanet,
+  OceanPlanet,
+  DesertPlanet,
   Moon,
+  // Rocas del cinturón de asteroides
+  Asteroid,
+  // Núcleo de un cometa: hielo sucio
+  Comet,
   Ring,
   Ship,
+  // Imagen de Uniforms::texture en proyección equirectangular
+  Textured,
+}
+
+impl ShaderType {
+  /// Los shaders translúcidos devuelven colores con alfa; sus objetos se dibujan después de los
+  /// opacos, de atrás hacia adelante
+  pub fn is_transparent(&self) -> bool {
+    matches!(self, ShaderType::Ring)
+  }
+}
+
+/// Valores ajustables de un shader, para poder retocar un planeta sin recompilar. Cada shader
+/// interpreta los campos a su manera (ver `for_type`); los que no usa quedan en 0.
+#[derive(Debug, Clone, Copy)]
+pub struct ShaderParams {
+  pub colors: [Color; 4],
+  pub noise_scale: f32,
+  pub octaves: u32,
+  pub threshold: f32,
+  pub emissive: f32,
+  pub shininess: f32,
+  pub specular: f32,
+}
+
+impl ShaderParams {
+  /// Valores con los que se ve cada shader por defecto
+  pub fn for_type(shader: &ShaderType) -> Self {
+    let none = ShaderParams {
+      colors: [Color::new(0, 0, 0); 4],
+      noise_scale: 0.0,
+      octaves: 0,
+      threshold: 0.0,
+      emissive: 0.0,
+      shininess: 0.0,
+      specular: 0.0,
+    };
+    match shader {
+      // Granulación y limbo; el umbral hace aparecer las fulguraciones
+      ShaderType::Sun => ShaderParams {
+        colors: [Color::new(204, 102, 0), Color::new(255, 190, 40), Color::new(255, 245, 200), Color::new(190, 50, 0)],
+        noise_scale: 9.0,
+        octaves: 4,
+        threshold: 0.42,
+        emissive: 1.3,
+        ..none
+      },
+      // Tierra, océano, nubes y ciudades; el umbral separa continentes de océano y la
+      // emisión es el brillo de las ciudades
+      ShaderType::Earth => ShaderParams {
+        colors: [Color::new(34, 139, 34), Color::new(0, 105, 148), Color::new(255, 255, 255), Color::new(255, 200, 90)],
+        noise_scale: 2.5,
+        octaves: 5,
+        threshold: 0.05,
+        emissive: 1.0,
+        specular: 0.25,
+        ..none
+      },
+      // Paleta de las bandas (crema, óxido, marrón, blanco); la escala es el número de bandas
+      ShaderType::GasPlanet => ShaderParams {
+        colors: [Color::new(236, 220, 190), Color::new(180, 95, 55), Color::new(120, 75, 45), Color::new(250, 246, 236)],
+        noise_scale: 9.0,
+        octaves: 4,
+        ..none
+      },
+      ShaderType::RingPlanet => ShaderParams {
+        colors: [Color::new(189, 155, 107), Color::new(210, 180, 140), Color::new(255, 222, 173), Color::new(0, 0, 0)],
+        noise_scale: 3.5,
+        ..none
+      },
+      // Roca oscura, media y clara; el umbral es dónde pasa del tono oscuro al medio
+      ShaderType::RockyPlanet => ShaderParams {
+        colors: [Color::new(139, 69, 19), Color::new(205, 92, 92), Color::new(255, 160, 122), Color::new(0, 0, 0)],
+        noise_scale: 3.0,
+        octaves: 4,
+        threshold: 0.4,
+        ..none
+      },
+      // Hielo, grietas, dispersión y reflejo; el umbral es desde qué cresta hay grieta
+      ShaderType::IcyPlanet => ShaderParams {
+        colors: [Color::new(173, 216, 230), Color::new(60, 90, 120), Color::new(90, 230, 255), Color::new(255, 255, 255)],
+        noise_scale: 3.5,
+        octaves: 2,
+        threshold: 0.86,
+        shininess: 180.0,
+        specular: 1.2,
+        ..none
+      },
+      // Basalto y lava (fría y caliente); la emisión es el brillo de la lava
+      ShaderType::VolcanicPlanet => ShaderParams {
+        colors: [Color::new(40, 36, 34), Color::new(150, 10, 0), Color::new(255, 230, 60), Color::new(0, 0, 0)],
+        noise_scale: 4.0,
+        octaves: 3,
+        threshold: 0.75,
+        emissive: 1.4,
+        ..none
+      },
+      // Agua profunda, agua poco profunda, arena y vegetación; el umbral es desde dónde hay islas
+      ShaderType::OceanPlanet => ShaderParams {
+        colors: [Color::new(5, 30, 85), Color::new(20, 110, 150), Color::new(210, 190, 130), Color::new(40, 120, 50)],
+        noise_scale: 3.0,
+        octaves: 5,
+        threshold: 0.28,
+        shininess: 220.0,
+        specular: 1.5,
+        ..none
+      },
+      // Arena, dunas, roca y polvo; el umbral es desde qué cresta hay mesetas
+      ShaderType::DesertPlanet => ShaderParams {
+        colors: [Color::new(214, 170, 110), Color::new(196, 110, 50), Color::new(100, 70, 50), Color::new(225, 195, 150)],
+        noise_scale: 2.0,
+        octaves: 4,
+        threshold: 0.8,
+        ..none
+      },
+      ShaderType::Moon => ShaderParams {
+        colors: [Color::new(169, 169, 169), Color::new(190, 190, 190), Color::new(211, 211, 211), Color::new(0, 0, 0)],
+        noise_scale: 8.0,
+        octaves: 4,
+        threshold: 0.5,
+        ..none
+      },
+      // Roca oscura y clara
+      ShaderType::Asteroid => ShaderParams {
+        colors: [Color::new(90, 86, 82), Color::new(150, 146, 140), Color::new(0, 0, 0), Color::new(0, 0, 0)],
+        noise_scale: 4.0,
+        octaves: 3,
+        ..none
+      },
+      // Polvo oscuro y hielo; el umbral es desde dónde asoma el hielo
+      ShaderType::Comet => ShaderParams {
+        colors: [Color::new(70, 66, 64), Color::new(200, 220, 235), Color::new(0, 0, 0), Color::new(0, 0, 0)],
+        noise_scale: 5.0,
+        octaves: 4,
+        threshold: 0.1,
+        specular: 0.3,
+        ..none
+      },
+      // Color de las bandas y de la parte en sombra; la escala es la frecuencia de las bandas
+      ShaderType::Ring => ShaderParams {
+        colors: [Color::new(255, 220, 80), Color::new(150, 120, 60), Color::new(0, 0, 0), Color::new(0, 0, 0)],
+        noise_scale: 24.0,
+        ..none
+      },
+      // Metal, juntas, reflejo del cielo y motor; la escala es el tamaño de los paneles y la
+      // emisión el brillo del motor
+      ShaderType::Ship => ShaderParams {
+        colors: [Color::new(150, 155, 165), Color::new(70, 72, 80), Color::new(200, 215, 255), Color::new(80, 160, 255)],
+        noise_scale: 10.7,
+        emissive: 1.5,
+        shininess: 64.0,
+        specular: 1.0,
+        ..none
+      },
+      ShaderType::Textured => none,
+    }
+  }
+
+  pub fn get(&self, field: ParamField) -> f32 {
+    match field {
+      ParamField::NoiseScale => self.noise_scale,
+      ParamField::Octaves => self.octaves as f32,
+      ParamField::Threshold => self.threshold,
+      ParamField::Emissive => self.emissive,
+      ParamField::Shininess => self.shininess,
+      ParamField::Specular => self.specular,
+    }
+  }
+
+  /// Mueve `field` `steps` pasos: las escalas crecen un 10% por paso, las octavas de a una y
+  /// el resto de a 0.01
+  pub fn nudge(&mut self, field: ParamField, steps: i32) {
+    let steps_f = steps as f32;
+    match field {
+      ParamField::NoiseScale => self.noise_scale = (self.noise_scale * 1.1_f32.powf(steps_f)).max(0.01),
+      ParamField::Octaves => self.octaves = self.octaves.saturating_add_signed(steps).min(8),
+      ParamField::Threshold => self.threshold += 0.01 * steps_f,
+      ParamField::Emissive => self.emissive = (self.emissive + 0.01 * steps_f).max(0.0),
+      ParamField::Shininess => self.shininess = (self.shininess * 1.1_f32.powf(steps_f)).max(1.0),
+      ParamField::Specular => self.specular = (self.specular + 0.01 * steps_f).max(0.0),
+    }
+  }
+}
+
+/// Campo de `ShaderParams` que se ajusta en marcha
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParamField {
+  NoiseScale,
+  Octaves,
+  Threshold,
+  Emissive,
+  Shininess,
+  Specular,
+}
+
+impl ParamField {
+  pub fn next(self) -> Self {
+    match self {
+      ParamField::NoiseScale => ParamField::Octaves,
+      ParamField::Octaves => ParamField::Threshold,
+      ParamField::Threshold => ParamField::Emissive,
+      ParamField::Emissive => ParamField::Shininess,
+      ParamField::Shininess => ParamField::Specular,
+      ParamField::Specular => ParamField::NoiseScale,
+    }
+  }
+}
+
+// None descarta el fragmento (no escribe color ni profundidad)
+pub fn fragment_shader(fragment: &Fragments, uniforms: &Uniforms, current_shader: &ShaderType, params: &ShaderParams) -> Option<Color> {
+  if uniforms.debug_mode == DEBUG_DEPTH {
+    return Some(depth_shader(fragment));
+  }
+
+  let color = match current_shader {
+    ShaderType::Sun => sun_shader(fragment, uniforms, params),
+    ShaderType::Earth => earth_shader(fragment, uniforms, params),
+    ShaderType::GasPlanet => gas_planet_shader(fragment, uniforms, params),
+    ShaderType::RingPlanet => ring_planet_shader(fragment, uniforms, params),
+    ShaderType::RockyPlanet => rocky_planet_shader(fragment, uniforms, params),
+    ShaderType::IcyPlanet => icy_planet_shader(fragment, uniforms, params),
+    ShaderType::VolcanicPlanet => volcanic_planet_shader(fragment, uniforms, params),
+    ShaderType::OceanPlanet => ocean_planet_shader(fragment, uniforms, params),
+    ShaderType::DesertPlanet => desert_planet_shader(fragment, uniforms, params),
+    ShaderType::Moon => moon_shader(fragment, uniforms, params),
+    ShaderType::Asteroid => asteroid_shader(fragment, params),
+    ShaderType::Comet => comet_shader(fragment, params),
+    ShaderType::Ring => ring_shader(fragment, uniforms, params)?,
+    ShaderType::Ship => ship_shader(fragment, uniforms, params),
+    ShaderType::Textured => textured_shader(fragment, uniforms),
+  };
+
+  // El sol emite luz propia, la Tierra y el planeta volcánico se iluminan a sí mismos
+  // (tienen partes emisivas), el hielo, el océano, el desierto (brillo de sus casquetes) y la
+  // nave tienen su propio modelo especular, la luna y el planeta rocoso iluminan el relieve de
+  // sus cráteres y el anillo combina la luz con la sombra del planeta; las capas de depuración
+  // se muestran sin iluminar
+  let color = match current_shader {
+    ShaderType::Sun | ShaderType::Earth | ShaderType::IcyPlanet | ShaderType::VolcanicPlanet | ShaderType::OceanPlanet
+      | ShaderType::DesertPlanet | ShaderType::RockyPlanet | ShaderType::Moon | ShaderType::Ring | ShaderType::Ship => color,
+    _ if uniforms.debug_mode >= DEBUG_LAYER_1 => color,
+    _ => sun_lighting(color, fragment, uniforms, params.specular),
+  };
+
+  // Halo de atmósfera en la silueta
+  let color = match atmosphere_color(current_shader) {
+    Some(tint) if uniforms.debug_mode < DEBUG_LAYER_1 => rim_glow(color, fragment, uniforms, tint),
+    _ => color,
+  };
+
+  let color = if uniforms.debug_mode < DEBUG_LAYER_1 { toon_outline(color, fragment, uniforms, current_shader) } else { color };
+
+  let color = if uniforms.heat > 0.0 { heat_tint(color, fragment, uniforms) } else { color };
+
+  match uniforms.fog {
+    Some(fog) if uniforms.debug_mode < DEBUG_LAYER_1 => Some(fog.apply(color, fragment.view_depth, current_shader)),
+    _ => Some(color),
+  }
+}
+
+/// Niebla por distancia: el color se mezcla hacia `color` con una caída exponencial que
+/// empieza en `near` y llega a FOG_MAX en `far` (distancias en espacio de vista)
+#[derive(Debug, Clone, Copy)]
+pub struct Fog {
+  pub color: Color,
+  pub near: f32,
+  pub far: f32,
+}
+
+// Cuánto tapa la niebla en `far`
+const FOG_MAX: f32 = 0.9;
+// Fracción de la niebla que atraviesa el brillo propio del sol
+const SUN_FOG_PIERCE: f32 = 0.75;
+
+impl Default for Fog {
+  fn default() -> Self {
+    Fog { color: Color::new(4, 6, 18), near: 40.0, far: 300.0 }
+  }
+}
+
+impl Fog {
+  /// Cantidad de niebla en [0, FOG_MAX) a una distancia de la cámara
+  pub fn amount(&self, distance: f32) -> f32 {
+    // Densidad tal que 1 - e^(-densidad * (far - near)) = FOG_MAX
+    let density = -(1.0 - FOG_MAX).ln() / (self.far - self.near).max(f32::EPSILON);
+    1.0 - (-density * (distance - self.near).max(0.0)).exp()
+  }
+
+  // La opacidad del fragmento no cambia: un anillo translúcido sigue siéndolo
+  fn apply(&self, color: Color, distance: f32, shader: &ShaderType) -> Color {
+    let amount = match shader {
+      ShaderType::Sun => self.amount(distance) * (1.0 - SUN_FOG_PIERCE),
+      _ => self.amount(distance),
+    };
+    Color { a: color.a, ..color.lerp(&self.color, amount) }
+  }
+}
+
+// Fracción de luz que reciben las caras de noche (medida en sRGB, como se ve en pantalla)
+const AMBIENT_LIGHT: f32 = 0.05;
+// Color de la luz del sol que tiñe la difusa y el brillo especular
+const SUN_LIGHT: Color = Color::new(255, 255, 255);
+
+// Phong con el sol como luz puntual: ambiente + difusa + especular, en espacio del mundo.
+// Las mallas de doble cara (anillos) se iluminan igual por ambos lados. Las esferas de
+// `uniforms.shadow_spheres` tapan la difusa y la especular.
+fn sun_lighting(color: Color, fragment: &Fragments, uniforms: &Uniforms, specular: f32) -> Color {
+  let Some(light_dir) = (uniforms.light_position - fragment.world_position).try_normalize(f32::EPSILON) else {
+    return color;
+  };
+  let normal = fragment.normal;
+  // Luz que llega (1 - sombra de un eclipse); el ambiente no se tapa
+  let sunlight = 1.0 - planet_shadow(fragment, uniforms);
+
+  let n_dot_l = dot(&normal, &light_dir);
+  let diffuse = if uniforms.cull_backfaces { n_dot_l.max(0.0) } else { n_dot_l.abs() };
+  let ambient = srgb_factor(AMBIENT_LIGHT);
+  let mut lit = color * SUN_LIGHT * (ambient + (1.0 - ambient) * toon_diffuse(diffuse * sunlight, uniforms));
+
+  if specular <= 0.0 || n_dot_l <= 0.0 {
+    return lit;
+  }
+  let view_dir = (uniforms.camera_position - fragment.world_position).try_normalize(f32::EPSILON).unwrap_or(normal);
+  let reflect_dir = 2.0 * n_dot_l * normal - light_dir;
+  let highlight = toon_specular(dot(&reflect_dir, &view_dir).max(0.0).powf(32.0), uniforms) * specular * sunlight;
+  lit += SUN_LIGHT * highlight;
+  lit
+}
+
+// Estilo toon: número de niveles de la luz difusa, umbral del brillo especular y cuánto
+// de la silueta (1 - n·v) se pinta como contorno
+const TOON_BANDS: f32 = 4.0;
+const TOON_SPECULAR_THRESHOLD: f32 = 0.5;
+const TOON_OUTLINE: f32 = 0.22;
+
+// Con el estilo toon la difusa cae en bandas fijas; como sólo depende de la geometría y del
+// sol, los bordes no parpadean entre frames
+fn toon_diffuse(diffuse: f32, uniforms: &Uniforms) -> f32 {
+  match uniforms.style {
+    ShadingStyle::Smooth => diffuse,
+    ShadingStyle::Toon => (diffuse * TOON_BANDS).floor().min(TOON_BANDS - 1.0) / (TOON_BANDS - 1.0),
+  }
 }
 
-pub fn fragment_shader(fragment: &Fragments, uniforms: &Uniforms, current_shader: &ShaderType) -> Color {
-  match current_shader {
-    ShaderType::Sun => sun_shader(fragment, uniforms),
-    ShaderType::Earth => earth_shader(fragment, uniforms),
-    ShaderType::GasPlanet => gas_planet_shader(fragment, uniforms),
-    ShaderType::RingPlanet => ring_planet_shader(fragment, uniforms),
-    ShaderType::RockyPlanet => rocky_planet_shader(fragment, uniforms),
-    ShaderType::IcyPlanet => icy_planet_shader(fragment, uniforms),
-    ShaderType::VolcanicPlanet => volcanic_planet_shader(fragment, uniforms),
-    ShaderType::Moon => moon_shader(fragment, uniforms),
-    ShaderType::Ring => ring_shader(fragment, uniforms),
-    ShaderType::Ship => ship_shader(fragment, uniforms)
+// Con el estilo toon el reflejo es una mancha sólida o nada
+fn toon_specular(specular: f32, uniforms: &Uniforms) -> f32 {
+  match uniforms.style {
+    ShadingStyle::Smooth => specular,
+    ShadingStyle::Toon if specular > TOON_SPECULAR_THRESHOLD => 1.0,
+    ShadingStyle::Toon => 0.0,
   }
 }
 
+// Contorno oscuro donde la normal casi es perpendicular a la vista. El sol sigue emitiendo
+// sin contorno y los anillos, planos, no tienen silueta que marcar.
+fn toon_outline(color: Color, fragment: &Fragments, uniforms: &Uniforms, shader: &ShaderType) -> Color {
+  if uniforms.style != ShadingStyle::Toon || matches!(shader, ShaderType::Sun | ShaderType::Ring) {
+    return color;
+  }
+  let Some(view_dir) = (uniforms.camera_position - fragment.world_position).try_normalize(f32::EPSILON) else {
+    return color;
+  };
+  if dot(&fragment.normal, &view_dir) < TOON_OUTLINE {
+    Color::new(15, 15, 20)
+  } else {
+    color
+  }
+}
+
+// Color del halo de los planetas con atmósfera
+fn atmosphere_color(shader: &ShaderType) -> Option<Color> {
+  match shader {
+    ShaderType::Earth => Some(Color::new(90, 160, 255)),
+    ShaderType::GasPlanet => Some(Color::new(230, 200, 150)),
+    ShaderType::OceanPlanet => Some(Color::new(120, 190, 255)),
+    _ => None,
+  }
+}
+
+// Auroras: latitud (|y| sobre la esfera unitaria) del centro de la banda, medio ancho de la
+// cortina, color de la base y de la parte alta, e intensidad
+struct Aurora {
+  latitude: f32,
+  thickness: f32,
+  low_color: Color,
+  high_color: Color,
+  intensity: f32,
+}
+
+fn aurora(shader: &ShaderType) -> Option<Aurora> {
+  match shader {
+    ShaderType::Earth => Some(Aurora {
+      latitude: 0.8,
+      thickness: 0.06,
+      low_color: Color::new(60, 255, 140),
+      high_color: Color::new(170, 80, 255),
+      intensity: 0.9,
+    }),
+    ShaderType::IcyPlanet => Some(Aurora {
+      latitude: 0.72,
+      thickness: 0.08,
+      low_color: Color::new(80, 255, 220),
+      high_color: Color::new(255, 90, 200),
+      intensity: 0.7,
+    }),
+    _ => None,
+  }
+}
+
+// Casquetes polares estacionales: latitud (|y| sobre la esfera unitaria) donde empieza el
+// hielo a mitad del verano y a mitad del invierno de cada hemisferio
+struct IceCaps {
+  summer_latitude: f32,
+  winter_latitude: f32,
+}
+
+fn ice_caps(shader: &ShaderType) -> Option<IceCaps> {
+  match shader {
+    ShaderType::Earth => Some(IceCaps { summer_latitude: 0.9, winter_latitude: 0.72 }),
+    ShaderType::DesertPlanet => Some(IceCaps { summer_latitude: 0.96, winter_latitude: 0.82 }),
+    _ => None,
+  }
+}
+
+const ICE_CAP_COLOR: Color = Color::new(235, 245, 255);
+// Brillo especular del hielo e irregularidad del borde del casquete
+const ICE_CAP_SPECULAR: f32 = 0.4;
+const ICE_CAP_EDGE_NOISE: f32 = 0.05;
+
+// Cantidad de hielo en `p` (esfera unitaria) en [0, 1]. El eje Y del modelo es el polo, que
+// la matriz del modelo inclina; la estación sale de hacia dónde apunta respecto al sol
+// (Uniforms::season): cuando un hemisferio está en invierno el otro está en verano.
+fn ice_cover(p: &Vec3, uniforms: &Uniforms, caps: &IceCaps, seed: u32) -> f32 {
+  let winter = 0.5 - 0.5 * uniforms.season * p.y.signum();
+  let latitude = caps.summer_latitude + (caps.winter_latitude - caps.summer_latitude) * winter;
+  let edge = latitude + fbm(&(p * 6.0), seed, &Fractal::new(3, 2.0, 0.5)) * ICE_CAP_EDGE_NOISE;
+  smoothstep(edge, edge + 0.015, p.y.abs())
+}
+
+// Pasos del reloj por ciclo del movimiento de las cortinas y del pulso
+const AURORA_PERIOD: u32 = 4096;
+const AURORA_PULSE_PERIOD: u32 = 1024;
+// Grados que el tono de la aurora se desplaza hacia cada lado
+const AURORA_HUE_DRIFT: f32 = 20.0;
+
+// Luz de la aurora que se suma sobre la superficie. La cortina es una cinta alrededor del polo
+// cuya latitud ondula con la longitud, con rayos finos a lo largo de ella; el ruido se lee
+// sobre el círculo de longitud, así que no hay costura. Sólo se ve en el lado nocturno.
+fn aurora_light(fragment: &Fragments, uniforms: &Uniforms, aurora: &Aurora) -> Color {
+  let p = fragment.vertex_pos.normalize();
+  let latitude = p.y.abs();
+  if (latitude - aurora.latitude).abs() > aurora.thickness * 3.0 {
+    return Color::new(0, 0, 0);
+  }
+  let Some(around) = Vec2::new(p.x, p.z).try_normalize(f32::EPSILON) else {
+    return Color::new(0, 0, 0);
+  };
+
+  let phase = cycle_angle(uniforms.time, AURORA_PERIOD);
+  let drift = Vec3::new(phase.cos(), phase.sin(), 0.0);
+  // Cada hemisferio con su propio ruido
+  let seed = if p.y > 0.0 { AURORA_SEED } else { AURORA_SEED + 2 };
+
+  let wave = perlin(&(Vec3::new(around.x * 2.0, 0.0, around.y * 2.0) + drift * 0.6), seed);
+  let center = aurora.latitude + wave * aurora.thickness * 1.5;
+  let across = (latitude - center) / aurora.thickness;
+  let ribbon = 1.0 - smoothstep(0.0, 1.0, across.abs());
+  let rays = 0.5 + 0.5 * perlin(&(Vec3::new(around.x * 30.0, 0.0, around.y * 30.0) + drift * 2.0), seed + 1);
+
+  let pulse_phase = cycle_angle(uniforms.time, AURORA_PULSE_PERIOD);
+  let pulse = 0.75 + 0.25 * pulse_phase.sin();
+
+  let sun_dir = (uniforms.light_position - fragment.world_position).try_normalize(f32::EPSILON).unwrap_or(fragment.normal);
+  let night = smoothstep(0.2, -0.3, dot(&fragment.normal, &sun_dir));
+
+  // Verde abajo (hacia el ecuador) y morado arriba (hacia el polo), pasando por el cian; el
+  // tono de toda la cortina deriva un poco con el tiempo
+  let hue_drift = AURORA_HUE_DRIFT * (phase * 3.0).sin();
+  let color = aurora.low_color.lerp_hsv(&aurora.high_color, smoothstep(-0.5, 1.0, across)).with_hue_shift(hue_drift);
+  color * (ribbon * (0.35 + 0.65 * rays) * pulse * night * aurora.intensity)
+}
+
+// Brillo aditivo tipo fresnel cerca de los ángulos rasantes. El smoothstep deja el interior
+// del disco intacto y limita el efecto a una banda junto al borde; el lado que mira al
+// sol brilla más.
+fn rim_glow(color: Color, fragment: &Fragments, uniforms: &Uniforms, tint: Color) -> Color {
+  let Some(view_dir) = (uniforms.camera_position - fragment.world_position).try_normalize(f32::EPSILON) else {
+    return color;
+  };
+  let grazing = 1.0 - dot(&fragment.normal, &view_dir).max(0.0);
+  let rim = smoothstep(0.55, 1.0, grazing);
+  if rim <= 0.0 {
+    return color;
+  }
 
+  let sun_dir = (uniforms.light_position - fragment.world_position).try_normalize(f32::EPSILON).unwrap_or(view_dir);
+  let sun_facing = 0.25 + 0.75 * dot(&fragment.normal, &sun_dir).max(0.0);
+  color + tint * (rim * sun_facing * 0.8)
+}
 
-// Planeta de hielo
-pub fn icy_planet_shader(fragment: &Fragments, uniforms: &Uniforms) -> Color {
-  let base_color = Color::new(173, 216, 230); // Celeste
-  let fracture_color = Color::new(255, 255, 255); // Blanco
+// Tinte naranja cerca del sol, con una ondulación vertical que simula la distorsión del calor
+fn heat_tint(color: Color, fragment: &Fragments, uniforms: &Uniforms) -> Color {
+  let shimmer = 0.5 + 0.5 * (fragment.position.y * 0.15 + uniforms.time * 0.4).sin();
+  let strength = uniforms.heat * (0.25 + 0.15 * shimmer);
+  color.lerp(&Color::new(255, 140, 30), strength)
+}
 
-  // Grietas
-  let stripe_width = 0.15;
-  let combined_pos = fragment.vertex_pos.x * 0.7 + fragment.vertex_pos.y * 0.3;
-  let stripe_factor = ((combined_pos / stripe_width) * PI).sin().abs();
+// Profundidad en escala de grises: blanco cerca, negro lejos. La z en NDC se
+// linealiza con los planos del frustum y se mapea en escala logarítmica para que
+// la escena (a decenas de unidades) no quede toda del mismo tono.
+fn depth_shader(fragment: &Fragments) -> Color {
+  let ndc_z = fragment.depth.clamp(-1.0, 1.0);
+  let distance = 2.0 * NEAR_PLANE * FAR_PLANE / (FAR_PLANE + NEAR_PLANE - ndc_z * (FAR_PLANE - NEAR_PLANE));
+  let t = ((distance / NEAR_PLANE).ln() / (FAR_PLANE / NEAR_PLANE).ln()).clamp(0.0, 1.0);
+  Color::new(255, 255, 255) * (1.0 - t)
+}
 
-  let fracture_factor = (1.0 - stripe_factor).powf(3.0);
-  let fractured_surface = base_color.lerp(&fracture_color, fracture_factor);
+// Planeta de hielo: el exponente del brillo (`shininess`) alto da un reflejo pequeño y nítido
+pub fn icy_planet_shader(fragment: &Fragments, uniforms: &Uniforms, params: &ShaderParams) -> Color {
+  let [base_color, crack_color, subsurface_color, specular_color] = params.colors; // Celeste, azul oscuro, cian y blanco
+
+  // Grietas: crestas finas del ruido, pocas y dispersas
+  let p = fragment.vertex_pos.normalize();
+  let ridges = ridged(&(p * params.noise_scale), ICY_SEED, &Fractal::new(params.octaves, 2.0, 0.3));
+  let crack_factor = smoothstep(params.threshold, params.threshold + 0.11, ridges) * 0.7;
+  let cracked_surface = base_color.lerp(&crack_color, crack_factor);
+
+  let normal = fragment.normal;
+  let view_dir = (uniforms.camera_position - fragment.world_position).try_normalize(f32::EPSILON).unwrap_or(normal);
+  let light_dir = (uniforms.light_position - fragment.world_position).try_normalize(f32::EPSILON).unwrap_or(normal);
+
+  // Dispersión bajo la superficie: el hielo se aclara hacia cian en los ángulos rasantes,
+  // sólo del lado iluminado
+  let grazing = 1.0 - dot(&normal, &view_dir).max(0.0);
+  let lit_side = smoothstep(-0.2, 0.3, dot(&normal, &light_dir));
+  let sunlight = 1.0 - planet_shadow(fragment, uniforms);
+  let subsurface = grazing * grazing * lit_side * sunlight * 0.6;
+
+  // Blinn-Phong: el reflejo queda donde el vector medio sol-cámara coincide con la normal
+  let half_dir = (light_dir + view_dir).try_normalize(f32::EPSILON).unwrap_or(normal);
+  let specular = if dot(&normal, &light_dir) > 0.0 {
+    toon_specular(dot(&normal, &half_dir).max(0.0).powf(params.shininess), uniforms) * params.specular * sunlight
+  } else {
+    0.0
+  };
 
-  // Reflejo
-  let normal = fragment.normal.normalize();
-  let light_dir = Vec3::new(0.0, 0.0, -1.0);
-  let view_dir = -fragment.vertex_pos.normalize();
-  let reflect_dir = (2.0 * dot(&light_dir, &normal) * normal - light_dir).normalize();
-  let specular_intensity = dot(&reflect_dir, &view_dir).max(0.0).powf(32.0);
-  let specular_color = Color::new(255, 255, 255);
-  let reflected_surface = fractured_surface.lerp(&specular_color, specular_intensity * 0.5);
+  let diffuse = sun_lighting(cracked_surface, fragment, uniforms, 0.0);
+  let aurora_color = aurora(&ShaderType::IcyPlanet).map_or(Color::new(0, 0, 0), |a| aurora_light(fragment, uniforms, &a));
 
   // Depuración
   match uniforms.debug_mode {
-      1 => base_color * fragment.intensity,            // Solo el color base
-      2 => fracture_color * fracture_factor,           // Solo las grietas
-      3 => specular_color * specular_intensity,        // Solo la reflexión especular
-      _ => reflected_surface * fragment.intensity,     // Shader completo
+      DEBUG_LAYER_1 => base_color,                                  // Solo el color base
+      DEBUG_LAYER_2 => Color::new(255, 255, 255) * crack_factor,    // Solo las grietas
+      DEBUG_LAYER_3 => specular_color * specular,                   // Solo la reflexión especular
+      _ => diffuse + subsurface_color * subsurface + specular_color * specular + aurora_color, // Shader completo
   }
 }
 
-// Planeta volcánico
-pub fn volcanic_planet_shader(fragment: &Fragments, uniforms: &Uniforms) -> Color {
-  let rock_color = Color::new(50, 50, 50);    // Gris oscuro
-  let lava_color = Color::new(255, 100, 0);    // Naranja más intenso (más saturado)
+// Planeta océano: ancho de la espuma en la costa y escala e inclinación de las olas
+const FOAM_WIDTH: f32 = 0.06;
+const WAVE_SCALE: f32 = 14.0;
+const WAVE_STRENGTH: f32 = 0.25;
+// Pasos del reloj por ciclo de las olas
+const WAVE_PERIOD: u32 = 2048;
+
+pub fn ocean_planet_shader(fragment: &Fragments, uniforms: &Uniforms, params: &ShaderParams) -> Color {
+  let [deep_color, shallow_color, sand_color, jungle_color] = params.colors;
+  let foam_color = Color::new(245, 250, 255);  // Espuma
+
+  let p = fragment.vertex_pos.normalize();
+  let phase = cycle_angle(uniforms.time, WAVE_PERIOD);
+
+  // Islas: sólo las crestas más altas del ruido salen del agua
+  let island_threshold = params.threshold;
+  let elevation = fbm(&(p * params.noise_scale), OCEAN_SEED, &Fractal::new(params.octaves, 2.0, 0.5));
+  let land = smoothstep(island_threshold, island_threshold + 0.01, elevation);
+  let island_color = sand_color.lerp(&jungle_color, smoothstep(island_threshold + 0.02, island_threshold + 0.1, elevation));
+
+  // Espuma: anillos alrededor de la costa que avanzan hacia la orilla
+  let coast = smoothstep(island_threshold - FOAM_WIDTH, island_threshold, elevation) * (1.0 - land);
+  let ripples = 0.5 + 0.5 * ((island_threshold - elevation) / FOAM_WIDTH * 12.0 + phase * 3.0).sin();
+  let foam = coast * smoothstep(0.4, 0.8, ripples);
+
+  // Olas: el gradiente de un ruido que se desplaza en un círculo inclina la normal
+  let flow = Vec3::new(phase.cos(), 0.0, phase.sin()) * 0.8;
+  let q = p * WAVE_SCALE + flow;
+  let h = 0.05;
+  let gradient = Vec3::new(
+    perlin(&(q + Vec3::new(h, 0.0, 0.0)), OCEAN_SEED + 1) - perlin(&(q - Vec3::new(h, 0.0, 0.0)), OCEAN_SEED + 1),
+    perlin(&(q + Vec3::new(0.0, h, 0.0)), OCEAN_SEED + 1) - perlin(&(q - Vec3::new(0.0, h, 0.0)), OCEAN_SEED + 1),
+    perlin(&(q + Vec3::new(0.0, 0.0, h)), OCEAN_SEED + 1) - perlin(&(q - Vec3::new(0.0, 0.0, h)), OCEAN_SEED + 1),
+  ) / (2.0 * h);
+  let tangent_gradient = gradient - p * dot(&gradient, &p);
+  // La dirección se lleva al mundo como la normal; la inclinación se limita para que no se de vuelta
+  let slope = tangent_gradient.magnitude().min(1.0) * WAVE_STRENGTH;
+  let slope_dir = (mat4_to_mat3(&uniforms.model_matrix) * tangent_gradient).try_normalize(f32::EPSILON).unwrap_or(Vec3::zeros());
+  let wave_normal = (fragment.normal - slope_dir * slope).try_normalize(f32::EPSILON).unwrap_or(fragment.normal);
+  // Las islas no tienen olas
+  let normal = fragment.normal.lerp(&wave_normal, 1.0 - land).normalize();
+
+  let view_dir = (uniforms.camera_position - fragment.world_position).try_normalize(f32::EPSILON).unwrap_or(normal);
+  let light_dir = (uniforms.light_position - fragment.world_position).try_normalize(f32::EPSILON).unwrap_or(normal);
+  let sunlight = 1.0 - planet_shadow(fragment, uniforms);
+
+  // Reflejo del sol: en ángulos rasantes el exponente baja y el brillo se estira sobre las olas
+  let grazing = 1.0 - dot(&fragment.normal, &view_dir).max(0.0);
+  let shininess = params.shininess * (1.0 - 0.85 * grazing * grazing);
+  let half_dir = (light_dir + view_dir).try_normalize(f32::EPSILON).unwrap_or(normal);
+  let glint = if dot(&normal, &light_dir) > 0.0 {
+    toon_specular(dot(&normal, &half_dir).max(0.0).powf(shininess), uniforms) * sunlight * (1.0 - land)
+  } else {
+    0.0
+  };
 
-  // Lava
-  let lava_scale = 15.0;
-  let noise_x = fragment.vertex_pos.x * lava_scale + uniforms.time as f32 * 0.1;
-  let noise_y = fragment.vertex_pos.y * lava_scale - uniforms.time as f32 * 0.1;
-  let lava_noise = ((noise_x.sin() * noise_y.cos()).abs() * 1.5).fract();
-  let lava_factor = (lava_noise - 0.7).max(0.0) / 0.3;
-  let surface_color = rock_color.lerp(&lava_color, lava_factor);
+  let water = deep_color.lerp(&shallow_color, coast);
+  let surface = water.lerp(&island_color, land).lerp(&foam_color, foam);
+  let lit = sun_lighting(surface, &Fragments { normal, ..*fragment }, uniforms, 0.0);
 
-  // Brillo
-  let glow_factor = (lava_factor.powf(2.0) * 0.8).clamp(0.0, 1.0);
-  let glow_color = lava_color.lerp(&Color::new(255, 255, 50), glow_factor);
-  let final_color = surface_color.lerp(&glow_color, glow_factor);
+  // Depuración
+  match uniforms.debug_mode {
+      DEBUG_LAYER_1 => water.lerp(&island_color, land),                     // Agua e islas
+      DEBUG_LAYER_2 => Color::new(
+        ((normal.x * 0.5 + 0.5) * 255.0) as u8,
+        ((normal.y * 0.5 + 0.5) * 255.0) as u8,
+        ((normal.z * 0.5 + 0.5) * 255.0) as u8,
+      ),                                                                     // Normales de las olas
+      DEBUG_LAYER_3 => Color::new(255, 255, 255) * glint,                    // Solo el reflejo del sol
+      _ => lit + Color::new(255, 245, 220) * (glint * params.specular),                  // Shader completo
+  }
+}
 
-  // Luz de la lava
-  let lava_emission_factor = 0.8;
-  let lava_emitted_color = lava_color * lava_emission_factor;
-  let emitted_color = final_color.lerp(&lava_emitted_color, lava_factor);
+// Planeta desértico: radio angular de la tormenta de polvo, pasos que tarda en dar la vuelta
+// al planeta y en girar sobre sí misma, y opacidad máxima
+const STORM_RADIUS: f32 = 0.7;
+const STORM_TRAVEL_PERIOD: u32 = 8192;
+const STORM_SWIRL_PERIOD: u32 = 2048;
+const STORM_OPACITY: f32 = 0.85;
+
+pub fn desert_planet_shader(fragment: &Fragments, uniforms: &Uniforms, params: &ShaderParams) -> Color {
+  let [sand_color, dune_color, rock_color, dust_color] = params.colors;
+  let polar_color = Color::new(235, 215, 185);  // Arena clara de los polos
+
+  let p = fragment.vertex_pos.normalize();
+
+  // Dunas: capas de ruido de baja frecuencia y ondulaciones finas deformadas por el mismo ruido
+  let dunes = fbm(&(p * params.noise_scale), DESERT_SEED, &Fractal::new(params.octaves, 2.0, 0.5)) * 0.5 + 0.5;
+  let ripples = 0.5 + 0.5 * ((p.x * 0.6 + p.z * 0.8) * 40.0 + dunes * 12.0).sin();
+  let ground = sand_color.lerp(&dune_color, dunes).lerp(&dune_color, ripples * 0.35);
+
+  // Mesetas rocosas donde las crestas del ruido son altas
+  let highlands = smoothstep(params.threshold, params.threshold + 0.12, ridged(&(p * 3.0), DESERT_SEED + 1, &Fractal::new(3, 2.0, 0.5)));
+  let terrain = ground.lerp(&rock_color, highlands * 0.6);
+
+  // Polos algo más claros
+  let terrain = terrain.lerp(&polar_color, smoothstep(0.7, 0.95, p.y.abs()) * 0.7);
+
+  // Casquetes de hielo que crecen en invierno
+  let ice = ice_caps(&ShaderType::DesertPlanet).map_or(0.0, |caps| ice_cover(&p, uniforms, &caps, DESERT_SEED + 3));
+  let terrain = terrain.lerp(&ICE_CAP_COLOR, ice);
+
+  // Tormenta de polvo: su centro recorre el planeta girando alrededor de un eje inclinado y
+  // el polvo se arremolina alrededor del centro. Los dos giros son periódicos, así que no salta.
+  let travel = cycle_angle(uniforms.time, STORM_TRAVEL_PERIOD);
+  let swirl = cycle_angle(uniforms.time, STORM_SWIRL_PERIOD);
+  let storm_axis = Vec3::new(0.3, 1.0, 0.1).normalize();
+  let storm_center = rotate_vec3(&Vec3::new(1.0, 0.15, 0.0).normalize(), travel, &storm_axis);
+
+  let storm_distance = dot(&p, &storm_center).clamp(-1.0, 1.0).acos() / STORM_RADIUS;
+  let swirled = rotate_vec3(&p, swirl + (1.0 - storm_distance).max(0.0) * 3.0, &storm_center);
+  let dust = fbm(&(swirled * 5.0), DESERT_SEED + 2, &Fractal::new(4, 2.0, 0.55)) * 0.5 + 0.5;
+  // Borde irregular: el ruido se come el contorno de la mancha
+  let storm = (1.0 - smoothstep(0.6, 1.0, storm_distance + (dust - 0.5) * 0.4)) * smoothstep(0.2, 0.6, dust);
+  let final_color = sun_lighting(terrain.lerp(&dust_color, storm * STORM_OPACITY), fragment, uniforms, ice * ICE_CAP_SPECULAR);
 
   // Depuración
   match uniforms.debug_mode {
-      1 => rock_color * fragment.intensity,             // Only rock color
-      2 => lava_color * lava_factor,                    // Only lava regions
-      3 => glow_color * glow_factor,                    // Only glow effect
-      _ => emitted_color * fragment.intensity,          // Full shader with emission effect
+      DEBUG_LAYER_1 => ground,                                  // Solo las dunas
+      DEBUG_LAYER_2 => Color::new(255, 255, 255) * storm,       // Solo la tormenta
+      DEBUG_LAYER_3 => Color::new(255, 255, 255) * highlands,   // Solo las mesetas
+      _ => final_color,      // Shader completo
   }
 }
 
-// Sol
-pub fn sun_shader(fragment: &Fragments, uniforms: &Uniforms) -> Color {
-  // Colores base del degradado
-  let color1 = Color::new(255, 255, 255); // Amarillo muy claro
-  let color2 = Color::new(255, 230, 28); // Amarillo pastel
-  let color3 = Color::new(255, 178, 51); // Amarillo intenso
-  let color4 = Color::new(204, 102, 0);  // Naranja oscuro
+// Planeta volcánico: periodo de la animación en pasos del reloj
+const LAVA_PERIOD: u32 = 4096;
 
-  // Coordenadas del fragmento normalizadas al rango [-1, 1]
-  let x = fragment.vertex_pos.x;
-  let y = fragment.vertex_pos.y;
+pub fn volcanic_planet_shader(fragment: &Fragments, uniforms: &Uniforms, params: &ShaderParams) -> Color {
+  let [basalt_color, deep_lava, bright_lava, _] = params.colors; // Basalto, rojo profundo y amarillo
 
-  // Centro del degradado
-  let center = (0.0, 0.0);
-  let radius = ((x - center.0).powi(2) + (y - center.1).powi(2)).sqrt();
+  // Fase periódica: el campo se desplaza en un círculo del espacio de ruido y vuelve al inicio
+  let phase = cycle_angle(uniforms.time, LAVA_PERIOD);
+  let flow = Vec3::new(phase.cos(), phase.sin(), 0.0) * 0.6;
 
-  // Radio normalizado entre 0 y 1
-  let t = radius.clamp(0.0, 1.0);
+  // Grietas con ruido de crestas sobre la posición en espacio del modelo (no se desliza al orbitar)
+  let p = fragment.vertex_pos.normalize() * params.noise_scale + flow;
+  let ridges = ridged(&p, VOLCANIC_SEED, &Fractal::new(params.octaves, 2.0, 0.5));
+  let lava_factor = ((ridges - params.threshold) / (1.0 - params.threshold)).clamp(0.0, 1.0);
 
-  // Mezcla de colores según el radio
-  let blended_color = if t < 0.33 {
-      color1.lerp(&color2, t / 0.33)
-  } else if t < 0.66 {
-      color2.lerp(&color3, (t - 0.33) / 0.33)
-  } else {
-      color3.lerp(&color4, (t - 0.66) / 0.34)
-  };
+  // El tono de la lava oscila entre rojo y amarillo (4 ciclos por periodo), pasando por
+  // naranjas saturados
+  let cycle = 0.5 + 0.5 * (phase * 4.0 + ridges * 6.0).sin();
+  let lava_color = deep_lava.lerp_hsv(&bright_lava, cycle * lava_factor);
 
-  // Emisión del sol
-  let emission_factor = 1.5;
-  let emitted_color = blended_color * emission_factor;
+  // El basalto recibe la luz del sol; la lava emite y sigue brillando en la cara de noche
+  let rock = sun_lighting(basalt_color, fragment, uniforms, 0.0);
+  let glow = lava_color * (lava_factor * params.emissive);
 
   // Depuración
   match uniforms.debug_mode {
-      1 => blended_color * fragment.intensity,                      // Degradado sin emisión
-      2 => blended_color,                                           // Degradado puro
-      3 => Color::new(255, 255, 255) * emission_factor,     // Solo emisión blanca
-      _ => emitted_color * fragment.intensity,                      // Shader completo
+      DEBUG_LAYER_1 => basalt_color,                                // Solo el basalto
+      DEBUG_LAYER_2 => Color::new(255, 255, 255) * lava_factor,     // Solo las grietas
+      DEBUG_LAYER_3 => glow,                                        // Solo la emisión
+      _ => rock * (1.0 - lava_factor) + glow,                       // Shader completo
   }
 }
 
-// Planeta gaseoso
-pub fn gas_planet_shader(fragment: &Fragments, uniforms: &Uniforms) -> Color {
-  let band_color1 = Color::new(139, 69, 19);  // Marrón más oscuro
-  let band_color2 = Color::new(205, 133, 63); // Marrón claro
-  let band_color3 = Color::new(222, 184, 135); // Beige
+// Sol: pasos del reloj por ciclo de la granulación y de las fulguraciones (más lentas)
+const SUN_PERIOD: u32 = 2048;
+const FLARE_PERIOD: u32 = 16384;
 
+pub fn sun_shader(fragment: &Fragments, uniforms: &Uniforms, params: &ShaderParams) -> Color {
+  // Naranja oscuro, amarillo intenso, amarillo muy claro y rojo del borde
+  let [cool_color, warm_color, hot_color, limb_color] = params.colors;
 
-  // Franjas horizontales
-  let band_scale = 4.0;
-  let flow_speed = 0.001;
-  let flow_offset = uniforms.time as f32 * flow_speed;
-  let y_position = fragment.vertex_pos.y + flow_offset;
-  let band_factor = ((y_position * band_scale).sin() * 0.5 + 0.5).fract();
+  let p = fragment.vertex_pos.normalize();
 
-  // Mezcla entre colores según la posición en las bandas
-  let band_color = if band_factor < 0.33 {
-      band_color1.lerp(&band_color2, band_factor / 0.33)
-  } else if band_factor < 0.66 {
-      band_color2.lerp(&band_color3, (band_factor - 0.33) / 0.33)
+  // Granulación: celdas de convección que hierven recorriendo un círculo del espacio de ruido
+  let phase = cycle_angle(uniforms.time, SUN_PERIOD);
+  let boil = Vec3::new(phase.cos(), phase.sin(), 0.0) * 0.6;
+  let granulation = fbm(&(p * params.noise_scale + boil), SUN_SEED, &Fractal::new(params.octaves, 2.0, 0.5)) * 0.5 + 0.5;
+  let surface = if granulation < 0.5 {
+    cool_color.lerp(&warm_color, granulation / 0.5)
   } else {
-      band_color3.lerp(&band_color1, (band_factor - 0.66) / 0.34)
+    warm_color.lerp(&hot_color, (granulation - 0.5) / 0.5)
   };
 
-  // Vortice
-  let vortex_center = Vec2::new(-0.2, -0.2);
-  let vortex_radius = 0.3;
-  let distance_to_vortex = ((fragment.vertex_pos.x - vortex_center.x).powi(2)
-      + (fragment.vertex_pos.y - vortex_center.y).powi(2))
-      .sqrt();
-  let vortex_intensity = ((vortex_radius - distance_to_vortex).max(0.0f32) / vortex_radius).powf(2.0);
-  let vortex_color = Color::new(255, 69, 0);
-  let final_color = band_color.lerp(&vortex_color, vortex_intensity);
+  // Oscurecimiento del limbo: hacia el borde del disco la luz llega de capas más frías
+  let view_dir = (uniforms.camera_position - fragment.world_position).try_normalize(f32::EPSILON).unwrap_or(fragment.normal);
+  let mu = dot(&fragment.normal, &view_dir).max(0.0);
+  let limb = 0.45 + 0.55 * mu.sqrt();
+  let limb_surface = surface.lerp(&limb_color, (1.0 - mu).powi(2) * 0.6) * limb;
+
+  // Fulguraciones: manchas brillantes donde un ruido lento supera el umbral, así aparecen y se apagan
+  let flare_phase = cycle_angle(uniforms.time, FLARE_PERIOD);
+  let drift = Vec3::new(flare_phase.cos(), 0.0, flare_phase.sin()) * 1.5;
+  let flare_noise = perlin(&(p * 2.5 + drift), SUN_SEED + 1);
+  let flare = smoothstep(params.threshold, params.threshold + 0.15, flare_noise);
+  let flare_color = Color::new(255, 255, 230);
+
+  // Emisión del sol
+  let emitted_color = (limb_surface + flare_color * (flare * 0.6)) * params.emissive;
 
   // Depuración
   match uniforms.debug_mode {
-      1 => band_color * fragment.intensity,       // Solo franjas
-      2 => vortex_color * vortex_intensity,       // Solo vórtice
-      _ => final_color * fragment.intensity,      // Shader completo
+      DEBUG_LAYER_1 => surface,                                 // Solo la granulación
+      DEBUG_LAYER_2 => Color::new(255, 255, 255) * limb,        // Solo el oscurecimiento del limbo
+      DEBUG_LAYER_3 => flare_color * flare,                     // Solo las fulguraciones
+      _ => emitted_color,                      // Shader completo
   }
 }
 
-// Planeta rocoso
-pub fn rocky_planet_shader(fragment: &Fragments, _uniforms: &Uniforms) -> Color {
-  // Colores base para la superficie rocosa
-  let base_color = Color::new(139, 69, 19);    // Marrón rojizo oscuro
-  let mid_color = Color::new(205, 92, 92);     // Rojo rosado
-  let highlight_color = Color::new(255, 160, 122); // Salmón claro
+// Planeta gaseoso: cuánto ondula la turbulencia las bandas de latitud
+const GAS_TURBULENCE: f32 = 0.09;
+// Pasos del reloj por ciclo de la animación
+const GAS_PERIOD: u32 = 8192;
+// Gran mancha: dirección fija en espacio del modelo, radio angular y torsión del remolino
+const GREAT_SPOT_RADIUS: f32 = 0.42;
+const GREAT_SPOT_TWIST: f32 = 5.0;
+
+pub fn gas_planet_shader(fragment: &Fragments, uniforms: &Uniforms, params: &ShaderParams) -> Color {
+  let p = fragment.vertex_pos.normalize();
+  let phase = cycle_angle(uniforms.time, GAS_PERIOD);
+
+  // Gran mancha: el dominio del ruido gira alrededor de su centro, más cuanto más cerca
+  let spot_center = Vec3::new(-0.35, -0.3, 0.89).normalize();
+  let spot_distance = dot(&p, &spot_center).clamp(-1.0, 1.0).acos();
+  let falloff = (1.0 - spot_distance / GREAT_SPOT_RADIUS).max(0.0);
+  let spot_mask = smoothstep(0.0, 0.45, falloff);
+  let swirled = rotate_vec3(&p, falloff * falloff * GREAT_SPOT_TWIST, &spot_center);
+
+  // Cizalla: bandas vecinas avanzan y retroceden en sentidos opuestos y vuelven al inicio
+  let shear = (swirled.y * params.noise_scale).sin() * 0.25 * phase.sin();
+  let sheared = rotate_y(&swirled, shear);
+
+  // Turbulencia que ondula los bordes de las bandas; el desplazamiento recorre un círculo
+  let flow = Vec3::new(phase.cos(), phase.sin(), 0.0) * 0.5;
+  let turbulence = fbm(&(sheared * 4.0 + flow), GAS_SEED, &Fractal::new(params.octaves, 2.0, 0.5));
+  let latitude = sheared.y + turbulence * GAS_TURBULENCE;
+  let band_factor = (latitude * params.noise_scale).sin() * 0.5 + 0.5;
+  let band_color = sample_palette(&params.colors, band_factor);
+
+  // Interior de la mancha: remolino que gira con la animación alrededor de su centro
+  let spin = rotate_vec3(&p, falloff * GREAT_SPOT_TWIST * 2.0 + phase, &spot_center);
+  let spot_noise = fbm(&(spin * 7.0), GAS_SEED + 1, &Fractal::new(3, 2.0, 0.5)) * 0.5 + 0.5;
+  let spot_color = params.colors[1].lerp(&params.colors[0], spot_noise * (1.0 - falloff));
+  let final_color = band_color.lerp(&spot_color, spot_mask);
 
-  // Generar ruido para simular textura rocosa
-  let rock_scale = 10.0; // Mayor escala para patrones más finos
-  let detail_scale = 0.3; // Escala para detalles pequeños
+  // Depuración
+  match uniforms.debug_mode {
+      DEBUG_LAYER_1 => band_color,                                            // Solo franjas
+      DEBUG_LAYER_2 => Color::new(255, 255, 255) * spot_mask,                 // Solo la mancha
+      DEBUG_LAYER_3 => Color::new(255, 255, 255) * (turbulence * 0.5 + 0.5),  // Solo la turbulencia
+      _ => final_color,      // Shader completo
+  }
+}
 
-  // Coordenadas ajustadas con pseudoaleatoriedad
-  let x = fragment.vertex_pos.x;
-  let y = fragment.vertex_pos.y;
-  let randomness = (x * 12.9898 + y * 78.233).sin() * 43758.5453;
-  let random_factor = randomness.fract() * detail_scale;
+// Interpolación lineal entre los colores de una paleta, con t en [0, 1]
+fn sample_palette(palette: &[Color], t: f32) -> Color {
+  let scaled = t.clamp(0.0, 1.0) * (palette.len() - 1) as f32;
+  let index = (scaled.floor() as usize).min(palette.len() - 2);
+  palette[index].lerp(&palette[index + 1], scaled - index as f32)
+}
+
+// Planeta rocoso
+pub fn rocky_planet_shader(fragment: &Fragments, uniforms: &Uniforms, params: &ShaderParams) -> Color {
+  // Marrón rojizo oscuro, rojo rosado y salmón claro
+  let [base_color, mid_color, highlight_color, _] = params.colors;
 
-  // Patrón principal con variaciones añadidas
-  let noise = (((x + random_factor) * rock_scale).sin() * ((y + random_factor) * rock_scale).cos()).abs();
+  // Ruido fractal sobre la esfera unitaria, con crestas para las vetas de roca
+  let p = fragment.vertex_pos.normalize();
+  let base_noise = fbm(&(p * params.noise_scale), ROCKY_SEED, &Fractal::new(params.octaves, 2.0, 0.5)) * 0.5 + 0.5;
+  let ridges = ridged(&(p * 6.0), ROCKY_SEED + 1, &Fractal::new(3, 2.2, 0.5));
+  let noise = (base_noise * 0.7 + ridges * 0.3).clamp(0.0, 1.0);
 
   // Interpolación entre colores según el ruido
-  let rocky_surface = if noise < 0.4 {
-      base_color.lerp(&mid_color, noise / 0.4)
+  let split = params.threshold;
+  let rock = if noise < split {
+      base_color.lerp(&mid_color, noise / split)
   } else {
-      mid_color.lerp(&highlight_color, (noise - 0.4) / 0.6)
+      mid_color.lerp(&highlight_color, (noise - split) / (1.0 - split))
   };
 
-  // Depuración
-  rocky_surface * fragment.intensity
+  // Menos cráteres y más pequeños que en la luna: la erosión borró los viejos
+  let craters = ROCKY_CRATERS.get_or_init(|| CraterField::new(ROCKY_SEED + 2, 18, 0.05, 0.22));
+  shade_craters(rock, fragment, uniforms, craters)
 }
 
 // Luna (del planeta rocoso)
-pub fn moon_shader(fragment: &Fragments, _uniforms: &Uniforms) -> Color {
-  // Colores base para la luna
-  let base_color = Color::new(169, 169, 169);    // Gris
-  let mid_color = Color::new(190, 190, 190);     // Gris medio
-  let highlight_color = Color::new(211, 211, 211); // Gris claro
-
-  // Generar ruido para simular textura rocosa
-  let rock_scale = 12.0; // Escala mayor para patrones más finos
-  let detail_scale = 0.25; // Escala para detalles adicionales
-
-  // Coordenadas ajustadas con pseudoaleatoriedad
-  let x = fragment.vertex_pos.x;
-  let y = fragment.vertex_pos.y;
-  let randomness = (x * 15.789 + y * 41.233).sin() * 43758.5453;
-  let random_factor = randomness.fract() * detail_scale;
-
-  // Patrón principal de ruido
-  let noise = (((x + random_factor) * rock_scale).sin() * ((y + random_factor) * rock_scale).cos()).abs();
-
-  // Interpolar entre colores según el ruido
-  let rocky_surface = if noise < 0.5 {
-      base_color.lerp(&mid_color, noise / 0.5)
+pub fn moon_shader(fragment: &Fragments, uniforms: &Uniforms, params: &ShaderParams) -> Color {
+  // Gris, gris medio y gris claro
+  let [base_color, mid_color, highlight_color, _] = params.colors;
+
+  // Regolito: ruido fino sobre la esfera
+  let p = fragment.vertex_pos.normalize();
+  let noise = fbm(&(p * params.noise_scale), MOON_SEED, &Fractal::new(params.octaves, 2.0, 0.5)) * 0.5 + 0.5;
+  let split = params.threshold;
+  let rocky_surface = if noise < split {
+      base_color.lerp(&mid_color, noise / split)
   } else {
-      mid_color.lerp(&highlight_color, (noise - 0.5) / 0.5)
+      mid_color.lerp(&highlight_color, (noise - split) / (1.0 - split))
+  };
+
+  let craters = MOON_CRATERS.get_or_init(|| CraterField::new(MOON_SEED + 1, 30, 0.06, 0.32));
+  shade_craters(rocky_surface, fragment, uniforms, craters)
+}
+
+// Roca del cinturón: manchas grises sin cráteres, con la luz común. En la posición del
+// modelo y sin normalizar; las rocas lejanas pasan el punto del modelo que miran.
+fn asteroid_shader(fragment: &Fragments, params: &ShaderParams) -> Color {
+  let [dark_color, light_color, _, _] = params.colors;
+  let noise = fbm(&(fragment.vertex_pos * params.noise_scale), ASTEROID_SEED, &Fractal::new(params.octaves, 2.0, 0.5));
+  dark_color.lerp(&light_color, noise * 0.5 + 0.5)
+}
+
+// Núcleo de cometa: costra de polvo con manchas de hielo donde el ruido pasa el umbral
+fn comet_shader(fragment: &Fragments, params: &ShaderParams) -> Color {
+  let [dust_color, ice_color, _, _] = params.colors;
+  let p = fragment.vertex_pos.normalize();
+  let noise = fbm(&(p * params.noise_scale), COMET_SEED, &Fractal::new(params.octaves, 2.0, 0.5));
+  dust_color.lerp(&ice_color, smoothstep(params.threshold, params.threshold + 0.2, noise))
+}
+
+// Campos de cráteres: se generan la primera vez que se usan y se comparten entre hilos
+static MOON_CRATERS: OnceLock<CraterField> = OnceLock::new();
+static ROCKY_CRATERS: OnceLock<CraterField> = OnceLock::new();
+
+// Perfil de los cráteres, en radios del cráter: hasta dónde llega el borde, profundidad del
+// cuenco, altura y ancho del borde levantado, y cuánto inclina la normal el relieve
+const CRATER_REACH: f32 = 1.4;
+const CRATER_DEPTH: f32 = 1.0;
+const CRATER_RIM_HEIGHT: f32 = 0.3;
+const CRATER_RIM_WIDTH: f32 = 0.15;
+const CRATER_BUMP: f32 = 0.3;
+
+// Cuenco oscuro y borde claro sobre `color`, iluminado con la normal inclinada por el relieve:
+// la pared que mira al sol brilla y la opuesta queda en sombra
+fn shade_craters(color: Color, fragment: &Fragments, uniforms: &Uniforms, craters: &CraterField) -> Color {
+  let p = fragment.vertex_pos.normalize();
+  let (bowl, rim, normal) = match craters.nearest(&p, CRATER_REACH) {
+    Some(hit) => {
+      let s = hit.distance;
+      let rim = (-((s - 1.0) / CRATER_RIM_WIDTH).powi(2)).exp();
+      let bowl = (1.0 - s * s).max(0.0);
+
+      // Pendiente radial de la altura: cuenco parabólico más una campana en el borde
+      let bowl_slope = if s < 1.0 { 2.0 * s * CRATER_DEPTH } else { 0.0 };
+      let rim_slope = -2.0 * (s - 1.0) / CRATER_RIM_WIDTH.powi(2) * CRATER_RIM_HEIGHT * rim;
+      let slope = (bowl_slope + rim_slope) * CRATER_BUMP;
+
+      // La dirección está en espacio del modelo; se lleva al mundo como la normal
+      let tangent = (mat4_to_mat3(&uniforms.model_matrix) * hit.direction).try_normalize(f32::EPSILON).unwrap_or(Vec3::zeros());
+      let normal = (fragment.normal - tangent * slope).try_normalize(f32::EPSILON).unwrap_or(fragment.normal);
+      (bowl, rim, normal)
+    }
+    None => (0.0, 0.0, fragment.normal),
   };
 
-  // Configuración de cráteres
-  let crater_positions = [
-      (0.1, 0.2, 0.50), 
-      (-0.3, -0.1, 0.30),
-      (0.4, -0.3, 0.2), 
-      (-0.1, 0.5, 0.40),
-      (-0.5, -0.4, 0.25),
-      (0.3, 0.4, 0.35),
-      (0.1, 0.5, 0.20),
-      (0.2, -0.1, 0.25),
-      (0.0, -0.6, 0.28), 
-      (-0.4, 0.2, 0.22),
-      (0.5, 0.0, 0.30),  
-      (-0.2, -0.5, 0.18), 
-      (0.35, 0.5, 0.24),
-      (-0.45, -0.3, 0.20),
-  ];
-
-  let crater_color = Color::new(100, 100, 100); // Gris oscuro para los cráteres
-
-  // Combinar intensidades de todos los cráteres
-  let mut combined_crater_intensity = 0.0;
-  for &(cx, cy, radius) in crater_positions.iter() {
-      let distance = ((fragment.vertex_pos.x - cx).powi(2)
-          + (fragment.vertex_pos.y - cy).powi(2))
-          .sqrt();
-      let crater_intensity = ((radius - distance).max(0.0f32) / radius).powf(3.0);
-      combined_crater_intensity += crater_intensity;
-  }
-
-  // Aplicar la intensidad de los cráteres a la superficie
-  let final_surface = rocky_surface.lerp(&crater_color, combined_crater_intensity);
-
-  // Multiplicar por la intensidad para iluminación
-  final_surface * fragment.intensity
-}
-
-// Movimiento orbital de la luna
-pub fn moon_position(time: f32, radius: f32) -> Vec3 {
-  let angle = time * 0.01;
-  Vec3::new(radius * angle.cos(), 0.0, radius * angle.sin())
+  let surface = color.lerp(&(color * 0.55), bowl * 0.8).lerp(&(color * 1.2), rim * 0.4);
+  let relief = Fragments { normal, ..*fragment };
+
+  // Depuración
+  match uniforms.debug_mode {
+      DEBUG_LAYER_1 => color,                                                       // Solo la roca
+      DEBUG_LAYER_2 => Color::new(255, 255, 255) * (rim * 0.5 + bowl * 0.5),        // Solo cráteres
+      DEBUG_LAYER_3 => sun_lighting(Color::new(255, 255, 255), &relief, uniforms, 0.0), // Solo el relieve
+      _ => sun_lighting(surface, &relief, uniforms, 0.0),                           // Shader completo
+  }
+}
+
+// Movimiento orbital de una luna respecto a su planeta: un círculo recorrido a `speed`
+// radianes por paso desde `phase`, con el plano inclinado `inclination` (en radianes)
+// alrededor del eje X
+pub fn moon_position(time: f32, radius: f32, speed: f32, inclination: f32, phase: f32) -> Vec3 {
+  let angle = moon_orbit_angle(time, speed, phase);
+  let (x, z) = (radius * angle.cos(), radius * angle.sin());
+  let (sin_i, cos_i) = inclination.sin_cos();
+  Vec3::new(x, -z * sin_i, z * cos_i)
+}
+
+pub fn moon_orbit_angle(time: f32, speed: f32, phase: f32) -> f32 {
+  phase + time * speed
 }
 
 // planeta con anillos
-pub fn ring_planet_shader(fragment: &Fragments, uniforms: &Uniforms) -> Color {
-  let band_color1 = Color::new(189, 155, 107); // Marrón claro
-  let band_color2 = Color::new(210, 180, 140); // Beige
-  let band_color3 = Color::new(255, 222, 173); // Crema
+pub fn ring_planet_shader(fragment: &Fragments, uniforms: &Uniforms, params: &ShaderParams) -> Color {
+  // Marrón claro, beige y crema
+  let [band_color1, band_color2, band_color3, _] = params.colors;
 
-  // Franjas horizontales
-  let band_scale = 3.5; // Ajusta el número de franjas
+  // Franjas horizontales; la escala ajusta el número de franjas
+  let band_scale = params.noise_scale;
   let flow_speed = 0.0008; // Movimiento más lento que Júpiter
-  let flow_offset = uniforms.time as f32 * flow_speed;
+  let flow_offset = uniforms.time * flow_speed;
   let y_position = fragment.vertex_pos.y + flow_offset;
   let band_factor = ((y_position * band_scale).sin() * 0.5 + 0.5).fract();
 
@@ -339,113 +1037,238 @@ pub fn ring_planet_shader(fragment: &Fragments, uniforms: &Uniforms) -> Color {
 
   // Depuración
   match uniforms.debug_mode {
-      1 => band_color * fragment.intensity, // Solo las franjas
-      _ => band_color * fragment.intensity, // Shader completo
+      DEBUG_LAYER_1 => band_color * fragment.intensity, // Solo las franjas
+      _ => band_color, // Shader completo
   }
 }
 
-// Anillos
-fn ring_shader(fragment: &Fragments, uniforms: &Uniforms) -> Color {
-  // Colores base para el anillo
-  let base_color = Color::new(255, 220, 80); // Amarillo
-  let shadow_color = Color::new(150, 120, 60); // Sombra
+// Radios interior y exterior del anillo en espacio del modelo (los de la malla generada)
+pub const RING_INNER_RADIUS: f32 = 1.49;
+pub const RING_OUTER_RADIUS: f32 = 2.87;
+// Huecos transparentes, en fracción del ancho del anillo (la división de Cassini es la grande)
+const RING_GAPS: [(f32, f32); 3] = [(0.58, 0.64), (0.30, 0.315), (0.90, 0.915)];
+// Opacidad de las bandas más tenues y más densas, y ancho del desvanecido en los bordes
+const RING_MIN_ALPHA: f32 = 0.3;
+const RING_MAX_ALPHA: f32 = 0.95;
+const RING_EDGE_FADE: f32 = 0.06;
+
+// Anillos: bandas concéntricas con huecos descartados y la sombra del planeta
+fn ring_shader(fragment: &Fragments, uniforms: &Uniforms, params: &ShaderParams) -> Option<Color> {
+  let [base_color, shadow_color, _, _] = params.colors; // Amarillo y sombra
+
+  // Distancia radial en espacio del modelo: no depende de dónde esté el planeta
+  let radius = Vec2::new(fragment.vertex_pos.x, fragment.vertex_pos.z).norm();
+  let t = ((radius - RING_INNER_RADIUS) / (RING_OUTER_RADIUS - RING_INNER_RADIUS)).clamp(0.0, 1.0);
+
+  if RING_GAPS.iter().any(|&(start, end)| t > start && t < end) {
+    return None;
+  }
+
+  // Brillo de las bandas con ruido a lo largo del radio
+  let bands = perlin(&Vec3::new(t * params.noise_scale, 0.0, 0.0), RING_SEED) * 0.5 + perlin(&Vec3::new(t * 70.0, 0.5, 0.0), RING_SEED + 1) * 0.2;
+  let brightness = (0.7 + bands).clamp(0.0, 1.0);
+  let band_color = shadow_color.lerp(&base_color, brightness);
 
-  // Interpolación de colores
-  let surface_color = base_color;
+  // sun_lighting ya oscurece lo que está en la sombra del planeta
+  let lit = sun_lighting(band_color, fragment, uniforms, 0.0);
+  let shadow = planet_shadow(fragment, uniforms);
 
-  // Iluminación básica para simular sombras
-  let light_direction = Vec3::new(1.0, 1.0, 1.0).normalize(); // Dirección de la luz
-  let normal = fragment.vertex_pos.normalize(); // Normal del fragmento
-  let light_intensity = (normal.dot(&light_direction)).clamp(0.2, 1.0); // Intensidad de la luz
+  // Las bandas densas tapan más; los bordes se desvanecen en vez de cortarse
+  let edges = smoothstep(0.0, RING_EDGE_FADE, t) * smoothstep(1.0, 1.0 - RING_EDGE_FADE, t);
+  let alpha = (RING_MIN_ALPHA + (RING_MAX_ALPHA - RING_MIN_ALPHA) * brightness) * edges;
 
   // Lógica de depuración
-  let final_color = match uniforms.debug_mode {
-      1 => base_color * fragment.intensity,                                                 // Solo el color base
-      _ => surface_color * light_intensity + shadow_color * (1.0 - light_intensity),      // Shader completo
+  Some(match uniforms.debug_mode {
+      DEBUG_LAYER_1 => base_color,                                  // Solo el color base
+      DEBUG_LAYER_2 => Color::new(255, 255, 255) * brightness,      // Solo las bandas
+      DEBUG_LAYER_3 => Color::new(255, 255, 255) * (1.0 - shadow),  // Solo la sombra
+      _ => lit.with_alpha(alpha),                                   // Shader completo
+  })
+}
+
+// 1 si alguna esfera de `uniforms.shadow_spheres` tapa el sol desde este fragmento, con una
+// penumbra suave según cuánto falla el rayo hacia el sol. El fragmento está en sombra si
+// queda detrás de la esfera respecto al sol y a menos de su radio del eje esfera-sol; con
+// varias, la luz que deja pasar cada una se multiplica.
+// Ensanchamiento de la penumbra por unidad de distancia detrás de la esfera (aprox. el
+// radio angular del sol visto desde los planetas)
+const PENUMBRA_SPREAD: f32 = 0.1;
+
+fn planet_shadow(fragment: &Fragments, uniforms: &Uniforms) -> f32 {
+  let Some(light_dir) = (uniforms.light_position - fragment.world_position).try_normalize(f32::EPSILON) else {
+    return 0.0;
   };
+  let light: f32 = uniforms.shadow_spheres.iter()
+    .map(|&(center, radius)| 1.0 - sphere_shadow(&fragment.world_position, &light_dir, &center, radius))
+    .product();
+  1.0 - light
+}
 
-  final_color
+fn sphere_shadow(position: &Vec3, light_dir: &Vec3, center: &Vec3, radius: f32) -> f32 {
+  let offset = position - center;
+  let along = dot(&offset, light_dir);
+  if along >= 0.0 {
+    return 0.0;
+  }
+  // El sol no es un punto: la penumbra se ensancha cuanto más lejos de la esfera cae la sombra
+  let distance_to_axis = (offset - light_dir * along).magnitude();
+  let penumbra = radius * 0.08 - along * PENUMBRA_SPREAD;
+  1.0 - smoothstep(radius - penumbra, radius + penumbra, distance_to_axis)
 }
 
 // Planeta Tierra
-pub fn earth_shader(fragment: &Fragments, uniforms: &Uniforms) -> Color {
-  let x = fragment.vertex_pos.x;
-  let y = fragment.vertex_pos.y;
-  let z = fragment.vertex_pos.z;
-
-  // Coordenadas esféricas
-  let theta = (y / 0.5).asin(); // Latitud
-  let phi = z.atan2(x);         // Longitud
-  let u = (phi / (2.0 * PI)) + 0.5; // Coordenada u [0, 1]
-  let v = (theta / PI) + 0.5;      // Coordenada v [0, 1]
-
-  let scale = 7.2;
-  let noise = ((u * scale).sin() * (v * scale).cos()).abs();
-  let continent_threshold = 0.55;
-
-  let land_color = Color::new(34, 139, 34); // Verde para los continentes
-  let ocean_color = Color::new(0, 105, 148); // Azul para el océano
-  let base_color = if noise > continent_threshold { land_color } else { ocean_color };
-
-  // Parámetros de las nubes
-  let time = uniforms.time as f32 * 0.01; // Escala temporal para el movimiento de las nubes
-  let cloud_scale = 8.0;                 // Escala de dispersión de las nubes
-  let cloud_intensity = ((u * cloud_scale + time).sin() * (v * cloud_scale + time).cos()).abs();
-  let cloud_intensity = (cloud_intensity - 0.5).clamp(0.0, 1.0) * 0.5; // Intensidad y opacidad de las nubes
-
-  let cloud_color = Color::new(255, 255, 255); // Blanco para las nubes
-
-  // Crear círculos de nubes (en movimiento)
-  let cloud_radius = 1.5; // Radio máximo de la atmósfera con nubes
-  let distance_from_center = Vec2::new(u, v).norm(); // Distancia del centro para determinar si está dentro de la atmósfera
-  let is_in_atmosphere = distance_from_center < cloud_radius;
-
-  // Reducir el número de círculos de nubes y hacerlos más pequeños
-  let num_clouds = 6; // Menor número de círculos de nubes
-  let mut cloud_positions = Vec::new();
-
-  for i in 0..num_clouds {
-      let angle = (i as f32 / num_clouds as f32) * 2.0 * PI + time * 0.2; // Movimiento en el tiempo
-      let radius = 0.2 + (i as f32 * 0.05); // Radios de los círculos
-      let x_pos = (angle.cos() * radius + 0.5) % 1.0; // Posición en u
-      let y_pos = (angle.sin() * radius + 0.5) % 1.0; // Posición en v
-      cloud_positions.push(Vec2::new(x_pos, y_pos));
-  }
-
-  // Dibujar las nubes en círculos
-  let mut cloud_color_final = Color::new(0, 0, 0); // Comienza con un color negro
-  for cloud_pos in cloud_positions.iter() {
-      let frag_position = Vec2::new(u, v);
-      let distance_to_cloud = (frag_position - *cloud_pos).norm(); // Distancia a cada círculo de nube
-      let cloud_radius = 0.075; // Radio más pequeño para los círculos de nubes
-      let is_in_cloud = distance_to_cloud < cloud_radius;
-
-      // Si el fragmento está dentro de un círculo de nube, añade su color
-      if is_in_cloud {
-          cloud_color_final = cloud_color_final.lerp(&cloud_color, 0.7); // Aumentamos la mezcla para que sea más blanco
-      }
-  }
-
-  // Determinar el color final
-  let final_color = if is_in_atmosphere {
-      // Mezclar nubes y superficie
-      base_color * (1.0 - cloud_intensity) + cloud_color_final
+pub fn earth_shader(fragment: &Fragments, uniforms: &Uniforms, params: &ShaderParams) -> Color {
+  let p = fragment.vertex_pos.normalize();
+
+  // Continentes con ruido fractal sobre la esfera unitaria
+  let continents = fbm(&(p * params.noise_scale), EARTH_SEED, &Fractal::new(params.octaves, 2.0, 0.5));
+  let continent_threshold = params.threshold;
+
+  // Verde para los continentes, azul para el océano, nubes y luces de ciudades
+  let [land_color, ocean_color, cloud_color, city_color] = params.colors;
+  let ice = ice_caps(&ShaderType::Earth).map_or(0.0, |caps| ice_cover(&p, uniforms, &caps, EARTH_SEED + 17));
+  let ground_color = if continents > continent_threshold { land_color } else { ocean_color };
+  let base_color = ground_color.lerp(&ICE_CAP_COLOR, ice);
+
+  // Nubes: se leen sobre la esfera girada alrededor del eje Y, más rápido que la superficie.
+  // Al ser ruido 3D no se amontonan en los polos como pasaría con coordenadas u/v.
+  let cloud_angle = cycle_angle(uniforms.time, CLOUD_PERIOD);
+  let cloud_p = rotate_y(&p, cloud_angle);
+  let cloud_noise = fbm(&(cloud_p * 3.0), EARTH_SEED + 7, &Fractal::new(5, 2.0, 0.55)) * 0.5 + 0.5;
+  let coverage = smoothstep(CLOUD_THRESHOLD, CLOUD_THRESHOLD + 0.15, cloud_noise) * 0.85;
+
+  // Las nubes reciben la luz del sol: brillantes de día, invisibles de noche
+  let specular = params.specular + (ICE_CAP_SPECULAR - params.specular) * ice;
+  let surface = sun_lighting(base_color.lerp(&cloud_color, coverage), fragment, uniforms, specular);
+
+  // Luces de ciudades: sólo en tierra firme fuera de los casquetes, apareciendo de forma
+  // suave al cruzar el terminador. Las nubes las tapan.
+  let is_land = continents > continent_threshold && ice < 0.5;
+  let city_lights = if is_land {
+    let sun_dir = (uniforms.light_position - fragment.world_position).try_normalize(f32::EPSILON).unwrap_or(fragment.normal);
+    let edge = (TERMINATOR_SOFTNESS.to_radians() * 0.5).sin();
+    let night = smoothstep(edge, -edge, dot(&fragment.normal, &sun_dir));
+    sparkle(&(p * CITY_DENSITY), EARTH_SEED + 13, 0.35, 0.45) * night * (1.0 - coverage) * params.emissive
   } else {
-      base_color
+    0.0
   };
+  let aurora_color = aurora(&ShaderType::Earth).map_or(Color::new(0, 0, 0), |a| aurora_light(fragment, uniforms, &a));
 
-  final_color
+  match uniforms.debug_mode {
+      DEBUG_LAYER_1 => base_color,                        // Solo continentes
+      DEBUG_LAYER_2 => cloud_color * coverage,            // Solo nubes
+      DEBUG_LAYER_3 => city_color * city_lights,          // Solo luces de ciudades
+      _ => surface + city_color * city_lights + aurora_color, // Shader completo
+  }
 }
 
-// Movimiento orbital de los planetas
-pub fn planet_orbit(time: f32, radius: f32, speed: f32) -> Vec3 {
-  let angle = time * speed; // Velocidad angular ajusta la rapidez de la órbita
-  Vec3::new(radius * angle.cos(), 0.0, radius * angle.sin())
+// Ancho en grados de la transición día/noche para las luces de ciudades
+const TERMINATOR_SOFTNESS: f32 = 12.0;
+// Celdas de ruido por unidad de radio: más alto, ciudades más pequeñas y numerosas
+const CITY_DENSITY: f32 = 40.0;
+
+// Pasos del reloj por vuelta completa de las nubes
+const CLOUD_PERIOD: u32 = 16384;
+// Valor del ruido desde el que empieza a haber nube
+const CLOUD_THRESHOLD: f32 = 0.55;
+
+fn rotate_y(p: &Vec3, angle: f32) -> Vec3 {
+  let (sin, cos) = angle.sin_cos();
+  Vec3::new(p.x * cos + p.z * sin, p.y, -p.x * sin + p.z * cos)
 }
 
-// Shader para la nave
-pub fn ship_shader(fragment: &Fragments, _uniforms: &Uniforms) -> Color {
-  // Pinta la nave completamente de blanco
-  let white = Color::new(255, 255, 255); // Blanco
-  white * fragment.intensity // Ajuste de intensidad
+pub fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+  let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+  t * t * (3.0 - 2.0 * t)
+}
+
+// Ángulo en [0, 2π) del instante `time` (en pasos del reloj) dentro de un ciclo de `period`
+// pasos; el tiempo puede ser negativo si la simulación corre hacia atrás
+pub fn cycle_angle(time: f32, period: u32) -> f32 {
+  time.rem_euclid(period as f32) / period as f32 * 2.0 * PI
+}
+
+// Planeta con textura. Las uv de sphere.obj son de relleno (cada triángulo va de 0 a 1), así
+// que se calculan por fragmento a partir de la posición en el modelo: al no interpolarse entre
+// vértices, no hay un triángulo que cruce la costura con u saltando de 1 a 0.
+pub fn textured_shader(fragment: &Fragments, uniforms: &Uniforms) -> Color {
+  let Some(texture) = uniforms.texture else {
+    return Color::new(255, 0, 255); // Magenta: falta la textura
+  };
+  let uv = sphere_uv(&fragment.vertex_pos);
+
+  // Depuración
+  match uniforms.debug_mode {
+      DEBUG_LAYER_1 => Color::new((uv.x * 255.0) as u8, (uv.y * 255.0) as u8, 0),            // Coordenadas uv
+      DEBUG_LAYER_2 => texture.sample(&uv, &Sampler { filter: Filter::Nearest, ..Sampler::SPHERE }), // Sin filtrar
+      _ => texture.sample(&uv, &Sampler::SPHERE),                                             // Shader completo
+  }
+}
+
+// Longitud y latitud de un punto de la esfera como uv: u crece hacia el este desde el
+// meridiano -X, v = 0 en el polo norte
+pub fn sphere_uv(p: &Vec3) -> Vec2 {
+  let p = p.try_normalize(f32::EPSILON).unwrap_or(Vec3::new(1.0, 0.0, 0.0));
+  let u = 0.5 + (-p.z).atan2(p.x) / (2.0 * PI);
+  let v = 0.5 - p.y.clamp(-1.0, 1.0).asin() / PI;
+  Vec2::new(u, v)
+}
+
+// Nave metálica: reflectividad del cielo
+const SHIP_REFLECTIVITY: f32 = 0.6;
+// Coordenada x (en el modelo normalizado) desde la que empieza la zona del motor, en la cola
+const EXHAUST_START: f32 = -0.294;
+const EXHAUST_END: f32 = -0.413;
+
+pub fn ship_shader(fragment: &Fragments, uniforms: &Uniforms, params: &ShaderParams) -> Color {
+  // Gris metálico, juntas de los paneles, reflejo de las estrellas y azul del motor
+  let [metal_color, panel_color, sky_color, exhaust_color] = params.colors;
+  // Con material del .mtl el casco toma su color difuso, su brillo y su emisión; sin él,
+  // los de los parámetros
+  let material = fragment.material.and_then(|index| uniforms.materials.get(index as usize));
+  let metal_color = material.map_or(metal_color, |material| material.diffuse);
+  let (specular_color, shininess) = match material {
+    Some(material) if material.shininess > 0.0 => (material.specular, material.shininess),
+    _ => (Color::new(255, 255, 255), params.shininess),
+  };
+  let emission = material.map_or(Color::BLACK, |material| material.emissive);
+
+  // Juntas de los paneles donde el ruido cruza por cero: líneas finas y conectadas
+  let panel_noise = perlin(&(fragment.vertex_pos * params.noise_scale), SHIP_SEED).abs();
+  let panel_line = 1.0 - smoothstep(0.01, 0.03, panel_noise);
+  let hull = metal_color.lerp(&panel_color, panel_line);
+
+  let normal = fragment.normal;
+  let view_dir = (uniforms.camera_position - fragment.world_position).try_normalize(f32::EPSILON).unwrap_or(normal);
+  let light_dir = (uniforms.light_position - fragment.world_position).try_normalize(f32::EPSILON).unwrap_or(normal);
+
+  // Blinn-Phong fuerte: el metal refleja casi todo el sol en un punto
+  let half_dir = (light_dir + view_dir).try_normalize(f32::EPSILON).unwrap_or(normal);
+  let specular = if dot(&normal, &light_dir) > 0.0 {
+    toon_specular(dot(&normal, &half_dir).max(0.0).powf(shininess), uniforms) * params.specular
+  } else {
+    0.0
+  };
+
+  // Reflejo falso del cielo: el rayo de vista reflejado en la normal lee la densidad de estrellas.
+  // El término de fresnel refuerza el reflejo en los ángulos rasantes.
+  let reflect_dir = 2.0 * dot(&normal, &view_dir) * normal - view_dir;
+  let sky = uniforms.skybox.map_or(0.0, |skybox| skybox.sample(&reflect_dir));
+  let fresnel = 0.3 + 0.7 * (1.0 - dot(&normal, &view_dir).max(0.0)).powi(3);
+  let reflection = sky * fresnel * SHIP_REFLECTIVITY;
+
+  // El motor brilla con el empuje, con un parpadeo leve
+  let exhaust = smoothstep(EXHAUST_START, EXHAUST_END, fragment.vertex_pos.x);
+  let flicker = 0.85 + 0.15 * (cycle_angle(uniforms.time, 64) * 8.0).sin();
+  let glow = exhaust * uniforms.thrust * flicker;
+
+  let lit = sun_lighting(hull, fragment, uniforms, 0.0);
+
+  // Depuración
+  match uniforms.debug_mode {
+      DEBUG_LAYER_1 => hull,                                        // Solo el casco y los paneles
+      DEBUG_LAYER_2 => sky_color * reflection,                      // Solo el reflejo del cielo
+      DEBUG_LAYER_3 => exhaust_color * glow,                        // Solo el motor
+      _ => lit + emission + specular_color * specular + sky_color * reflection + exhaust_color * (glow * params.emissive), // Shader completo
+  }
 }
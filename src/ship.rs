@@ -0,0 +1,83 @@
+use std::time::{Duration, Instant};
+use nalgebra_glm::{Vec3, Mat4, translation, scaling, quat_to_mat4};
+use crate::camera::{Camera, ease_in_out_cubic};
+
+/// Duración de la transición entre vistas
+const VIEW_TRANSITION: Duration = Duration::from_millis(500);
+/// Envergadura de la nave en el mundo: el modelo llega normalizado, con su lado más largo
+/// (las alas) de 1 y apoyado en el origen
+const SHIP_SCALE: f32 = 5.9;
+/// En tercera persona la nave va por debajo de la línea de vista para no tapar el centro
+const THIRD_PERSON_OFFSET: Vec3 = Vec3::new(0.0, -5.0, 0.0);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ViewMode {
+    ThirdPerson,
+    FirstPerson,
+}
+
+/// Pose de la nave. La cámara es el ojo del piloto: en primera persona está en la nariz
+/// de la nave y en tercera persona la nave va `distance` unidades delante de ella.
+pub struct Ship {
+    pub view_mode: ViewMode,
+    /// Distancia delante de la cámara en tercera persona
+    pub distance: f32,
+    /// Desplazamiento en ejes de la cámara (x derecha, y arriba, z adelante) en tercera persona
+    pub offset: Vec3,
+    // Inicio de la transición y mezcla de la que parte (1 = tercera persona)
+    transition: Option<(Instant, f32)>,
+}
+
+impl Ship {
+    pub fn new(distance: f32) -> Self {
+        Ship {
+            view_mode: ViewMode::ThirdPerson,
+            distance,
+            offset: THIRD_PERSON_OFFSET,
+            transition: None,
+        }
+    }
+
+    /// Alterna la vista; el cambio se anima en lugar de cortar
+    pub fn toggle_view(&mut self) {
+        let from = self.blend();
+        self.view_mode = match self.view_mode {
+            ViewMode::ThirdPerson => ViewMode::FirstPerson,
+            ViewMode::FirstPerson => ViewMode::ThirdPerson,
+        };
+        self.transition = Some((Instant::now(), from));
+    }
+
+    // 1 en tercera persona, 0 en primera, con valores intermedios durante la transición
+    fn blend(&self) -> f32 {
+        let target = match self.view_mode {
+            ViewMode::ThirdPerson => 1.0,
+            ViewMode::FirstPerson => 0.0,
+        };
+        let Some((started, from)) = self.transition else { return target };
+
+        let t = (started.elapsed().as_secs_f32() / VIEW_TRANSITION.as_secs_f32()).min(1.0);
+        from + (target - from) * ease_in_out_cubic(t)
+    }
+
+    /// En primera persona la nave no se dibuja (la cámara está dentro de ella)
+    pub fn is_visible(&self) -> bool {
+        self.blend() > 0.0
+    }
+
+    pub fn position(&self, camera: &Camera) -> Vec3 {
+        let local = Vec3::new(self.offset.x, self.offset.y, self.offset.z + self.distance) * self.blend();
+        camera.eye + camera.right() * local.x + camera.up() * local.y + camera.forward() * local.z
+    }
+
+    /// La nave apunta hacia donde mira la cámara
+    pub fn model_matrix(&self, camera: &Camera) -> Mat4 {
+        // El modelo viene girado 90 grados alrededor de Y respecto a la dirección de vuelo
+        let model_rotation = Mat4::new_rotation(Vec3::new(0.0, 90.0_f32.to_radians(), 0.0));
+
+        translation(&self.position(camera))
+            * quat_to_mat4(&camera.orientation())
+            * scaling(&Vec3::new(SHIP_SCALE, SHIP_SCALE, SHIP_SCALE))
+            * model_rotation
+    }
+}
@@ -0,0 +1,65 @@
+// Pasos de simulación por segundo real a velocidad 1x. Las velocidades de la escena y los
+// periodos de los shaders se cuentan en pasos, que antes eran los frames a 60 FPS.
+pub const TICKS_PER_SECOND: f64 = 60.0;
+// Límites del multiplicador (en valor absoluto), que +/- duplican o dividen a la mitad
+const MIN_SPEED: f64 = 1.0 / 64.0;
+const MAX_SPEED: f64 = 1024.0;
+
+/// Reloj de la simulación: avanza con el tiempo real por el multiplicador, que puede ser
+/// negativo para que todo corra hacia atrás. La pausa no cambia el multiplicador.
+pub struct SimClock {
+    time: f64,
+    speed: f64,
+    paused: bool,
+}
+
+impl SimClock {
+    pub fn new() -> Self {
+        SimClock { time: 0.0, speed: 1.0, paused: false }
+    }
+
+    /// Avanza `delta` segundos reales; devuelve los segundos de simulación que pasaron
+    /// (negativos en reversa, 0 en pausa)
+    pub fn advance(&mut self, delta: f32) -> f32 {
+        if self.paused {
+            return 0.0;
+        }
+        let elapsed = delta as f64 * self.speed;
+        self.time += elapsed * TICKS_PER_SECOND;
+        elapsed as f32
+    }
+
+    /// Tiempo de la simulación en pasos
+    pub fn time(&self) -> f32 {
+        self.time as f32
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    pub fn faster(&mut self) {
+        self.set_magnitude(self.speed.abs() * 2.0);
+    }
+
+    pub fn slower(&mut self) {
+        self.set_magnitude(self.speed.abs() / 2.0);
+    }
+
+    pub fn reverse(&mut self) {
+        self.speed = -self.speed;
+    }
+
+    fn set_magnitude(&mut self, magnitude: f64) {
+        self.speed = magnitude.clamp(MIN_SPEED, MAX_SPEED).copysign(self.speed);
+    }
+
+    /// Multiplicador para el HUD, como "x2", "x1/4" o "-x8"; en pausa lo dice
+    pub fn label(&self) -> String {
+        let magnitude = self.speed.abs();
+        let sign = if self.speed < 0.0 { "-" } else { "" };
+        let value = if magnitude >= 1.0 { format!("{}", magnitude) } else { format!("1/{}", 1.0 / magnitude) };
+        let paused = if self.paused { " (pausa)" } else { "" };
+        format!("{}x{}{}", sign, value, paused)
+    }
+}
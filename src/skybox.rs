@@ -1,97 +1,876 @@
-use nalgebra_glm::{Vec3, Vec4};
+use nalgebra_glm::{Vec2, Vec3, Vec4, cross, dot, rotate_vec3};
+use rand::distributions::WeightedIndex;
 use rand::prelude::*;
+use rand::rngs::StdRng;
 use std::f32::consts::PI;
 use crate::{Framebuffer, Uniforms};
+use crate::color::{Color, srgb_factor};
+use crate::noise::{Fractal, fbm, perlin};
+use crate::shaders::{cycle_angle, smoothstep, sphere_uv};
+use crate::texture::{Sampler, Texture};
+
+// Centelleo: pasos del reloj por ciclo, variación máxima del brillo de las
+// estrellas más débiles y brillo mínimo relativo, para que nunca se apaguen del todo
+const TWINKLE_PERIOD: u32 = 4096;
+const TWINKLE_AMOUNT: f32 = 0.45;
+const TWINKLE_FLOOR: f32 = 0.3;
+// Parpadeos ocasionales: radio del círculo que recorre el ruido en un periodo (más grande,
+// parpadeos más frecuentes) y semilla
+const FLICKER_RADIUS: f32 = 20.0;
+const FLICKER_SEED: u32 = 131;
+
+// Resolución del mapa de densidad de estrellas (longitud x latitud) que usan los reflejos
+const DENSITY_WIDTH: usize = 64;
+const DENSITY_HEIGHT: usize = 32;
+
+// Mapa de las nebulosas (longitud x latitud): se hornea al crear el skybox y por pixel sólo
+// se interpola
+const NEBULA_WIDTH: usize = 256;
+const NEBULA_HEIGHT: usize = 128;
+// Radio angular (radianes) de las nebulosas, brillo máximo y ruido de las nubes
+const NEBULA_MIN_RADIUS: f32 = 0.35;
+const NEBULA_MAX_RADIUS: f32 = 0.7;
+const NEBULA_BRIGHTNESS: f32 = 0.45;
+const NEBULA_FRACTAL: Fractal = Fractal::new(5, 2.0, 0.5);
+const NEBULA_SCALE: f32 = 3.0;
+
+// Objetos de cielo profundo: pixeles del sprite por radián de tamaño angular (con el FOV por
+// defecto, aproximadamente) y lado máximo del sprite
+const DEEP_SKY_PIXELS_PER_RADIAN: f32 = 650.0;
+const DEEP_SKY_MAX_SIDE: usize = 41;
+// Estrellas sueltas sobre el núcleo de un cúmulo
+const CLUSTER_STARS: usize = 40;
+// Resplandor del sol: a menos de esta fracción de la altura de la pantalla los objetos se apagan
+const GLARE_INNER: f32 = 0.1;
+const GLARE_OUTER: f32 = 0.45;
+
+/// Opciones del cielo de estrellas procedural
+#[derive(Debug, Clone)]
+pub struct SkyboxConfig {
+    /// Peso relativo de cada clase espectral, en el orden de SPECTRAL_TINTS (O, B, A, F, G, K, M)
+    pub class_weights: [f32; 7],
+    /// Normal (en el mundo) del plano de la Vía Láctea; None reparte las estrellas sin banda
+    pub galactic_plane: Option<Vec3>,
+    /// Desviación angular (radianes) de la caída gaussiana de la banda
+    pub band_width: f32,
+    /// Puntos tenues de la bruma que se dibuja a lo largo de la banda
+    pub haze_points: usize,
+    /// Cantidad de nebulosas repartidas al azar por el cielo
+    pub nebulae: usize,
+    /// Pares de colores de las nebulosas; cada una toma uno al azar y mezcla entre ambos
+    pub nebula_colors: Vec<(Color, Color)>,
+    /// Segundos promedio entre estrellas fugaces; 0 las desactiva
+    pub meteor_interval: f32,
+    /// Cantidad de galaxias lejanas (elípticas y espirales)
+    pub galaxies: usize,
+    /// Cantidad de cúmulos globulares
+    pub clusters: usize,
+    /// Tamaño angular (radianes) mínimo y máximo de galaxias y cúmulos
+    pub deep_sky_size: (f32, f32),
+    /// Brillo máximo del centro de galaxias y cúmulos
+    pub deep_sky_brightness: f32,
+    /// Color de las galaxias y de los cúmulos
+    pub galaxy_tint: Color,
+    pub cluster_tint: Color,
+}
+
+impl Default for SkyboxConfig {
+    /// Muchas enanas rojas y casi ninguna gigante azul, aproximadamente como en la galaxia
+    /// (con las clases calientes algo infladas, si no no se vería ninguna), sin banda
+    fn default() -> Self {
+        SkyboxConfig {
+            class_weights: [0.002, 0.01, 0.03, 0.06, 0.1, 0.2, 0.6],
+            galactic_plane: None,
+            band_width: 0.2,
+            haze_points: 12000,
+            nebulae: 0,
+            nebula_colors: vec![
+                (Color::new(200, 40, 160), Color::new(40, 60, 210)), // Magenta / azul
+                (Color::new(30, 170, 170), Color::new(50, 190, 70)), // Turquesa / verde
+            ],
+            meteor_interval: 6.0,
+            galaxies: 0,
+            clusters: 0,
+            deep_sky_size: (0.02, 0.05),
+            deep_sky_brightness: 0.6,
+            galaxy_tint: Color::new(255, 235, 200),
+            cluster_tint: Color::new(255, 245, 215),
+        }
+    }
+}
+
+// Estrellas fugaces: semilla de sus sorteos con un panorama (que no tiene semilla propia),
+// duración (segundos), arco que recorren (radianes), fracción del arco que ocupa la estela y
+// distancia a la que se proyectan
+const METEOR_SEED: u64 = 137;
+const METEOR_LIFETIME: f32 = 0.5;
+const METEOR_MIN_ARC: f32 = 0.15;
+const METEOR_MAX_ARC: f32 = 0.35;
+const METEOR_TRAIL: f32 = 0.4;
+const METEOR_DISTANCE: f32 = 100.0;
+const METEOR_COLOR: Color = Color::new(255, 245, 225);
+
+// Fracción de las estrellas lejos de la banda que se conservan (el resto se vuelve a sortear)
+const BAND_FLOOR: f32 = 0.15;
+// Brillo máximo de un punto de la bruma
+const HAZE_BRIGHTNESS: f32 = 0.25;
+
+// Banda de la Vía Láctea: plano por el centro de la esfera y caída gaussiana a su alrededor
+struct GalacticBand {
+    normal: Vec3,
+    width: f32,
+}
+
+impl GalacticBand {
+    // Densidad relativa en [0, 1] según la distancia angular al plano
+    fn weight(&self, dir: &Vec3) -> f32 {
+        let angle = dot(dir, &self.normal).clamp(-1.0, 1.0).asin();
+        (-0.5 * (angle / self.width).powi(2)).exp()
+    }
+}
+
+// Nube de gas: región del cielo con su par de colores y su propia semilla de ruido
+struct Nebula {
+    center: Vec3,
+    radius: f32,
+    colors: (Color, Color),
+    seed: u32,
+}
+
+impl Nebula {
+    // Color que aporta en la dirección `dir` (unitaria): la densidad del fbm cae hacia el borde
+    // y un segundo fbm decide la mezcla entre los dos colores
+    fn color(&self, dir: &Vec3) -> Color {
+        let angle = dot(dir, &self.center).clamp(-1.0, 1.0).acos();
+        let falloff = 1.0 - smoothstep(0.3 * self.radius, self.radius, angle);
+        if falloff <= 0.0 {
+            return Color::BLACK;
+        }
+        let p = dir * NEBULA_SCALE / self.radius;
+        let density = smoothstep(-0.2, 0.6, fbm(&p, self.seed, &NEBULA_FRACTAL)) * falloff;
+        let mix = fbm(&(p * 0.5), self.seed.wrapping_add(7), &NEBULA_FRACTAL) * 0.5 + 0.5;
+        self.colors.0.lerp(&self.colors.1, mix) * srgb_factor(density * NEBULA_BRIGHTNESS)
+    }
+}
+
+// Forma de un objeto de cielo profundo
+#[derive(Debug, Clone, Copy)]
+enum DeepSkyKind {
+    Elliptical,
+    Spiral,
+    Cluster,
+}
+
+// Galaxia o cúmulo: se proyecta su centro y se suma el sprite precalculado alrededor
+struct DeepSkyObject {
+    position: Vec3,
+    tint: Color,
+    // Intensidades en [0, 1] de un cuadrado de side x side pixeles centrado en el objeto
+    kernel: Vec<f32>,
+    side: usize,
+}
+
+impl DeepSkyObject {
+    fn new(rng: &mut StdRng, kind: DeepSkyKind, radius: f32, config: &SkyboxConfig) -> Self {
+        let (min_size, max_size) = config.deep_sky_size;
+        let angular_size = rng.gen_range(min_size..max_size.max(min_size + f32::EPSILON));
+        // Lado impar, así el centro cae en un pixel
+        let side = ((angular_size * DEEP_SKY_PIXELS_PER_RADIAN) as usize | 1).clamp(3, DEEP_SKY_MAX_SIDE);
+        let half = (side / 2) as f32;
+
+        // Orientación e inclinación del disco al azar
+        let angle = rng.gen::<f32>() * PI;
+        let flattening = if let DeepSkyKind::Cluster = kind { 1.0 } else { rng.gen_range(0.3..1.0) };
+        let arm_twist = rng.gen_range(2.0..4.0);
+        let (sin, cos) = angle.sin_cos();
+
+        let mut kernel = vec![0.0; side * side];
+        for y in 0..side {
+            for x in 0..side {
+                // Coordenadas en el plano del objeto, con radio 1 en el borde del sprite
+                let (dx, dy) = ((x as f32 - half) / half, (y as f32 - half) / half);
+                let u = dx * cos + dy * sin;
+                let v = (dy * cos - dx * sin) / flattening;
+                let r = (u * u + v * v).sqrt();
+                kernel[y * side + x] = match kind {
+                    DeepSkyKind::Elliptical => (-r * r * 6.0).exp(),
+                    DeepSkyKind::Cluster => (-r * r * 9.0).exp(),
+                    // Núcleo y dos brazos en espiral logarítmica
+                    DeepSkyKind::Spiral => {
+                        let arms = (2.0 * (v.atan2(u) - arm_twist * r.max(0.05).ln())).cos().max(0.0).powi(2);
+                        (-r * r * 25.0).exp() + 0.9 * arms * (-r * 2.5).exp() * (1.0 - r).max(0.0)
+                    }
+                };
+            }
+        }
+
+        // Los cúmulos llevan estrellas sueltas concentradas al centro
+        if let DeepSkyKind::Cluster = kind {
+            for _ in 0..CLUSTER_STARS {
+                let spread = rng.gen::<f32>().powi(2) * half;
+                let direction = rng.gen::<f32>() * 2.0 * PI;
+                let x = (half + spread * direction.cos()).round() as usize;
+                let y = (half + spread * direction.sin()).round() as usize;
+                if let Some(cell) = kernel.get_mut(y.min(side - 1) * side + x.min(side - 1)) {
+                    *cell = (*cell + rng.gen_range(0.3..0.8)).min(1.0);
+                }
+            }
+        }
+
+        let tint = match kind {
+            DeepSkyKind::Cluster => config.cluster_tint,
+            _ => config.galaxy_tint,
+        };
+        DeepSkyObject {
+            position: random_direction(rng, None, 0.0) * radius,
+            tint: tint * (config.deep_sky_brightness * rng.gen_range(0.6..1.0)),
+            kernel,
+            side,
+        }
+    }
+}
+
+// Dirección aleatoria (unitaria). Con banda se descartan direcciones hasta aceptar una con
+// probabilidad floor + (1 - floor) * peso, así se amontonan cerca del plano.
+fn random_direction(rng: &mut impl Rng, band: Option<&GalacticBand>, floor: f32) -> Vec3 {
+    loop {
+        let theta = rng.gen::<f32>() * 2.0 * PI; // Ángulo azimutal
+        let phi = rng.gen::<f32>() * PI;         // Ángulo polar
+
+        // Coordenadas cartesianas
+        let dir = Vec3::new(phi.sin() * theta.cos(), phi.cos(), phi.sin() * theta.sin());
+        match band {
+            Some(band) if rng.gen::<f32>() >= floor + (1.0 - floor) * band.weight(&dir) => continue,
+            _ => return dir,
+        }
+    }
+}
+
+// Color de cada clase espectral (O, B, A, F, G, K, M), de la más caliente a la más fría
+const SPECTRAL_TINTS: [Color; 7] = [
+    Color::new(155, 176, 255),
+    Color::new(170, 191, 255),
+    Color::new(202, 215, 255),
+    Color::new(248, 247, 255),
+    Color::new(255, 244, 234),
+    Color::new(255, 210, 161),
+    Color::new(255, 204, 111),
+];
+// Clases (índices en SPECTRAL_TINTS) que tienden a verse más grandes
+const BLUE_CLASSES: usize = 2;
 
 pub struct Skybox {
-    stars: Vec<Star>,
+    background: Background,
+    // Brillo del cielo por celda, suavizado y llevado a [0, 1]
+    density: Vec<f32>,
+    meteors: Vec<Meteor>,
+    meteor_interval: f32,
+    meteor_rng: StdRng,
+}
+
+// Estrella fugaz: recorre un arco de círculo máximo desde `start` girando alrededor de `axis`
+struct Meteor {
+    start: Vec3,
+    axis: Vec3,
+    arc: f32,
+    brightness: f32,
+    age: f32,
+}
+
+impl Meteor {
+    fn spawn(rng: &mut StdRng) -> Self {
+        let start = random_direction(rng, None, 0.0);
+        // Cualquier eje perpendicular al inicio da un arco por un círculo máximo
+        let axis = cross(&start, &random_direction(rng, None, 0.0))
+            .try_normalize(f32::EPSILON)
+            .unwrap_or(Vec3::new(0.0, 1.0, 0.0));
+        Meteor {
+            start,
+            axis,
+            arc: rng.gen_range(METEOR_MIN_ARC..METEOR_MAX_ARC),
+            brightness: rng.gen_range(0.6..1.0),
+            age: 0.0,
+        }
+    }
+
+    // Dirección del punto del arco en `progress` (0 al aparecer, 1 al apagarse)
+    fn point(&self, progress: f32) -> Vec3 {
+        rotate_vec3(&self.start, self.arc * progress, &self.axis)
+    }
+}
+
+// Qué se dibuja de fondo; se elige al construir el skybox
+enum Background {
+    // Estrellas como puntos proyectados, con la bruma de la Vía Láctea, las nebulosas
+    // y los objetos de cielo profundo
+    Stars { stars: Vec<Star>, haze: Vec<HazePoint>, nebula: Option<Texture>, deep_sky: Vec<DeepSkyObject> },
+    // Panorama pintado pixel a pixel detrás de la geometría
+    Cubemap(Box<Cubemap>),
+}
+
+// Punto de luz muy tenue que se suma al fondo
+struct HazePoint {
+    position: Vec3,
+    brightness: f32,
 }
 
 struct Star {
     position: Vec3,
     brightness: f32,
     size: u8,
+    // Color de su clase espectral
+    tint: Color,
+    // Fase inicial y ciclos por TWINKLE_PERIOD del centelleo (entero, así el ciclo cierra)
+    twinkle_phase: f32,
+    twinkle_cycles: u32,
 }
 
 impl Star {
-    /// Crea una estrella con propiedades aleatorias
-    pub fn new(radius: f32) -> Self {
-        let mut rng = rand::thread_rng();
-        let theta = rng.gen::<f32>() * 2.0 * PI; // Ángulo azimutal
-        let phi = rng.gen::<f32>() * PI;         // Ángulo polar
-
-        // Coordenadas cartesianas
-        let x = radius * phi.sin() * theta.cos();
-        let y = radius * phi.cos();
-        let z = radius * phi.sin() * theta.sin();
+    /// Crea una estrella con propiedades aleatorias; `classes` reparte las clases espectrales
+    /// y `band` la acerca a la Vía Láctea
+    fn new(rng: &mut StdRng, radius: f32, classes: Option<&WeightedIndex<f32>>, band: Option<&GalacticBand>) -> Self {
+        // Sin pesos válidos todas son de tipo solar (G)
+        let class = classes.map_or(4, |classes| classes.sample(rng));
 
         Star {
-            position: Vec3::new(x, y, z),
+            position: random_direction(rng, band, BAND_FLOOR) * radius,
             brightness: rng.gen::<f32>(), // Brillo entre 0.0 y 1.0
-            size: rng.gen_range(1..=3),  // Tamaño entre 1 y 3 píxeles
+            // Tamaño entre 1 y 3 píxeles; las azules nunca son de 1
+            size: if class < BLUE_CLASSES { rng.gen_range(2..=3) } else { rng.gen_range(1..=3) },
+            tint: SPECTRAL_TINTS[class],
+            twinkle_phase: rng.gen::<f32>() * 2.0 * PI,
+            twinkle_cycles: rng.gen_range(150..=600), // Entre 2 y 9 parpadeos por segundo a 60 FPS
         }
     }
+
+    /// Brillo en el paso `time` del reloj: una oscilación suave más bajones de un ruido lento. Las
+    /// estrellas brillantes centellean menos que las débiles.
+    fn twinkled_brightness(&self, index: usize, time: f32) -> f32 {
+        let angle = cycle_angle(time, TWINKLE_PERIOD);
+        let amount = TWINKLE_AMOUNT * (1.0 - self.brightness);
+        let wave = (self.twinkle_phase + angle * self.twinkle_cycles as f32).sin();
+
+        // El ruido recorre un círculo propio de cada estrella, así también cierra el ciclo
+        let noise_point = Vec3::new(angle.cos() * FLICKER_RADIUS, angle.sin() * FLICKER_RADIUS, index as f32 * 3.7);
+        let flicker = (perlin(&noise_point, FLICKER_SEED) - 0.3).max(0.0) * 2.0;
+
+        let factor = (1.0 + amount * wave - amount * flicker).max(TWINKLE_FLOOR);
+        (self.brightness * factor).clamp(0.0, 1.0)
+    }
 }
 
 impl Skybox {
-    /// Genera un nuevo skybox con un número específico de estrellas
-    pub fn new(star_count: usize, radius: f32) -> Self {
-        let stars = (0..star_count)
-            .map(|_| Star::new(radius))
+    /// Genera un nuevo skybox con un número específico de estrellas. Todo el cielo (estrellas,
+    /// bruma, nebulosas y estrellas fugaces) sale de `seed`: la misma semilla da siempre el
+    /// mismo cielo, útil para renders reproducibles
+    pub fn with_seed(star_count: usize, radius: f32, config: SkyboxConfig, seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let classes = WeightedIndex::new(config.class_weights).ok();
+        let band = config.galactic_plane
+            .and_then(|normal| normal.try_normalize(f32::EPSILON))
+            .map(|normal| GalacticBand { normal, width: config.band_width.max(0.01) });
+        let stars: Vec<Star> = (0..star_count)
+            .map(|_| Star::new(&mut rng, radius, classes.as_ref(), band.as_ref()))
+            .collect();
+
+        // La bruma sólo existe con banda; sus puntos se pegan al plano (sin piso)
+        let haze = band.as_ref().map_or_else(Vec::new, |band| {
+            (0..config.haze_points)
+                .map(|_| {
+                    let dir = random_direction(&mut rng, Some(band), 0.0);
+                    HazePoint { position: dir * radius, brightness: rng.gen::<f32>() * HAZE_BRIGHTNESS * band.weight(&dir) }
+                })
+                .collect()
+        });
+
+        let nebulae: Vec<Nebula> = (0..config.nebulae)
+            .filter_map(|_| {
+                let colors = *config.nebula_colors.choose(&mut rng)?;
+                Some(Nebula {
+                    center: random_direction(&mut rng, None, 0.0),
+                    radius: rng.gen_range(NEBULA_MIN_RADIUS..NEBULA_MAX_RADIUS),
+                    colors,
+                    seed: rng.gen(),
+                })
+            })
             .collect();
-        Skybox { stars }
+        let nebula = (!nebulae.is_empty()).then(|| nebula_map(&nebulae));
+
+        // Galaxias (mitad elípticas, mitad espirales) y cúmulos
+        let deep_sky: Vec<DeepSkyObject> = (0..config.galaxies)
+            .map(|i| if i % 2 == 0 { DeepSkyKind::Spiral } else { DeepSkyKind::Elliptical })
+            .chain(std::iter::repeat_n(DeepSkyKind::Cluster, config.clusters))
+            .map(|kind| DeepSkyObject::new(&mut rng, kind, radius, &config))
+            .collect();
+
+        let density = density_map(&stars);
+        let meteor_seed = rng.gen();
+        Skybox::with_background(Background::Stars { stars, haze, nebula, deep_sky }, density, config.meteor_interval, meteor_seed)
+    }
+
+    /// Skybox con un panorama en seis imágenes, en el orden +X, -X, +Y, -Y, +Z, -Z
+    /// (derecha, izquierda, arriba, abajo, frente, atrás)
+    pub fn from_cubemap(paths: [&str; 6]) -> Result<Self, image::ImageError> {
+        let [px, nx, py, ny, pz, nz] = paths.map(Texture::load);
+        let cubemap = Cubemap { faces: [px?, nx?, py?, ny?, pz?, nz?] };
+        let density = cubemap_density(&cubemap);
+        Ok(Skybox::with_background(Background::Cubemap(Box::new(cubemap)), density, SkyboxConfig::default().meteor_interval, METEOR_SEED))
+    }
+
+    fn with_background(background: Background, density: Vec<f32>, meteor_interval: f32, meteor_seed: u64) -> Self {
+        Skybox {
+            background,
+            density,
+            meteors: Vec::new(),
+            meteor_interval,
+            meteor_rng: StdRng::seed_from_u64(meteor_seed),
+        }
     }
 
-    /// Renderiza el skybox al framebuffer
+    /// Avanza las estrellas fugaces `delta` segundos: envejece las activas, quita las que
+    /// terminaron y, en promedio cada `meteor_interval` segundos, lanza una nueva
+    pub fn update(&mut self, delta: f32) {
+        for meteor in self.meteors.iter_mut() {
+            meteor.age += delta;
+        }
+        self.meteors.retain(|meteor| meteor.age < METEOR_LIFETIME);
+
+        if self.meteor_interval > 0.0 && self.meteor_rng.gen::<f32>() < 1.0 - (-delta / self.meteor_interval).exp() {
+            self.meteors.push(Meteor::spawn(&mut self.meteor_rng));
+        }
+    }
+
+    /// Densidad de estrellas en [0, 1] vista en la dirección `dir` (para reflejos falsos
+    /// del cielo). Interpola el mapa precalculado, así no recorre las estrellas por fragmento.
+    pub fn sample(&self, dir: &Vec3) -> f32 {
+        let uv = sphere_uv(dir);
+        let x = uv.x * DENSITY_WIDTH as f32 - 0.5;
+        let y = (uv.y * DENSITY_HEIGHT as f32 - 0.5).clamp(0.0, (DENSITY_HEIGHT - 1) as f32);
+        let (x0, y0) = (x.floor(), y.floor());
+        let (tx, ty) = (x - x0, y - y0);
+
+        // La longitud da la vuelta; la latitud se recorta en los polos
+        let cell = |cx: i64, cy: usize| {
+            self.density[cy.min(DENSITY_HEIGHT - 1) * DENSITY_WIDTH + cx.rem_euclid(DENSITY_WIDTH as i64) as usize]
+        };
+        let (x0, y0) = (x0 as i64, y0 as usize);
+        let top = cell(x0, y0) + (cell(x0 + 1, y0) - cell(x0, y0)) * tx;
+        let bottom = cell(x0, y0 + 1) + (cell(x0 + 1, y0 + 1) - cell(x0, y0 + 1)) * tx;
+        top + (bottom - top) * ty
+    }
+
+    /// Renderiza el skybox al framebuffer; las estrellas centellean según `uniforms.time`
     pub fn render_sb(&self, framebuffer: &mut Framebuffer, uniforms: &Uniforms, camera_position: Vec3) {
-        for star in &self.stars {
-            // Posición relativa a la cámara
-            let position = star.position + camera_position;
-
-            // Proyectar al espacio de pantalla
-            let pos_vec4 = Vec4::new(position.x, position.y, position.z, 1.0);
-            let projected = uniforms.projection_matrix * uniforms.view_matrix * pos_vec4;
-
-            // División perspectiva
-            if projected.w <= 0.0 { continue; }
-            let ndc = projected / projected.w;
-
-            // Transformar a coordenadas de pantalla
-            let screen_pos = uniforms.viewport_matrix * Vec4::new(ndc.x, ndc.y, ndc.z, 1.0);
-
-            // Verificar si está en frente de la cámara
-            if screen_pos.z < 0.0 { continue; }
-
-            let x = screen_pos.x as usize;
-            let y = screen_pos.y as usize;
-
-            // Verificar si está dentro de los límites del framebuffer
-            if x < framebuffer.width && y < framebuffer.height {
-                // Calcular el color basado en el brillo
-                let intensity = (star.brightness * 255.0) as u8;
-                let color = (intensity as u32) << 16 | (intensity as u32) << 8 | intensity as u32;
-
-                framebuffer.set_current_color(color);
-
-                // Renderizar según el tamaño de la estrella
-                match star.size {
-                    1 => framebuffer.point(x, y, 1000.0),
-                    2 => {
-                        framebuffer.point(x, y, 1000.0);
-                        framebuffer.point(x + 1, y, 1000.0);
-                        framebuffer.point(x, y + 1, 1000.0);
-                        framebuffer.point(x + 1, y + 1, 1000.0);
-                    }
-                    3 => {
-                        framebuffer.point(x, y, 1000.0);
-                        framebuffer.point(x - 1, y, 1000.0);
-                        framebuffer.point(x + 1, y, 1000.0);
-                        framebuffer.point(x, y - 1, 1000.0);
-                        framebuffer.point(x, y + 1, 1000.0);
-                    }
-                    _ => {}
+        match &self.background {
+            Background::Stars { stars, haze, nebula, deep_sky } => {
+                if let Some(nebula) = nebula {
+                    render_nebula(nebula, framebuffer, uniforms);
+                }
+                render_haze(haze, framebuffer, uniforms, camera_position);
+                render_deep_sky(deep_sky, framebuffer, uniforms, camera_position);
+                render_stars(stars, framebuffer, uniforms, camera_position);
+            }
+            Background::Cubemap(cubemap) => render_cubemap(cubemap, framebuffer, uniforms),
+        }
+        render_meteors(&self.meteors, framebuffer, uniforms, camera_position);
+    }
+}
+
+// Proyecta un punto del cielo (relativo a la cámara) a coordenadas de pantalla sin recortar;
+// None si queda detrás de la cámara
+fn project_sky(position: Vec3, uniforms: &Uniforms) -> Option<Vec2> {
+    let projected = uniforms.projection_matrix * uniforms.view_matrix * Vec4::new(position.x, position.y, position.z, 1.0);
+    if projected.w <= 0.0 {
+        return None;
+    }
+    let ndc = projected / projected.w;
+    let screen_pos = uniforms.viewport_matrix * Vec4::new(ndc.x, ndc.y, ndc.z, 1.0);
+    if screen_pos.z < 0.0 {
+        return None;
+    }
+    Some(Vec2::new(screen_pos.x, screen_pos.y))
+}
+
+// Pixel de pantalla de un punto del cielo
+fn sky_to_screen(position: Vec3, uniforms: &Uniforms) -> Option<(usize, usize)> {
+    project_sky(position, uniforms)
+        .filter(|screen| screen.x >= 0.0 && screen.y >= 0.0)
+        .map(|screen| (screen.x as usize, screen.y as usize))
+}
+
+// Cada estrella fugaz es una línea de la cola (apagada) a la cabeza (brillante). Se suma a
+// profundidad máxima, así nunca tapa a un planeta dibujado antes.
+fn render_meteors(meteors: &[Meteor], framebuffer: &mut Framebuffer, uniforms: &Uniforms, camera_position: Vec3) {
+    for meteor in meteors {
+        let progress = meteor.age / METEOR_LIFETIME;
+        let head = meteor.point(progress) * METEOR_DISTANCE + camera_position;
+        let tail = meteor.point((progress - METEOR_TRAIL).max(0.0)) * METEOR_DISTANCE + camera_position;
+        // Aparece y se apaga suavemente
+        let brightness = meteor.brightness * (progress * PI).sin();
+        if let (Some(tail), Some(head)) = (project_sky(tail, uniforms), project_sky(head, uniforms)) {
+            add_trail(framebuffer, tail, head, brightness);
+        }
+    }
+}
+
+// Línea con anti-aliasing (Xiaolin Wu): en cada paso del eje mayor la cobertura se reparte
+// entre los dos pixeles vecinos del eje menor. El brillo crece de `from` a `to`.
+fn add_trail(framebuffer: &mut Framebuffer, from: Vec2, to: Vec2, brightness: f32) {
+    let delta = to - from;
+    let steep = delta.y.abs() > delta.x.abs();
+    // Tope de pasos por si la cola cae muy lejos fuera de la pantalla
+    let steps = (delta.x.abs().max(delta.y.abs()).ceil() as usize).clamp(1, framebuffer.width + framebuffer.height);
+
+    for step in 0..=steps {
+        let t = step as f32 / steps as f32;
+        let point = from + delta * t;
+        let (major, minor) = if steep { (point.y, point.x) } else { (point.x, point.y) };
+        let base = minor.floor();
+        let fraction = minor - base;
+
+        for (offset, coverage) in [(0.0, 1.0 - fraction), (1.0, fraction)] {
+            let (x, y) = if steep { (base + offset, major) } else { (major, base + offset) };
+            if x >= 0.0 && y >= 0.0 && (x as usize) < framebuffer.width && (y as usize) < framebuffer.height {
+                framebuffer.add_pixel(x as usize, y as usize, Framebuffer::MAX_DEPTH, METEOR_COLOR * (brightness * t * coverage));
+            }
+        }
+    }
+}
+
+// Bruma de la Vía Láctea: puntos que se suman al fondo sin escribir profundidad
+fn render_haze(haze: &[HazePoint], framebuffer: &mut Framebuffer, uniforms: &Uniforms, camera_position: Vec3) {
+    for point in haze {
+        if let Some((x, y)) = sky_to_screen(point.position + camera_position, uniforms) {
+            let color = Color::new(200, 205, 230) * srgb_factor(point.brightness);
+            framebuffer.add_pixel(x, y, Framebuffer::MAX_DEPTH, color);
+        }
+    }
+}
+
+// Suma el sprite de cada galaxia o cúmulo alrededor de su centro proyectado. Cerca del sol
+// (uniforms.light_position) se van apagando, como si el resplandor los tapara.
+fn render_deep_sky(objects: &[DeepSkyObject], framebuffer: &mut Framebuffer, uniforms: &Uniforms, camera_position: Vec3) {
+    let sun = project_sky(uniforms.light_position, uniforms);
+    let height = framebuffer.height as f32;
+
+    for object in objects {
+        let Some(center) = project_sky(object.position + camera_position, uniforms) else { continue };
+        let glare = sun.map_or(1.0, |sun| smoothstep(GLARE_INNER, GLARE_OUTER, (center - sun).magnitude() / height));
+        if glare <= 0.0 {
+            continue;
+        }
+
+        let half = (object.side / 2) as i64;
+        let (cx, cy) = (center.x.floor() as i64, center.y.floor() as i64);
+        for ky in 0..object.side {
+            for kx in 0..object.side {
+                let (x, y) = (cx + kx as i64 - half, cy + ky as i64 - half);
+                let intensity = object.kernel[ky * object.side + kx] * glare;
+                if x >= 0 && y >= 0 && intensity > 0.0 {
+                    framebuffer.add_pixel(x as usize, y as usize, Framebuffer::MAX_DEPTH, object.tint * intensity);
                 }
             }
         }
     }
 }
+
+// Pixeles que ocupa una estrella según su tamaño, relativos a su centro
+fn star_footprint(size: u8) -> &'static [(i64, i64)] {
+    match size {
+        1 => &[(0, 0)],
+        2 => &[(0, 0), (1, 0), (0, 1), (1, 1)],
+        3 => &[(0, 0), (-1, 0), (1, 0), (0, -1), (0, 1)],
+        _ => &[],
+    }
+}
+
+fn render_stars(stars: &[Star], framebuffer: &mut Framebuffer, uniforms: &Uniforms, camera_position: Vec3) {
+    for (index, star) in stars.iter().enumerate() {
+        // Posición relativa a la cámara, proyectada a pantalla
+        let Some(screen) = project_sky(star.position + camera_position, uniforms) else { continue };
+        let (x, y) = (screen.x.floor() as i64, screen.y.floor() as i64);
+        let (width, height) = (framebuffer.width as i64, framebuffer.height as i64);
+        // Puede asomar un vecino aunque el centro quede justo fuera del borde
+        if x < -1 || y < -1 || x > width || y > height {
+            continue;
+        }
+
+        // Color de la clase espectral escalado por el brillo
+        let color = (star.tint * srgb_factor(star.twinkled_brightness(index, uniforms.time))).to_hex();
+
+        // A profundidad máxima: cualquier geometría gana la prueba de profundidad, se dibuje
+        // antes o después. Los vecinos fuera de la pantalla se descartan.
+        for &(dx, dy) in star_footprint(star.size) {
+            let (px, py) = (x + dx, y + dy);
+            if (0..width).contains(&px) && (0..height).contains(&py) {
+                framebuffer.set_pixel(px as usize, py as usize, Framebuffer::MAX_DEPTH, color);
+            }
+        }
+    }
+}
+
+// Recorre los pixeles que ninguna geometría cubrió con la dirección de su rayo. Sin la
+// traslación de la vista el rayo sale del origen, así la dirección es el punto lejano desproyectado.
+fn for_each_sky_pixel(framebuffer: &mut Framebuffer, uniforms: &Uniforms, mut paint: impl FnMut(&mut Framebuffer, usize, usize, Vec3)) {
+    let mut rotation = uniforms.view_matrix;
+    rotation.set_column(3, &Vec4::new(0.0, 0.0, 0.0, 1.0));
+    let Some(inverse) = (uniforms.projection_matrix * rotation).try_inverse() else { return };
+
+    let (width, height) = (framebuffer.width, framebuffer.height);
+    for y in 0..height {
+        let ndc_y = 1.0 - 2.0 * (y as f32 + 0.5) / height as f32;
+        for x in 0..width {
+            if !framebuffer.depth_test(x, y, Framebuffer::MAX_DEPTH) {
+                continue;
+            }
+            let ndc_x = 2.0 * (x as f32 + 0.5) / width as f32 - 1.0;
+            let far = inverse * Vec4::new(ndc_x, ndc_y, 1.0, 1.0);
+            paint(framebuffer, x, y, far.xyz() / far.w);
+        }
+    }
+}
+
+// Pinta el panorama en los pixeles libres
+fn render_cubemap(cubemap: &Cubemap, framebuffer: &mut Framebuffer, uniforms: &Uniforms) {
+    for_each_sky_pixel(framebuffer, uniforms, |framebuffer, x, y, dir| {
+        framebuffer.set_pixel(x, y, Framebuffer::MAX_DEPTH, cubemap.sample(&dir).to_hex());
+    });
+}
+
+// Suma las nebulosas horneadas sobre el fondo, antes que la bruma y las estrellas
+fn render_nebula(nebula: &Texture, framebuffer: &mut Framebuffer, uniforms: &Uniforms) {
+    for_each_sky_pixel(framebuffer, uniforms, |framebuffer, x, y, dir| {
+        let color = nebula.sample(&sphere_uv(&dir), &Sampler::SPHERE);
+        if color.r > 0.0 || color.g > 0.0 || color.b > 0.0 {
+            framebuffer.add_pixel(x, y, Framebuffer::MAX_DEPTH, color);
+        }
+    });
+}
+
+// Seis caras (+X, -X, +Y, -Y, +Z, -Z) con la convención de OpenGL: cada cara se ve desde
+// dentro del cubo con la fila 0 arriba
+struct Cubemap {
+    faces: [Texture; 6],
+}
+
+impl Cubemap {
+    /// Color en la dirección `dir` con filtrado bilineal. Las muestras que caen fuera de la
+    /// cara se leen de la cara vecina, así los bordes entre caras no se notan.
+    fn sample(&self, dir: &Vec3) -> Color {
+        let (face, u, v) = face_uv(dir);
+        let texture = &self.faces[face];
+        let x = u * texture.width as f32 - 0.5;
+        let y = v * texture.height as f32 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (tx, ty) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+
+        let top = self.texel(face, x0, y0).lerp(&self.texel(face, x0 + 1, y0), tx);
+        let bottom = self.texel(face, x0, y0 + 1).lerp(&self.texel(face, x0 + 1, y0 + 1), tx);
+        top.lerp(&bottom, ty)
+    }
+
+    // Texel (x, y) de una cara; si cae fuera, el centro del texel se lleva a una dirección y
+    // se busca en la cara que corresponde
+    fn texel(&self, face: usize, x: i64, y: i64) -> Color {
+        let texture = &self.faces[face];
+        let (width, height) = (texture.width as i64, texture.height as i64);
+        if (0..width).contains(&x) && (0..height).contains(&y) {
+            return texture.pixels[(y * width + x) as usize];
+        }
+
+        let u = (x as f32 + 0.5) / width as f32;
+        let v = (y as f32 + 0.5) / height as f32;
+        let (face, u, v) = face_uv(&face_direction(face, u, v));
+        let texture = &self.faces[face];
+        let x = ((u * texture.width as f32) as usize).min(texture.width - 1);
+        let y = ((v * texture.height as f32) as usize).min(texture.height - 1);
+        texture.pixels[y * texture.width + x]
+    }
+}
+
+// Cara del cubo que atraviesa `dir` y coordenadas (u, v) en [0, 1] dentro de ella
+fn face_uv(dir: &Vec3) -> (usize, f32, f32) {
+    let (ax, ay, az) = (dir.x.abs(), dir.y.abs(), dir.z.abs());
+    let (face, s, t, major) = if ax >= ay && ax >= az {
+        if dir.x > 0.0 { (0, -dir.z, -dir.y, ax) } else { (1, dir.z, -dir.y, ax) }
+    } else if ay >= az {
+        if dir.y > 0.0 { (2, dir.x, dir.z, ay) } else { (3, dir.x, -dir.z, ay) }
+    } else if dir.z > 0.0 {
+        (4, dir.x, -dir.y, az)
+    } else {
+        (5, -dir.x, -dir.y, az)
+    };
+    if major <= 0.0 {
+        return (4, 0.5, 0.5);
+    }
+    (face, (s / major + 1.0) * 0.5, (t / major + 1.0) * 0.5)
+}
+
+// Inversa de face_uv: punto del plano de la cara (u, v pueden salirse de [0, 1])
+fn face_direction(face: usize, u: f32, v: f32) -> Vec3 {
+    let (s, t) = (2.0 * u - 1.0, 2.0 * v - 1.0);
+    match face {
+        0 => Vec3::new(1.0, -t, -s),
+        1 => Vec3::new(-1.0, -t, s),
+        2 => Vec3::new(s, 1.0, t),
+        3 => Vec3::new(s, -1.0, -t),
+        4 => Vec3::new(s, -t, 1.0),
+        _ => Vec3::new(-s, -t, -1.0),
+    }
+}
+
+// Dirección del centro de la celda (x, y) de un mapa de longitud x latitud (inversa de sphere_uv)
+fn cell_direction(x: usize, y: usize, width: usize, height: usize) -> Vec3 {
+    let latitude = (0.5 - (y as f32 + 0.5) / height as f32) * PI;
+    let longitude = ((x as f32 + 0.5) / width as f32 - 0.5) * 2.0 * PI;
+    Vec3::new(latitude.cos() * longitude.cos(), latitude.sin(), -latitude.cos() * longitude.sin())
+}
+
+// Brillo del panorama leído en el centro de cada celda del mapa de densidad
+fn cubemap_density(cubemap: &Cubemap) -> Vec<f32> {
+    let mut cells = vec![0.0; DENSITY_WIDTH * DENSITY_HEIGHT];
+    for y in 0..DENSITY_HEIGHT {
+        for x in 0..DENSITY_WIDTH {
+            let color = cubemap.sample(&cell_direction(x, y, DENSITY_WIDTH, DENSITY_HEIGHT));
+            cells[y * DENSITY_WIDTH + x] = color.luminance();
+        }
+    }
+    smooth_density(&cells)
+}
+
+// Suma de todas las nebulosas en cada celda del mapa
+fn nebula_map(nebulae: &[Nebula]) -> Texture {
+    let mut pixels = Vec::with_capacity(NEBULA_WIDTH * NEBULA_HEIGHT);
+    for y in 0..NEBULA_HEIGHT {
+        for x in 0..NEBULA_WIDTH {
+            let dir = cell_direction(x, y, NEBULA_WIDTH, NEBULA_HEIGHT);
+            pixels.push(nebulae.iter().fold(Color::BLACK, |sum, nebula| sum + nebula.color(&dir)));
+        }
+    }
+    Texture { width: NEBULA_WIDTH, height: NEBULA_HEIGHT, pixels }
+}
+
+// Acumula el brillo de cada estrella en su celda
+fn density_map(stars: &[Star]) -> Vec<f32> {
+    let mut cells = vec![0.0; DENSITY_WIDTH * DENSITY_HEIGHT];
+    for star in stars {
+        let uv = sphere_uv(&star.position);
+        let x = ((uv.x * DENSITY_WIDTH as f32) as usize).min(DENSITY_WIDTH - 1);
+        let y = ((uv.y * DENSITY_HEIGHT as f32) as usize).min(DENSITY_HEIGHT - 1);
+        cells[y * DENSITY_WIDTH + x] += star.brightness;
+    }
+    smooth_density(&cells)
+}
+
+// Difumina el mapa con un filtro caja 3x3 y lo lleva a [0, 1]
+fn smooth_density(cells: &[f32]) -> Vec<f32> {
+    let mut blurred = vec![0.0; cells.len()];
+    for y in 0..DENSITY_HEIGHT {
+        for x in 0..DENSITY_WIDTH {
+            let mut sum = 0.0;
+            for dy in -1i64..=1 {
+                let row = (y as i64 + dy).clamp(0, DENSITY_HEIGHT as i64 - 1) as usize;
+                for dx in -1i64..=1 {
+                    let column = (x as i64 + dx).rem_euclid(DENSITY_WIDTH as i64) as usize;
+                    sum += cells[row * DENSITY_WIDTH + column];
+                }
+            }
+            blurred[y * DENSITY_WIDTH + x] = sum / 9.0;
+        }
+    }
+
+    // Se normaliza con el doble de la media (no con el máximo, que lo dominaría una sola
+    // celda muy poblada): el cielo promedio queda en 0.5
+    let mean = blurred.iter().sum::<f32>() / blurred.len() as f32;
+    if mean > 0.0 {
+        blurred.iter_mut().for_each(|value| *value = (*value / (2.0 * mean)).min(1.0));
+    }
+    blurred
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stars(skybox: &Skybox) -> &[Star] {
+        match &skybox.background {
+            Background::Stars { stars, .. } => stars,
+            Background::Cubemap(_) => panic!("se esperaba un cielo de estrellas"),
+        }
+    }
+
+    fn class_of(star: &Star) -> usize {
+        SPECTRAL_TINTS.iter().position(|tint| tint.to_hex() == star.tint.to_hex()).expect("tinte fuera de las clases")
+    }
+
+    #[test]
+    fn star_classes_follow_their_weights() {
+        const COUNT: usize = 10_000;
+        let config = SkyboxConfig { haze_points: 0, meteor_interval: 0.0, ..SkyboxConfig::default() };
+        let skybox = Skybox::with_seed(COUNT, 100.0, config.clone(), 53);
+
+        let mut histogram = [0usize; 7];
+        for star in stars(&skybox) {
+            let class = class_of(star);
+            histogram[class] += 1;
+            assert!(class >= BLUE_CLASSES || star.size >= 2, "estrella azul de 1 pixel");
+        }
+
+        // Cada clase dentro de 4 desviaciones de la binomial (más un margen para las raras)
+        let total: f32 = config.class_weights.iter().sum();
+        for (class, (&count, weight)) in histogram.iter().zip(config.class_weights).enumerate() {
+            let p = weight / total;
+            let expected = p * COUNT as f32;
+            let tolerance = 4.0 * (expected * (1.0 - p)).sqrt() + 2.0;
+            assert!((count as f32 - expected).abs() <= tolerance, "clase {class}: {count} estrellas, se esperaban {expected}");
+        }
+    }
+
+    // Todo lo sorteado del cielo en números comparables: estrellas, bruma, nebulosas,
+    // cielo profundo y las estrellas fugaces de los primeros segundos
+    fn fingerprint(skybox: &mut Skybox) -> Vec<f32> {
+        let mut values = Vec::new();
+        let Background::Stars { stars, haze, nebula, deep_sky } = &skybox.background else {
+            panic!("se esperaba un cielo de estrellas");
+        };
+        for star in stars {
+            values.extend(star.position.iter());
+            values.extend([star.brightness, star.size as f32, star.tint.to_hex() as f32, star.twinkle_phase, star.twinkle_cycles as f32]);
+        }
+        for point in haze {
+            values.extend(point.position.iter());
+            values.push(point.brightness);
+        }
+        values.extend(nebula.iter().flat_map(|texture| texture.pixels.iter().map(|pixel| pixel.to_hex() as f32)));
+        for object in deep_sky {
+            values.extend(object.position.iter());
+            values.extend(object.kernel.iter());
+        }
+        for _ in 0..600 {
+            skybox.update(0.1);
+            values.extend(skybox.meteors.iter().flat_map(|meteor| meteor.start.iter().copied().chain([meteor.arc])));
+        }
+        values
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_sky() {
+        let config = SkyboxConfig {
+            galactic_plane: Some(Vec3::new(0.2, 1.0, 0.1)),
+            haze_points: 500,
+            nebulae: 2,
+            meteor_interval: 2.0,
+            galaxies: 2,
+            clusters: 1,
+            ..SkyboxConfig::default()
+        };
+        // Se comparan los bits, así también cuenta el redondeo
+        let sky = |seed: u64| -> Vec<u32> {
+            fingerprint(&mut Skybox::with_seed(500, 100.0, config.clone(), seed)).iter().map(|v| v.to_bits()).collect()
+        };
+
+        assert_eq!(sky(59), sky(59));
+        assert_ne!(sky(59), sky(60));
+    }
+}
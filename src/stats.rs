@@ -0,0 +1,59 @@
+use std::time::{Duration, Instant};
+
+/// Contadores y tiempos del pipeline acumulados durante un frame
+#[derive(Debug, Default)]
+pub struct RenderStats {
+    pub enabled: bool,
+    pub objects_submitted: usize,
+    pub objects_culled: usize,
+    pub triangles: usize,
+    pub fragments: usize,
+    pub fragments_passed: usize,
+    // Rechazados por early-z antes de ejecutar el fragment shader
+    pub fragments_skipped: usize,
+    pub vertex_time: Duration,
+    pub primitive_time: Duration,
+    pub fragment_time: Duration,
+    pub merge_time: Duration,
+    // Post-proceso de la imagen terminada (bloom)
+    pub post_time: Duration,
+}
+
+impl RenderStats {
+    pub fn new(enabled: bool) -> Self {
+        RenderStats { enabled, ..Default::default() }
+    }
+
+    /// Reinicia los contadores para un nuevo frame conservando si está activo
+    pub fn reset(&mut self) {
+        *self = RenderStats::new(self.enabled);
+    }
+
+    /// Marca de tiempo solo si se están recolectando estadísticas
+    pub fn start(&self) -> Option<Instant> {
+        if self.enabled { Some(Instant::now()) } else { None }
+    }
+
+    pub fn elapsed(start: Option<Instant>, stage: &mut Duration) {
+        if let Some(start) = start {
+            *stage += start.elapsed();
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "obj {} (culled {}) | tris {} | frags {}/{} (early-z {}) | vs {:.1}ms prim {:.1}ms fs {:.1}ms merge {:.1}ms post {:.1}ms",
+            self.objects_submitted,
+            self.objects_culled,
+            self.triangles,
+            self.fragments_passed,
+            self.fragments,
+            self.fragments_skipped,
+            self.vertex_time.as_secs_f32() * 1000.0,
+            self.primitive_time.as_secs_f32() * 1000.0,
+            self.fragment_time.as_secs_f32() * 1000.0,
+            self.merge_time.as_secs_f32() * 1000.0,
+            self.post_time.as_secs_f32() * 1000.0,
+        )
+    }
+}
@@ -0,0 +1,142 @@
+// Fuente de mapa de bits de 8x8 para el HUD (MicroKnight, la del ejemplo de texto de
+// minifb), un glifo por carácter ASCII imprimible del 32 al 126. Cada byte es una fila y el
+// bit más alto es la columna de la izquierda.
+
+pub const GLYPH_SIZE: usize = 8;
+// Avance entre líneas: el glifo y una fila de separación
+pub const LINE_HEIGHT: usize = GLYPH_SIZE + 1;
+
+const FIRST_CHAR: u8 = b' ';
+const LAST_CHAR: u8 = b'~';
+
+#[rustfmt::skip]
+const GLYPHS: [[u8; GLYPH_SIZE]; (LAST_CHAR - FIRST_CHAR + 1) as usize] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // ' '
+    [0x18, 0x18, 0x18, 0x18, 0x18, 0x00, 0x18, 0x00], // '!'
+    [0x6c, 0x6c, 0x24, 0x00, 0x00, 0x00, 0x00, 0x00], // '"'
+    [0x6c, 0x6c, 0xfe, 0x6c, 0xfe, 0x6c, 0x6c, 0x00], // '#'
+    [0x10, 0x7c, 0xd0, 0x7c, 0x16, 0x16, 0x7c, 0x10], // '$'
+    [0x60, 0x96, 0x7c, 0x18, 0x30, 0x6c, 0xd2, 0x0c], // '%'
+    [0x70, 0xd8, 0x70, 0xf6, 0xdc, 0xd8, 0x7c, 0x06], // '&'
+    [0x18, 0x18, 0x08, 0x10, 0x00, 0x00, 0x00, 0x00], // "'"
+    [0x18, 0x30, 0x60, 0x60, 0x60, 0x30, 0x18, 0x00], // '('
+    [0x30, 0x18, 0x0c, 0x0c, 0x0c, 0x18, 0x30, 0x00], // ')'
+    [0x00, 0x6c, 0x38, 0xfe, 0x38, 0x6c, 0x00, 0x00], // '*'
+    [0x00, 0x18, 0x18, 0x7e, 0x18, 0x18, 0x00, 0x00], // '+'
+    [0x00, 0x00, 0x00, 0x00, 0x18, 0x18, 0x08, 0x10], // ','
+    [0x00, 0x00, 0x00, 0x7c, 0x00, 0x00, 0x00, 0x00], // '-'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x18, 0x18, 0x00], // '.'
+    [0x00, 0x06, 0x0c, 0x18, 0x30, 0x60, 0xc0, 0x00], // '/'
+    [0x00, 0x78, 0xcc, 0xde, 0xf6, 0xe6, 0x7c, 0x00], // '0'
+    [0x18, 0x18, 0x38, 0x18, 0x18, 0x18, 0x7e, 0x00], // '1'
+    [0x7c, 0x06, 0x3c, 0x60, 0xc0, 0xc0, 0xfe, 0x00], // '2'
+    [0x3c, 0x06, 0x1c, 0x06, 0x46, 0xc6, 0x7c, 0x00], // '3'
+    [0x18, 0x18, 0x30, 0x6c, 0xcc, 0xfe, 0x0c, 0x00], // '4'
+    [0xf8, 0xc0, 0xfc, 0x06, 0x46, 0xcc, 0x78, 0x00], // '5'
+    [0x70, 0xc0, 0xfc, 0xc6, 0xc6, 0xcc, 0x78, 0x00], // '6'
+    [0xfe, 0x06, 0x0c, 0x18, 0x18, 0x18, 0x18, 0x00], // '7'
+    [0x78, 0xcc, 0x7c, 0xc6, 0xc6, 0xcc, 0x78, 0x00], // '8'
+    [0x78, 0xcc, 0xc6, 0xc6, 0x7e, 0x06, 0x1c, 0x00], // '9'
+    [0x00, 0x18, 0x18, 0x00, 0x00, 0x18, 0x18, 0x00], // ':'
+    [0x00, 0x18, 0x18, 0x00, 0x00, 0x18, 0x08, 0x10], // ';'
+    [0x00, 0x18, 0x30, 0x60, 0x30, 0x18, 0x00, 0x00], // '<'
+    [0x00, 0x00, 0x7c, 0x00, 0x7c, 0x00, 0x00, 0x00], // '='
+    [0x00, 0x30, 0x18, 0x0c, 0x18, 0x30, 0x00, 0x00], // '>'
+    [0x7c, 0xc6, 0x06, 0x3c, 0x30, 0x00, 0x30, 0x00], // '?'
+    [0x38, 0x6c, 0xde, 0xf6, 0xde, 0xc0, 0x66, 0x3c], // '@'
+    [0x78, 0xcc, 0xc6, 0xfe, 0xc6, 0xc6, 0xc6, 0x00], // 'A'
+    [0xf8, 0xcc, 0xfc, 0xc6, 0xc6, 0xcc, 0xf8, 0x00], // 'B'
+    [0x78, 0xcc, 0xc0, 0xc0, 0xc0, 0xc6, 0x7c, 0x00], // 'C'
+    [0xf8, 0xcc, 0xc6, 0xc6, 0xc6, 0xc6, 0xfc, 0x00], // 'D'
+    [0xfe, 0xc0, 0xfc, 0xc0, 0xc0, 0xc0, 0xfe, 0x00], // 'E'
+    [0xfe, 0xc0, 0xfc, 0xc0, 0xc0, 0xc0, 0xc0, 0x00], // 'F'
+    [0x38, 0x60, 0xc0, 0xce, 0xc6, 0xc6, 0x7e, 0x06], // 'G'
+    [0xc6, 0xc6, 0xc6, 0xfe, 0xc6, 0xc6, 0xc6, 0x00], // 'H'
+    [0x7e, 0x18, 0x18, 0x18, 0x18, 0x18, 0x7e, 0x00], // 'I'
+    [0x0e, 0x06, 0x06, 0x06, 0xc6, 0xc6, 0x7c, 0x00], // 'J'
+    [0xc6, 0xcc, 0xd8, 0xf0, 0xd8, 0xcc, 0xc6, 0x00], // 'K'
+    [0xc0, 0xc0, 0xc0, 0xc0, 0xc0, 0xc0, 0xfe, 0x00], // 'L'
+    [0xc6, 0xee, 0xfe, 0xd6, 0xc6, 0xc6, 0xc6, 0x00], // 'M'
+    [0xc6, 0xe6, 0xf6, 0xde, 0xce, 0xc6, 0xc6, 0x00], // 'N'
+    [0x78, 0xcc, 0xc6, 0xc6, 0xc6, 0xc6, 0x7c, 0x00], // 'O'
+    [0xf8, 0xcc, 0xc6, 0xc6, 0xfc, 0xc0, 0xc0, 0x00], // 'P'
+    [0x78, 0xcc, 0xc6, 0xc6, 0xc6, 0xd6, 0x7c, 0x0c], // 'Q'
+    [0xf8, 0xcc, 0xc6, 0xc6, 0xfc, 0xd8, 0xcc, 0x06], // 'R'
+    [0x78, 0xc0, 0x7c, 0x06, 0x46, 0xc6, 0x7c, 0x00], // 'S'
+    [0x7e, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x00], // 'T'
+    [0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0x7c, 0x00], // 'U'
+    [0xc6, 0xc6, 0xc6, 0x6c, 0x6c, 0x38, 0x38, 0x00], // 'V'
+    [0xc6, 0xc6, 0xc6, 0xd6, 0xfe, 0xee, 0xc6, 0x00], // 'W'
+    [0xc6, 0x6c, 0x38, 0x38, 0x6c, 0xc6, 0xc6, 0x00], // 'X'
+    [0xc6, 0xc6, 0xc6, 0x7c, 0x0c, 0x0c, 0x0c, 0x00], // 'Y'
+    [0xfe, 0x0c, 0x18, 0x30, 0x60, 0xc0, 0xfe, 0x00], // 'Z'
+    [0x38, 0x30, 0x30, 0x30, 0x30, 0x30, 0x38, 0x00], // '['
+    [0x00, 0xc0, 0x60, 0x30, 0x18, 0x0c, 0x06, 0x00], // '\\'
+    [0x38, 0x18, 0x18, 0x18, 0x18, 0x18, 0x38, 0x00], // ']'
+    [0x10, 0x38, 0x6c, 0x00, 0x00, 0x00, 0x00, 0x00], // '^'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xfe], // '_'
+    [0x18, 0x18, 0x10, 0x08, 0x00, 0x00, 0x00, 0x00], // '`'
+    [0x00, 0x3c, 0x06, 0x7e, 0xc6, 0xc6, 0x7e, 0x00], // 'a'
+    [0xc0, 0xf8, 0xcc, 0xc6, 0xc6, 0xc6, 0xfc, 0x00], // 'b'
+    [0x00, 0x78, 0xcc, 0xc0, 0xc0, 0xc6, 0x7c, 0x00], // 'c'
+    [0x06, 0x3e, 0x66, 0xc6, 0xc6, 0xc6, 0x7e, 0x00], // 'd'
+    [0x00, 0x78, 0xcc, 0xfc, 0xc0, 0xc6, 0x7c, 0x00], // 'e'
+    [0x38, 0x6c, 0x60, 0x78, 0x60, 0x60, 0x60, 0x60], // 'f'
+    [0x00, 0x7e, 0xc6, 0xc6, 0xc6, 0x7e, 0x06, 0x7c], // 'g'
+    [0xc0, 0xf8, 0xcc, 0xc6, 0xc6, 0xc6, 0xc6, 0x00], // 'h'
+    [0x18, 0x00, 0x38, 0x18, 0x18, 0x18, 0x7e, 0x00], // 'i'
+    [0x0c, 0x00, 0x1c, 0x0c, 0x0c, 0x0c, 0x4c, 0x38], // 'j'
+    [0xc0, 0xcc, 0xd8, 0xf0, 0xd8, 0xcc, 0xc6, 0x00], // 'k'
+    [0x38, 0x18, 0x18, 0x18, 0x18, 0x18, 0x7e, 0x00], // 'l'
+    [0x00, 0xc4, 0xee, 0xfe, 0xd6, 0xc6, 0xc6, 0x00], // 'm'
+    [0x00, 0xf8, 0xcc, 0xc6, 0xc6, 0xc6, 0xc6, 0x00], // 'n'
+    [0x00, 0x78, 0xcc, 0xc6, 0xc6, 0xc6, 0x7c, 0x00], // 'o'
+    [0x00, 0xf8, 0xcc, 0xc6, 0xc6, 0xc6, 0xfc, 0xc0], // 'p'
+    [0x00, 0x3e, 0x66, 0xc6, 0xc6, 0xc6, 0x7e, 0x06], // 'q'
+    [0x00, 0xfc, 0xc6, 0xc0, 0xc0, 0xc0, 0xc0, 0x00], // 'r'
+    [0x00, 0x78, 0xc0, 0x7c, 0x06, 0xc6, 0x7c, 0x00], // 's'
+    [0x30, 0x7c, 0x30, 0x30, 0x30, 0x32, 0x1c, 0x00], // 't'
+    [0x00, 0xc6, 0xc6, 0xc6, 0xc6, 0xc6, 0x7e, 0x00], // 'u'
+    [0x00, 0xc6, 0xc6, 0x6c, 0x6c, 0x38, 0x38, 0x00], // 'v'
+    [0x00, 0xc6, 0xd6, 0xfe, 0x7c, 0x6c, 0x44, 0x00], // 'w'
+    [0x00, 0xc6, 0x6c, 0x38, 0x38, 0x6c, 0xc6, 0x00], // 'x'
+    [0x00, 0xc6, 0xc6, 0xc6, 0xc6, 0x7e, 0x06, 0x7c], // 'y'
+    [0x00, 0xfe, 0x0c, 0x18, 0x30, 0x60, 0xfe, 0x00], // 'z'
+    [0x0c, 0x18, 0x18, 0x30, 0x18, 0x18, 0x0c, 0x00], // '{'
+    [0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18], // '|'
+    [0x30, 0x18, 0x18, 0x0c, 0x18, 0x18, 0x30, 0x00], // '}'
+    [0x72, 0x9c, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // '~'
+];
+
+/// Filas del glifo de `c`; las letras con acento usan la letra sin él (los nombres de la
+/// escena están en español) y el resto fuera del rango imprimible se muestra `?`
+pub fn glyph(c: char) -> &'static [u8; GLYPH_SIZE] {
+    let c = without_accent(c);
+    let code = if c.is_ascii() && (FIRST_CHAR..=LAST_CHAR).contains(&(c as u8)) { c as u8 } else { b'?' };
+    &GLYPHS[(code - FIRST_CHAR) as usize]
+}
+
+fn without_accent(c: char) -> char {
+    match c {
+        'á' | 'à' | 'ä' | 'â' => 'a',
+        'é' | 'è' | 'ë' | 'ê' => 'e',
+        'í' | 'ì' | 'ï' | 'î' => 'i',
+        'ó' | 'ò' | 'ö' | 'ô' => 'o',
+        'ú' | 'ù' | 'ü' | 'û' => 'u',
+        'ñ' => 'n',
+        'Á' | 'À' | 'Ä' | 'Â' => 'A',
+        'É' | 'È' | 'Ë' | 'Ê' => 'E',
+        'Í' | 'Ì' | 'Ï' | 'Î' => 'I',
+        'Ó' | 'Ò' | 'Ö' | 'Ô' => 'O',
+        'Ú' | 'Ù' | 'Ü' | 'Û' => 'U',
+        'Ñ' => 'N',
+        _ => c,
+    }
+}
+
+/// Ancho y alto en pixeles de `text` (con sus saltos de línea) a la escala dada
+pub fn text_size(text: &str, scale: usize) -> (usize, usize) {
+    let columns = text.lines().map(|line| line.chars().count()).max().unwrap_or(0);
+    let lines = text.lines().count().max(1);
+    (columns * GLYPH_SIZE * scale, ((lines - 1) * LINE_HEIGHT + GLYPH_SIZE) * scale)
+}
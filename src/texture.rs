@@ -0,0 +1,84 @@
+use nalgebra_glm::Vec2;
+use crate::color::Color;
+
+/// Qué se hace con las coordenadas fuera de [0, 1]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Wrap {
+    // Se repite la imagen: el borde derecho se mezcla con el izquierdo (costura de la esfera)
+    Repeat,
+    // Se usa el texel del borde (polos)
+    Clamp,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Filter {
+    Nearest,
+    Bilinear,
+}
+
+/// Modo de muestreo, con el wrap de cada eje por separado
+#[derive(Debug, Clone, Copy)]
+pub struct Sampler {
+    pub filter: Filter,
+    pub wrap_u: Wrap,
+    pub wrap_v: Wrap,
+}
+
+impl Sampler {
+    /// Mapas equirectangulares: se repiten alrededor del ecuador y se recortan en los polos
+    pub const SPHERE: Sampler = Sampler { filter: Filter::Bilinear, wrap_u: Wrap::Repeat, wrap_v: Wrap::Clamp };
+}
+
+/// Imagen en memoria, con la fila 0 arriba
+pub struct Texture {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<Color>,
+}
+
+impl Texture {
+    /// Carga un PNG o JPEG; se llama una vez al inicio, nunca por frame
+    pub fn load(path: &str) -> Result<Self, image::ImageError> {
+        let image = image::open(path)?.to_rgb8();
+        let (width, height) = (image.width() as usize, image.height() as usize);
+        let pixels = image.pixels().map(|p| Color::new(p[0], p[1], p[2])).collect();
+        Ok(Texture { width, height, pixels })
+    }
+
+    /// Color en `uv` (v = 0 arriba de la imagen). OpenGL pone v = 0 abajo, así que las
+    /// coordenadas de los .obj se invierten antes de llamar.
+    pub fn sample(&self, uv: &Vec2, sampler: &Sampler) -> Color {
+        match sampler.filter {
+            Filter::Nearest => {
+                let x = wrap_index((uv.x * self.width as f32).floor() as i64, self.width, sampler.wrap_u);
+                let y = wrap_index((uv.y * self.height as f32).floor() as i64, self.height, sampler.wrap_v);
+                self.texel(x, y)
+            }
+            Filter::Bilinear => {
+                // Los centros de los texels están en +0.5
+                let x = uv.x * self.width as f32 - 0.5;
+                let y = uv.y * self.height as f32 - 0.5;
+                let (x0, y0) = (x.floor(), y.floor());
+                let (tx, ty) = (x - x0, y - y0);
+
+                let column = |dx: i64| wrap_index(x0 as i64 + dx, self.width, sampler.wrap_u);
+                let row = |dy: i64| wrap_index(y0 as i64 + dy, self.height, sampler.wrap_v);
+
+                let top = self.texel(column(0), row(0)).lerp(&self.texel(column(1), row(0)), tx);
+                let bottom = self.texel(column(0), row(1)).lerp(&self.texel(column(1), row(1)), tx);
+                top.lerp(&bottom, ty)
+            }
+        }
+    }
+
+    fn texel(&self, x: usize, y: usize) -> Color {
+        self.pixels[y * self.width + x]
+    }
+}
+
+fn wrap_index(index: i64, size: usize, wrap: Wrap) -> usize {
+    match wrap {
+        Wrap::Repeat => index.rem_euclid(size as i64) as usize,
+        Wrap::Clamp => index.clamp(0, size as i64 - 1) as usize,
+    }
+}
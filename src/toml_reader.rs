@@ -0,0 +1,433 @@
+use std::collections::HashSet;
+use std::fmt;
+use serde_json::{Map, Number, Value};
+
+// Lector del subconjunto de TOML que usan los archivos de configuración: tablas
+// (`[a.b]`), arreglos de tablas (`[[a]]`), claves con puntos, cadenas básicas y literales,
+// enteros, flotantes, booleanos, arreglos (también en varias líneas) y tablas en línea. El
+// resultado es un serde_json::Value, que se convierte a las estructuras con serde. Quedan
+// fuera las fechas y las cadenas de varias líneas.
+
+#[derive(Debug)]
+pub struct TomlError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for TomlError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "línea {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for TomlError {}
+
+pub fn parse(text: &str) -> Result<Value, TomlError> {
+    let mut parser = Parser { chars: text.chars().collect(), index: 0, line: 1 };
+    let mut root = Map::new();
+    // Ruta de la tabla a la que van las claves (la de la última cabecera)
+    let mut current: Vec<String> = Vec::new();
+    // Tablas ya abiertas con `[...]`; una cabecera no se puede repetir
+    let mut defined: HashSet<Vec<String>> = HashSet::new();
+
+    loop {
+        parser.skip_blank_lines();
+        match parser.peek() {
+            None => break,
+            Some('[') => {
+                parser.index += 1;
+                let array = parser.eat('[');
+                parser.skip_spaces();
+                let path = parser.key_path()?;
+                parser.expect(']')?;
+                if array {
+                    parser.expect(']')?;
+                }
+                let (last, parent) = path.split_last().expect("key_path nunca devuelve una ruta vacía");
+                let parent = table_mut(&mut root, parent, parser.line)?;
+                if array {
+                    match parent.entry(last.clone()).or_insert_with(|| Value::Array(Vec::new())) {
+                        Value::Array(tables) => tables.push(Value::Object(Map::new())),
+                        _ => return Err(parser.error(format!("`{}` ya tiene un valor que no es un arreglo de tablas", last))),
+                    }
+                    // Las subtablas de un elemento nuevo se pueden volver a abrir
+                    defined.retain(|table| !(table.len() > path.len() && table.starts_with(&path)));
+                } else if !matches!(parent.entry(last.clone()).or_insert_with(|| Value::Object(Map::new())), Value::Object(_)) {
+                    return Err(parser.error(format!("`{}` ya tiene un valor que no es una tabla", last)));
+                } else if !defined.insert(path.clone()) {
+                    return Err(parser.error(format!("la tabla `{}` está repetida", path.join("."))));
+                }
+                current = path;
+            }
+            Some(_) => {
+                let (path, value) = parser.key_value()?;
+                let full: Vec<String> = current.iter().chain(&path).cloned().collect();
+                insert(&mut root, &full, value, parser.line)?;
+            }
+        }
+        parser.end_of_line()?;
+    }
+    Ok(Value::Object(root))
+}
+
+// Tabla en `path`, creándola si falta; en un arreglo de tablas se usa la última
+fn table_mut<'a>(root: &'a mut Map<String, Value>, path: &[String], line: usize) -> Result<&'a mut Map<String, Value>, TomlError> {
+    let mut table = root;
+    for key in path {
+        let entry = table.entry(key.clone()).or_insert_with(|| Value::Object(Map::new()));
+        let entry = match entry {
+            Value::Array(items) => items.last_mut().ok_or_else(|| TomlError { line, message: format!("`{}` es un arreglo vacío, no una tabla", key) })?,
+            other => other,
+        };
+        table = match entry {
+            Value::Object(map) => map,
+            _ => return Err(TomlError { line, message: format!("`{}` no es una tabla", key) }),
+        };
+    }
+    Ok(table)
+}
+
+fn insert(root: &mut Map<String, Value>, path: &[String], value: Value, line: usize) -> Result<(), TomlError> {
+    let (last, parent) = path.split_last().expect("key_path nunca devuelve una ruta vacía");
+    let table = table_mut(root, parent, line)?;
+    if table.contains_key(last) {
+        return Err(TomlError { line, message: format!("la clave `{}` está repetida", last) });
+    }
+    table.insert(last.clone(), value);
+    Ok(())
+}
+
+struct Parser {
+    chars: Vec<char>,
+    index: usize,
+    line: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.index).copied()
+    }
+
+    fn error(&self, message: String) -> TomlError {
+        TomlError { line: self.line, message }
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.index += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), TomlError> {
+        self.skip_spaces();
+        if self.eat(expected) {
+            return Ok(());
+        }
+        let found = self.peek().map_or("el fin del archivo".to_string(), |c| format!("'{}'", c));
+        Err(self.error(format!("se esperaba '{}' y hay {}", expected, found)))
+    }
+
+    fn skip_spaces(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t')) {
+            self.index += 1;
+        }
+    }
+
+    fn skip_comment(&mut self) {
+        if self.peek() == Some('#') {
+            while !matches!(self.peek(), None | Some('\n')) {
+                self.index += 1;
+            }
+        }
+    }
+
+    // Espacios, comentarios y saltos de línea (entre sentencias o dentro de un arreglo)
+    fn skip_blank_lines(&mut self) {
+        loop {
+            self.skip_spaces();
+            self.skip_comment();
+            match self.peek() {
+                Some('\n') => {
+                    self.index += 1;
+                    self.line += 1;
+                }
+                Some('\r') => self.index += 1,
+                _ => break,
+            }
+        }
+    }
+
+    // Después de una sentencia sólo puede venir un comentario y el salto de línea
+    fn end_of_line(&mut self) -> Result<(), TomlError> {
+        self.skip_spaces();
+        self.skip_comment();
+        self.eat('\r');
+        match self.peek() {
+            None => Ok(()),
+            Some('\n') => {
+                self.index += 1;
+                self.line += 1;
+                Ok(())
+            }
+            Some(c) => Err(self.error(format!("'{}' sobra al final de la línea", c))),
+        }
+    }
+
+    fn key_value(&mut self) -> Result<(Vec<String>, Value), TomlError> {
+        let path = self.key_path()?;
+        self.expect('=')?;
+        self.skip_spaces();
+        let value = self.value()?;
+        Ok((path, value))
+    }
+
+    // `a`, `a.b`, `"a b".c`...
+    fn key_path(&mut self) -> Result<Vec<String>, TomlError> {
+        let mut path = Vec::new();
+        loop {
+            self.skip_spaces();
+            path.push(self.key()?);
+            self.skip_spaces();
+            if !self.eat('.') {
+                return Ok(path);
+            }
+        }
+    }
+
+    fn key(&mut self) -> Result<String, TomlError> {
+        match self.peek() {
+            Some('"') => self.basic_string(),
+            Some('\'') => self.literal_string(),
+            _ => {
+                let start = self.index;
+                while matches!(self.peek(), Some(c) if c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+                    self.index += 1;
+                }
+                if start == self.index {
+                    return Err(self.error("se esperaba una clave".to_string()));
+                }
+                Ok(self.chars[start..self.index].iter().collect())
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, TomlError> {
+        match self.peek() {
+            Some('"') => self.basic_string().map(Value::String),
+            Some('\'') => self.literal_string().map(Value::String),
+            Some('[') => self.array(),
+            Some('{') => self.inline_table(),
+            Some(c) if c == '+' || c == '-' || c.is_ascii_digit() => self.number(),
+            Some(c) if c.is_ascii_alphabetic() => {
+                let word = self.key()?;
+                match word.as_str() {
+                    "true" => Ok(Value::Bool(true)),
+                    "false" => Ok(Value::Bool(false)),
+                    _ => Err(self.error(format!("valor desconocido `{}` (¿faltan las comillas?)", word))),
+                }
+            }
+            Some(c) => Err(self.error(format!("'{}' no empieza ningún valor", c))),
+            None => Err(self.error("falta el valor".to_string())),
+        }
+    }
+
+    fn basic_string(&mut self) -> Result<String, TomlError> {
+        self.index += 1;
+        let mut text = String::new();
+        loop {
+            match self.peek() {
+                None | Some('\n') => return Err(self.error("la cadena no se cierra".to_string())),
+                Some('"') => {
+                    self.index += 1;
+                    return Ok(text);
+                }
+                Some('\\') => {
+                    self.index += 1;
+                    let escaped = match self.peek() {
+                        Some('n') => '\n',
+                        Some('t') => '\t',
+                        Some('r') => '\r',
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('u') => {
+                            let digits: String = self.chars.iter().skip(self.index + 1).take(4).collect();
+                            let code = u32::from_str_radix(&digits, 16).ok().and_then(char::from_u32);
+                            self.index += 4;
+                            code.ok_or_else(|| self.error(format!("escape \\u{} inválido", digits)))?
+                        }
+                        other => return Err(self.error(format!("escape \\{} desconocido", other.unwrap_or(' ')))),
+                    };
+                    self.index += 1;
+                    text.push(escaped);
+                }
+                Some(c) => {
+                    self.index += 1;
+                    text.push(c);
+                }
+            }
+        }
+    }
+
+    fn literal_string(&mut self) -> Result<String, TomlError> {
+        self.index += 1;
+        let start = self.index;
+        while !matches!(self.peek(), None | Some('\n' | '\'')) {
+            self.index += 1;
+        }
+        if !self.eat('\'') {
+            return Err(self.error("la cadena no se cierra".to_string()));
+        }
+        Ok(self.chars[start..self.index - 1].iter().collect())
+    }
+
+    fn number(&mut self) -> Result<Value, TomlError> {
+        let start = self.index;
+        while matches!(self.peek(), Some(c) if c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.' | '_')) {
+            self.index += 1;
+        }
+        let text: String = self.chars[start..self.index].iter().filter(|&&c| c != '_').collect();
+        if let Ok(integer) = text.parse::<i64>() {
+            return Ok(Value::Number(integer.into()));
+        }
+        text.parse::<f64>()
+            .ok()
+            .and_then(Number::from_f64)
+            .map(Value::Number)
+            .ok_or_else(|| self.error(format!("`{}` no es un número", text)))
+    }
+
+    fn array(&mut self) -> Result<Value, TomlError> {
+        self.index += 1;
+        let mut items = Vec::new();
+        loop {
+            self.skip_blank_lines();
+            if self.eat(']') {
+                return Ok(Value::Array(items));
+            }
+            items.push(self.value()?);
+            self.skip_blank_lines();
+            if !self.eat(',') {
+                self.skip_blank_lines();
+                return self.expect(']').map(|_| Value::Array(items));
+            }
+        }
+    }
+
+    fn inline_table(&mut self) -> Result<Value, TomlError> {
+        self.index += 1;
+        let mut table = Map::new();
+        self.skip_spaces();
+        if self.eat('}') {
+            return Ok(Value::Object(table));
+        }
+        loop {
+            let (path, value) = self.key_value()?;
+            insert(&mut table, &path, value, self.line)?;
+            self.skip_spaces();
+            if !self.eat(',') {
+                return self.expect('}').map(|_| Value::Object(table));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn error_line(text: &str) -> usize {
+        parse(text).expect_err("debería fallar").line
+    }
+
+    #[test]
+    fn accepts_the_supported_subset() {
+        let text = r##"
+# comentario
+title = "Sistema \"solar\"\u00e1"   # al final de la línea
+path = 'C:\sin\escapes'
+count = 1_000
+negative = -3
+ratio = 2.5e-1
+enabled = true
+colors = [
+    "#ff0000",  # rojo
+    "#00ff00",
+]
+point = { x = 1, y = -2.0 }
+camera.eye.z = 10
+
+[sun]
+radius = 2.0
+
+[sun.glow]
+size = 3
+"quoted key" = [[1, 2], []]
+
+[[planets]]
+name = "Tierra"
+[planets.ring]
+inner = 1.5
+
+[[planets]]
+name = "Marte"
+[planets.ring]
+inner = 2
+"##;
+        let value = parse(text).unwrap();
+        assert_eq!(value, json!({
+            "title": "Sistema \"solar\"á",
+            "path": "C:\\sin\\escapes",
+            "count": 1000,
+            "negative": -3,
+            "ratio": 0.25,
+            "enabled": true,
+            "colors": ["#ff0000", "#00ff00"],
+            "point": { "x": 1, "y": -2.0 },
+            "camera": { "eye": { "z": 10 } },
+            "sun": { "radius": 2.0, "glow": { "size": 3, "quoted key": [[1, 2], []] } },
+            "planets": [
+                { "name": "Tierra", "ring": { "inner": 1.5 } },
+                { "name": "Marte", "ring": { "inner": 2 } },
+            ],
+        }));
+
+        // Una tabla madre se puede abrir después de sus subtablas
+        assert_eq!(parse("[a.b]\nx = 1\n[a]\ny = 2\n").unwrap(), json!({ "a": { "b": { "x": 1 }, "y": 2 } }));
+        assert_eq!(parse("").unwrap(), json!({}));
+        // La escena que viene con el proyecto
+        parse(include_str!("../scene.toml")).unwrap();
+    }
+
+    #[test]
+    fn rejects_repeated_tables_and_keys() {
+        assert_eq!(error_line("[sun]\nradius = 1\n\n[sun]\nmass = 2\n"), 4);
+        assert_eq!(error_line("[a.b]\n[a.b]\n"), 2);
+        assert_eq!(error_line("[[planets]]\n[planets.ring]\n[planets.ring]\n"), 3);
+        assert_eq!(error_line("x = 1\nx = 2\n"), 2);
+        assert_eq!(error_line("[a]\nx = 1\n[a.x]\n"), 3);
+        assert_eq!(error_line("point = { x = 1, x = 2 }\n"), 1);
+        assert_eq!(error_line("[a]\n[[a]]\n"), 2);
+        assert_eq!(error_line("[[a]]\n[a]\n"), 2);
+    }
+
+    #[test]
+    fn rejects_malformed_lines() {
+        assert_eq!(error_line("name = \"sin cerrar\n"), 1);
+        assert_eq!(error_line("\nname = 'sin cerrar\n"), 2);
+        assert_eq!(error_line("name = \"\\q\"\n"), 1);
+        assert_eq!(error_line("name = \"\\uzzzz\"\n"), 1);
+        assert_eq!(error_line("name =\n"), 1);
+        assert_eq!(error_line("name = Tierra\n"), 1);
+        assert_eq!(error_line("radius = 1 2\n"), 1);
+        assert_eq!(error_line("radius = 1.2.3\n"), 1);
+        assert_eq!(error_line("= 1\n"), 1);
+        assert_eq!(error_line("[sun\n"), 1);
+        assert_eq!(error_line("[[sun]\n"), 1);
+        assert_eq!(error_line("list = [1, 2\n"), 2);
+        assert_eq!(error_line("point = { x = 1\n"), 1);
+    }
+}
@@ -1,17 +1,29 @@
 use nalgebra_glm::{Vec3, dot, Vec2};
 use crate::fragments::Fragments;
 use crate::vertex::Vertex;
+use crate::framebuffer::Rect;
 
-pub fn triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex) -> Vec<Fragments> {
+// Área mínima (en pixeles², el doble del área real) para rasterizar un triángulo
+const MIN_TRIANGLE_AREA: f32 = 1e-4;
+
+// Rasteriza solo los pixeles del triángulo que caen dentro de `rect` (un tile)
+pub fn triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex, rect: &Rect) -> Vec<Fragments> {
   let mut fragments = Vec::new();
   let (a, b, c) = (v1.transformed_position, v2.transformed_position, v3.transformed_position);
 
+  // Triángulos degenerados (colineales, coincidentes o sub-pixel) no aportan
+  // fragmentos y harían explotar las coordenadas baricéntricas
+  let triangle_area = edge_function(&a, &b, &c);
+  if ![v1, v2, v3].iter().all(|v| is_finite_vertex(v)) || triangle_area.abs() < MIN_TRIANGLE_AREA {
+    return fragments;
+  }
+
   let (min_x, min_y, max_x, max_y) = calculate_bounding_box(&a, &b, &c);
+  let (min_x, min_y) = (min_x.max(rect.x0 as i32), min_y.max(rect.y0 as i32));
+  let (max_x, max_y) = (max_x.min(rect.x1 as i32 - 1), max_y.min(rect.y1 as i32 - 1));
 
   let light_dir = Vec3::new(0.0, 0.0, 1.0);
 
-  let triangle_area = edge_function(&a, &b, &c);
-
   // Iterate over each pixel in the bounding box
   for y in min_y..=max_y {
     for x in min_x..=max_x {
@@ -21,30 +33,14 @@ pub fn triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex) -> Vec<Fragments> {
       let (w1, w2, w3) = barycentric_coordinates(&point, &a, &b, &c, triangle_area);
 
       // Check if the point is inside the triangle
-      if w1 >= 0.0 && w1 <= 1.0 && 
-         w2 >= 0.0 && w2 <= 1.0 &&
-         w3 >= 0.0 && w3 <= 1.0 {
-
-        // Interpolate normal
-        let normal = v1.transformed_normal * w1 + v2.transformed_normal * w2 + v3.transformed_normal * w3;
-        let normal = normal.normalize();
-
-        // Calculate lighting intensity
-        let intensity = dot(&normal, &light_dir).max(0.0);
-
-        // Interpolate depth
-        let depth = a.z * w1 + b.z * w2 + c.z * w3;
-
-        // Positions of the original vertex
-        let vertex_position = v1.position * w1 + v2.position * w2 + v3.position * w3;
-
-        fragments.push(Fragments::new(
-            Vec2::new(x as f32, y as f32),
-            depth,
-            normal,
-            intensity,
-            vertex_position,
-        ));
+      if (0.0..=1.0).contains(&w1) &&
+         (0.0..=1.0).contains(&w2) &&
+         (0.0..=1.0).contains(&w3) {
+        // Los pesos se recortan a [0, 1] y se renormalizan para absorber el error de redondeo
+        let (w1, w2, w3) = (w1.clamp(0.0, 1.0), w2.clamp(0.0, 1.0), w3.clamp(0.0, 1.0));
+        let sum = w1 + w2 + w3;
+        let (w1, w2, w3) = (w1 / sum, w2 / sum, w3 / sum);
+        fragments.push(interpolate_fragment([v1, v2, v3], [w1, w2, w3], x, y, &light_dir));
       }
     }
   }
@@ -52,6 +48,101 @@ pub fn triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex) -> Vec<Fragments> {
   fragments
 }
 
+// Rasteriza una arista con DDA, un fragmento por pixel a lo largo del eje dominante
+pub fn line(v1: &Vertex, v2: &Vertex) -> Vec<Fragments> {
+  let (a, b) = (v1.transformed_position, v2.transformed_position);
+  let light_dir = Vec3::new(0.0, 0.0, 1.0);
+
+  let steps = (b.x - a.x).abs().max((b.y - a.y).abs()).ceil().max(1.0) as usize;
+  (0..=steps)
+    .map(|i| {
+      let t = i as f32 / steps as f32;
+      let x = (a.x + (b.x - a.x) * t).floor() as i32;
+      let y = (a.y + (b.y - a.y) * t).floor() as i32;
+      interpolate_fragment([v1, v2, v1], [1.0 - t, t, 0.0], x, y, &light_dir)
+    })
+    .collect()
+}
+
+// Un único fragmento en la posición proyectada del vértice
+pub fn point(v: &Vertex) -> Fragments {
+  let p = v.transformed_position;
+  let light_dir = Vec3::new(0.0, 0.0, 1.0);
+  interpolate_fragment([v, v, v], [1.0, 0.0, 0.0], p.x.floor() as i32, p.y.floor() as i32, &light_dir)
+}
+
+// Construye un fragmento a partir de pesos baricéntricos en pantalla
+fn interpolate_fragment(v: [&Vertex; 3], weights: [f32; 3], x: i32, y: i32, light_dir: &Vec3) -> Fragments {
+  let [w1, w2, w3] = weights;
+  let (a, b, c) = (v[0].transformed_position, v[1].transformed_position, v[2].transformed_position);
+
+  // Pesos corregidos por perspectiva con el 1/w de cada vértice (la profundidad sí es lineal en pantalla)
+  let (p1, p2, p3) = (w1 / v[0].clip_position.w, w2 / v[1].clip_position.w, w3 / v[2].clip_position.w);
+  let sum = p1 + p2 + p3;
+  let (p1, p2, p3) = if sum > 0.0 { (p1 / sum, p2 / sum, p3 / sum) } else { (w1, w2, w3) };
+
+  // La w interpolada con corrección de perspectiva es 1 / suma de los pesos w/w
+  let view_depth = if sum > 0.0 { 1.0 / sum } else { v[0].clip_position.w * w1 + v[1].clip_position.w * w2 + v[2].clip_position.w * w3 };
+
+  // Interpolate normal
+  let normal = v[0].transformed_normal * p1 + v[1].transformed_normal * p2 + v[2].transformed_normal * p3;
+  let normal = normal.try_normalize(f32::EPSILON).unwrap_or(Vec3::new(0.0, 0.0, 1.0));
+
+  // Calculate lighting intensity
+  let intensity = dot(&normal, light_dir).max(0.0);
+
+  // Interpolate depth
+  let depth = a.z * w1 + b.z * w2 + c.z * w3;
+
+  // Positions of the original vertex
+  let vertex_position = v[0].position * p1 + v[1].position * p2 + v[2].position * p3;
+  let world_position = v[0].world_position * p1 + v[1].world_position * p2 + v[2].world_position * p3;
+
+  let tex_coords = v[0].tex_coords * p1 + v[1].tex_coords * p2 + v[2].tex_coords * p3;
+  let color = v[0].color * p1 + v[1].color * p2 + v[2].color * p3;
+
+  Fragments {
+      position: Vec2::new(x as f32, y as f32),
+      depth,
+      view_depth,
+      normal,
+      intensity,
+      vertex_pos: vertex_position,
+      world_position,
+      tex_coords,
+      color,
+      material: v[0].material,
+  }
+}
+
+// Las caras frontales (CCW en NDC) quedan con área positiva en pantalla,
+// porque el viewport invierte el eje Y
+pub fn is_front_facing(tri: &[Vertex; 3]) -> bool {
+  let (a, b, c) = (tri[0].transformed_position, tri[1].transformed_position, tri[2].transformed_position);
+  edge_function(&a, &b, &c) > 0.0
+}
+
+// Todas las coordenadas proyectadas son números finitos (sin NaN ni infinitos)
+pub fn is_finite(tri: &[Vertex; 3]) -> bool {
+  tri.iter().all(is_finite_vertex)
+}
+
+fn is_finite_vertex(v: &Vertex) -> bool {
+  let p = v.transformed_position;
+  p.x.is_finite() && p.y.is_finite() && p.z.is_finite() && v.clip_position.w > 0.0
+}
+
+// Bounding box en pantalla (min_x, min_y, max_x, max_y) usado para repartir en tiles
+pub fn screen_bounds(tri: &[Vertex; 3]) -> (f32, f32, f32, f32) {
+  let (a, b, c) = (tri[0].transformed_position, tri[1].transformed_position, tri[2].transformed_position);
+  (
+    a.x.min(b.x).min(c.x).floor(),
+    a.y.min(b.y).min(c.y).floor(),
+    a.x.max(b.x).max(c.x).ceil(),
+    a.y.max(b.y).max(c.y).ceil(),
+  )
+}
+
 fn calculate_bounding_box(v1: &Vec3, v2: &Vec3, v3: &Vec3) -> (i32, i32, i32, i32) {
     let min_x = v1.x.min(v2.x).min(v3.x).floor() as i32;
     let min_y = v1.y.min(v2.y).min(v3.y).floor() as i32;
@@ -73,4 +164,80 @@ fn edge_function(a: &Vec3, b: &Vec3, c: &Vec3) -> f32 {
     (c.x - a.x) * (b.y - a.y) - (c.y - a.y) * (b.x - a.x)
 }
 
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use rand::rngs::StdRng;
+  use rand::{Rng, SeedableRng};
+
+  const SIZE: usize = 32;
+
+  fn vertex(x: f32, y: f32, depth: f32) -> Vertex {
+    Vertex::new(Vec3::new(x, y, depth), Vec3::z(), Vec2::zeros())
+  }
 
+  // Ningún fragmento fuera del bounding box entero del triángulo ni del tile, y todas las
+  // profundidades son números entre las de los vértices
+  fn check(tri: [Vertex; 3]) {
+    let rect = Rect { x0: 0, y0: 0, x1: SIZE, y1: SIZE };
+    let (a, b, c) = (tri[0].transformed_position, tri[1].transformed_position, tri[2].transformed_position);
+    let (min_x, min_y, max_x, max_y) = calculate_bounding_box(&a, &b, &c);
+    let (near, far) = (a.z.min(b.z).min(c.z), a.z.max(b.z).max(c.z));
+    for fragment in triangle(&tri[0], &tri[1], &tri[2], &rect) {
+      let (x, y) = (fragment.position.x as i32, fragment.position.y as i32);
+      assert!(x >= min_x && x <= max_x && y >= min_y && y <= max_y, "({}, {}) fuera de {:?}", x, y, (a, b, c));
+      assert!(rect.contains(fragment.position.x, fragment.position.y));
+      assert!(fragment.depth.is_finite() && fragment.depth >= near - 1e-4 && fragment.depth <= far + 1e-4, "profundidad {}", fragment.depth);
+    }
+  }
+
+  #[test]
+  fn random_triangles_stay_in_bounds() {
+    let mut rng = StdRng::seed_from_u64(12);
+    let coordinate = |rng: &mut StdRng| rng.gen_range(-8.0..SIZE as f32 + 8.0);
+    for _ in 0..3000 {
+      let tri = [(); 3].map(|_| vertex(coordinate(&mut rng), coordinate(&mut rng), rng.gen_range(0.0..1.0)));
+      check(tri);
+    }
+  }
+
+  #[test]
+  fn collinear_and_coincident_triangles_emit_nothing() {
+    let rect = Rect { x0: 0, y0: 0, x1: SIZE, y1: SIZE };
+    let mut rng = StdRng::seed_from_u64(7);
+    for _ in 0..2000 {
+      let (p, q) = (Vec3::new(rng.gen_range(0.0..SIZE as f32), rng.gen_range(0.0..SIZE as f32), 0.5), Vec3::new(rng.gen_range(0.0..SIZE as f32), rng.gen_range(0.0..SIZE as f32), 0.5));
+      let t: f32 = rng.gen_range(-1.0..2.0);
+      let on_line = p + (q - p) * t;
+      for tri in [[p, q, on_line], [p, p, q], [p, p, p]] {
+        let tri = tri.map(|v| vertex(v.x, v.y, v.z));
+        check(tri.clone());
+        // Con área (casi) nula: lo que quede es a lo sumo redondeo sobre la recta
+        if edge_function(&tri[0].transformed_position, &tri[1].transformed_position, &tri[2].transformed_position).abs() < MIN_TRIANGLE_AREA {
+          assert!(triangle(&tri[0], &tri[1], &tri[2], &rect).is_empty());
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn slivers_do_not_panic() {
+    let mut rng = StdRng::seed_from_u64(3);
+    for _ in 0..2000 {
+      let (x, y) = (rng.gen_range(0.0..SIZE as f32), rng.gen_range(0.0..SIZE as f32));
+      let offset = rng.gen_range(1e-3..1e-1);
+      check([vertex(x, y, 0.1), vertex(x + 20.0, y + 15.0, 0.9), vertex(x + 20.0 + offset, y + 15.0, 0.4)]);
+    }
+  }
+
+  #[test]
+  fn covered_pixels_are_inside() {
+    // Triángulo rectángulo sobre la mitad de un cuadrado de 10x10: los centros con x + y <= 9,
+    // la diagonal incluida
+    let tri = [vertex(0.0, 0.0, 0.5), vertex(10.0, 0.0, 0.5), vertex(0.0, 10.0, 0.5)];
+    let rect = Rect { x0: 0, y0: 0, x1: SIZE, y1: SIZE };
+    let fragments = triangle(&tri[0], &tri[1], &tri[2], &rect);
+    assert!(fragments.iter().all(|fragment| fragment.position.x + fragment.position.y + 1.0 <= 10.0));
+    assert_eq!(fragments.len(), 55);
+  }
+}
@@ -1,4 +1,4 @@
-use nalgebra_glm::{Vec2, Vec3};
+use nalgebra_glm::{Vec2, Vec3, Vec4};
 use crate::color::Color;
 
 #[derive(Clone, Debug)]
@@ -7,8 +7,13 @@ pub struct Vertex {
   pub normal: Vec3,
   pub tex_coords: Vec2,
   pub color: Color,
+  pub clip_position: Vec4,
   pub transformed_position: Vec3,
   pub transformed_normal: Vec3,
+  // Posición en el mundo (model_matrix * position), para la iluminación
+  pub world_position: Vec3,
+  // Material del modelo (Model::materials) de la cara; es el mismo en sus tres vértices
+  pub material: Option<u32>,
 }
 
 impl Vertex {
@@ -18,8 +23,26 @@ impl Vertex {
       normal,
       tex_coords,
       color: Color::BLACK,
+      clip_position: Vec4::new(position.x, position.y, position.z, 1.0),
       transformed_position: position,
       transformed_normal: normal,
+      world_position: position,
+      material: None,
+    }
+  }
+
+  // Interpola todos los atributos entre dos vértices (usado al recortar triángulos)
+  pub fn lerp(&self, other: &Vertex, t: f32) -> Vertex {
+    Vertex {
+      position: self.position + (other.position - self.position) * t,
+      normal: self.normal + (other.normal - self.normal) * t,
+      tex_coords: self.tex_coords + (other.tex_coords - self.tex_coords) * t,
+      color: self.color.lerp(&other.color, t),
+      clip_position: self.clip_position + (other.clip_position - self.clip_position) * t,
+      transformed_position: self.transformed_position + (other.transformed_position - self.transformed_position) * t,
+      transformed_normal: self.transformed_normal + (other.transformed_normal - self.transformed_normal) * t,
+      world_position: self.world_position + (other.world_position - self.world_position) * t,
+      material: self.material,
     }
   }
 }
@@ -31,8 +54,11 @@ impl Default for Vertex {
       normal: Vec3::new(0.0, 1.0, 0.0),
       tex_coords: Vec2::new(0.0, 0.0),
       color: Color::BLACK,
+      clip_position: Vec4::new(0.0, 0.0, 0.0, 1.0),
       transformed_position: Vec3::new(0.0, 0.0, 0.0),
       transformed_normal: Vec3::new(0.0, 1.0, 0.0),
+      world_position: Vec3::new(0.0, 0.0, 0.0),
+      material: None,
     }
   }
 }